@@ -0,0 +1,106 @@
+use serde::Serialize;
+
+use crate::models::skill::SkillCategory;
+use crate::models::UserProfile;
+
+/// A skill's score next to the baseline's score for the same skill.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SkillDelta {
+    pub skill: String,
+    /// Properly cased name for reports, e.g. "JavaScript"
+    pub display_name: String,
+    pub category: SkillCategory,
+    pub score: u8,
+    /// `None` when the baseline never showed the skill
+    pub baseline_score: Option<u8>,
+    /// `score - baseline_score`, when the baseline has the skill
+    pub delta: Option<i16>,
+}
+
+/// A profile's skill scores relative to a reference profile, e.g. a known
+/// senior engineer, which reads more easily than percentiles from a small
+/// database.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CalibratedProfile {
+    /// Login of the baseline profile
+    pub baseline: String,
+    /// One entry per skill in the profile, in the profile's order
+    pub skills: Vec<SkillDelta>,
+    /// Display names of the baseline skills the profile does not show, in
+    /// the baseline's order
+    pub baseline_only: Vec<String>,
+}
+
+impl CalibratedProfile {
+    /// The delta for the skill named `skill`, ignoring case.
+    pub fn delta_for(&self, skill: &str) -> Option<&SkillDelta> {
+        self.skills.iter().find(|d| d.skill.eq_ignore_ascii_case(skill))
+    }
+}
+
+/// Compares each of `profile`'s skills with the same skill in `baseline`.
+/// Skills are matched by name, ignoring case.
+pub fn calibrate(profile: &UserProfile, baseline: &UserProfile) -> CalibratedProfile {
+    let baseline_score = |name: &str| {
+        baseline
+            .skills
+            .iter()
+            .find(|s| s.skill.name.eq_ignore_ascii_case(name))
+            .map(|s| s.proficiency_score)
+    };
+
+    let skills = profile
+        .skills
+        .iter()
+        .map(|rating| {
+            let baseline_score = baseline_score(&rating.skill.name);
+            SkillDelta {
+                skill: rating.skill.name.clone(),
+                display_name: rating.skill.display_name.clone(),
+                category: rating.skill.category.clone(),
+                score: rating.proficiency_score,
+                baseline_score,
+                delta: baseline_score.map(|b| rating.proficiency_score as i16 - b as i16),
+            }
+        })
+        .collect();
+
+    let baseline_only = baseline
+        .skills
+        .iter()
+        .filter(|b| {
+            !profile
+                .skills
+                .iter()
+                .any(|s| s.skill.name.eq_ignore_ascii_case(&b.skill.name))
+        })
+        .map(|b| b.skill.display_name.clone())
+        .collect();
+
+    CalibratedProfile {
+        baseline: baseline.user.login.clone(),
+        skills,
+        baseline_only,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::profile;
+
+    #[test]
+    fn test_calibrate_against_baseline() {
+        let candidate = profile("octocat", &[("Rust", 80), ("Go", 40), ("Elm", 55)]);
+        let baseline = profile("torvalds", &[("rust", 68), ("Go", 60), ("C", 95), ("haskell", 70)]);
+
+        let calibrated = calibrate(&candidate, &baseline);
+        assert_eq!(calibrated.baseline, "torvalds");
+        let rust = calibrated.delta_for("rust").unwrap();
+        assert_eq!((rust.display_name.as_str(), rust.delta), ("Rust", Some(12)));
+        assert_eq!(calibrated.delta_for("Go").unwrap().delta, Some(-20));
+        let elm = calibrated.delta_for("Elm").unwrap();
+        assert_eq!((elm.baseline_score, elm.delta), (None, None));
+        assert_eq!(calibrated.baseline_only, vec!["C", "Haskell"]);
+    }
+}
@@ -0,0 +1,16 @@
+use crate::models::UserProfile;
+
+/// Adjusts a finished profile before it is saved, e.g. to boost skills that
+/// match a job description. Register hooks with
+/// `AnalysisPipeline::with_hook`; they run in registration order.
+pub trait AnalysisHook: Send + Sync {
+    /// Called with the complete profile just before it is saved. Does
+    /// nothing by default.
+    fn on_profile(&self, _profile: &mut UserProfile) {}
+}
+
+/// Hook that leaves the profile unchanged.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopHook;
+
+impl AnalysisHook for NoopHook {}
@@ -1,7 +1,20 @@
+pub mod calibration;
+pub mod hooks;
 pub mod pipeline;
 pub mod skill_extractor;
 pub mod rating_engine;
+pub mod recommendations;
+pub mod scoring;
 
-pub use pipeline::AnalysisPipeline;
-pub use skill_extractor::SkillExtractor;
-pub use rating_engine::RatingEngine;
+pub use calibration::{calibrate, CalibratedProfile, SkillDelta};
+pub use hooks::{AnalysisHook, NoopHook};
+pub use pipeline::{
+    AnalysesCallback, AnalysisPipeline, AnalysisPipelineBuilder, SkillCallback, LARGE_REPO_FACTOR,
+};
+pub use skill_extractor::{NormalizationEntry, SkillExtractor};
+pub use rating_engine::{
+    classify_commit, commit_language_breakdown, contribution_breakdown, modal_utc_offset,
+    process_metrics, repository_language_breakdown, top_by_category, working_hours, AnalysisStats,
+    RatingEngine, CONTRIBUTION_TYPES,
+};
+pub use scoring::{ScoringContext, ScoringStrategy, WeightedScoring};
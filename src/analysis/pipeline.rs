@@ -1,20 +1,51 @@
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use chrono::Utc;
 use futures::future::join_all;
+use futures::stream::{self, Stream, StreamExt};
 use indicatif::{ProgressBar, ProgressStyle};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use tokio::sync::Semaphore;
+use tokio_util::sync::CancellationToken;
 
-use crate::config::PipelineConfig;
-use crate::error::Result;
-use crate::github::GitHubClient;
-use crate::llm::{AnalysisContext, AnalysisRequest, CommitBatcher, LLMProvider};
+use crate::config::{PipelineConfig, MAX_GITHUB_REQUESTS_IN_FLIGHT};
+use crate::error::{Error, Result};
+use crate::github::{missing_scopes, Conditional, GitHubClient};
+use crate::llm::{
+    default_reserved_tokens, AnalysisContext, AnalysisRequest, CommitBatcher, LLMProvider, TokenUsage,
+};
+use crate::llm::prompts::JSON_REPROMPT;
 use crate::models::analysis::LLMAnalysisResult;
-use crate::models::commit::{CommitForAnalysis, FileForAnalysis};
-use crate::models::{Commit, Repository, UserProfile};
+use crate::models::commit::{CommitForAnalysis, CommitSummary, FileForAnalysis};
+use crate::models::skill::SkillRating;
+use crate::models::{
+    AccountType, AnalysisWarning, Commit, GitHubUser, ProfileSummary, ProfileWithWarnings, PullRequest,
+    Repository, RunDiagnostics,
+    SkippedRepository, UserProfile, PROFILE_SCHEMA_VERSION,
+};
+use crate::analysis::hooks::AnalysisHook;
 use crate::analysis::skill_extractor::SkillExtractor;
-use crate::analysis::rating_engine::RatingEngine;
-use crate::storage::Storage;
-use crate::taxonomy::detect_language;
+use crate::analysis::rating_engine::{
+    commit_language_breakdown, contribution_breakdown, modal_utc_offset, process_metrics,
+    repository_language_breakdown, working_hours, AnalysisStats, RatingEngine, RatingWeights,
+};
+use crate::analysis::scoring::ScoringStrategy;
+use crate::storage::{CachedResponse, Storage};
+use crate::taxonomy::{
+    detect_language, detect_security_change, LanguageCoverage, SkillTaxonomy, SECURITY_SKILL,
+};
+
+/// A repository counts as large when the user has more than this many times
+/// `max_commits_per_repo` commits in it. Large repositories are capped at
+/// `max_commits_big_repo` instead, and truncating them is reported.
+pub const LARGE_REPO_FACTOR: u32 = 10;
+
+/// Attempts at listing the user's repositories before giving up on network
+/// errors. Each retry resumes from the last page that was fetched.
+const REPO_LISTING_ATTEMPTS: u32 = 3;
 
 pub struct AnalysisPipeline {
     github: Arc<GitHubClient>,
@@ -24,73 +55,486 @@ pub struct AnalysisPipeline {
     rating_engine: RatingEngine,
     storage: Storage,
     config: PipelineConfig,
+    shutdown: CancellationToken,
+    /// Counters of the current or most recent run for each user, kept up to
+    /// date as it goes so they survive a run that fails
+    diagnostics: Mutex<HashMap<String, RunDiagnostics>>,
+    on_skill_rated: Option<SkillCallback>,
+    on_analyses: Option<AnalysesCallback>,
+    hooks: Vec<Box<dyn AnalysisHook>>,
+    show_progress: bool,
+}
+
+/// Progress of one pipeline stage. Without a bar, an info line is logged at
+/// every tenth of `len` and at the end, which reads well in CI logs and when
+/// stderr is redirected to a file.
+#[derive(Clone)]
+struct Progress {
+    bar: Option<ProgressBar>,
+    len: usize,
+    unit: &'static str,
+    done: Arc<AtomicUsize>,
+}
+
+impl Progress {
+    fn inc(&self) {
+        let done = self.done.fetch_add(1, Ordering::SeqCst) + 1;
+        match &self.bar {
+            Some(bar) => bar.inc(1),
+            None if done == self.len || done.is_multiple_of((self.len / 10).max(1)) => {
+                tracing::info!("Progress: {}/{} {}", done, self.len, self.unit);
+            }
+            None => {}
+        }
+    }
+
+    fn position(&self) -> usize {
+        self.done.load(Ordering::SeqCst)
+    }
+
+    fn finish(&self, message: &'static str) {
+        if let Some(bar) = &self.bar {
+            bar.finish_with_message(message);
+        }
+    }
 }
 
+/// Called with each skill rating as soon as it is computed.
+pub type SkillCallback = Arc<dyn Fn(&SkillRating) + Send + Sync>;
+
+/// Called once with the raw LLM analyses, before skills are aggregated.
+pub type AnalysesCallback = Arc<dyn Fn(&[LLMAnalysisResult]) + Send + Sync>;
+
 impl AnalysisPipeline {
+    /// Fails with a configuration error if the configured signal scores are
+    /// outside 0-100.
     pub fn new(
         github: GitHubClient,
         llm: impl LLMProvider + 'static,
         storage: Storage,
         config: PipelineConfig,
-    ) -> Self {
+    ) -> Result<Self> {
+        Self::from_parts(github, Arc::new(llm), storage, config)
+    }
+
+    /// Starts an `AnalysisPipelineBuilder`, an alternative to `new` that also
+    /// takes the optional extension points.
+    pub fn builder() -> AnalysisPipelineBuilder {
+        AnalysisPipelineBuilder::default()
+    }
+
+    fn from_parts(
+        github: GitHubClient,
+        llm: Arc<dyn LLMProvider>,
+        storage: Storage,
+        config: PipelineConfig,
+    ) -> Result<Self> {
         let max_tokens = llm.max_context_tokens();
-        Self {
+        let reserved_tokens = config
+            .reserved_tokens
+            .unwrap_or_else(|| {
+                default_reserved_tokens(max_tokens, llm.max_output_tokens(), llm.system_prompt())
+            });
+        if reserved_tokens >= max_tokens {
+            tracing::warn!(
+                "Reserved tokens ({}) fill the {} token context; commit diffs will be dropped",
+                reserved_tokens,
+                max_tokens
+            );
+        }
+        let weights = RatingWeights {
+            breadth_weight: if config.breadth_factor {
+                RatingWeights::default().breadth_weight
+            } else {
+                0.0
+            },
+            neutral_baseline: config.neutral_baseline,
+            age_decay_half_life_days: config.age_decay_half_life_days,
+            frequency_basis: config.frequency_basis,
+            ..RatingWeights::default()
+        };
+        let weights = RatingWeights {
+            unknown_signal_score: config
+                .unknown_signal_score
+                .unwrap_or(weights.unknown_signal_score),
+            signal_scores: weights
+                .signal_scores
+                .into_iter()
+                .chain(config.signal_scores.iter().map(|(level, score)| (level.to_lowercase(), *score)))
+                .collect(),
+            ..weights
+        };
+        Ok(Self {
             github: Arc::new(github),
-            llm: Arc::new(llm),
-            batcher: CommitBatcher::new(max_tokens),
-            skill_extractor: SkillExtractor::new(),
-            rating_engine: RatingEngine::new(),
+            llm,
+            batcher: CommitBatcher::new(max_tokens, reserved_tokens)
+                .with_max_diff_chars(config.per_file_diff_limit)
+                .with_max_commits_per_batch(config.max_commits_per_batch),
+            skill_extractor: SkillExtractor::with_taxonomy(SkillTaxonomy::with_extensions(
+                &config.taxonomy,
+                &config.skill_aliases,
+            )),
+            rating_engine: RatingEngine::with_weights(weights)?
+                .with_min_commits_per_skill(config.min_commits_per_skill)
+                .with_as_of(config.as_of),
             storage,
             config,
+            shutdown: CancellationToken::new(),
+            diagnostics: Mutex::default(),
+            on_skill_rated: None,
+            on_analyses: None,
+            hooks: Vec::new(),
+            show_progress: true,
+        })
+    }
+
+    /// Replaces the default `WeightedScoring` used to rate each skill. The
+    /// configured `min_commits_per_skill` filter and `as_of` date still apply.
+    pub fn with_scoring_strategy(mut self, strategy: Box<dyn ScoringStrategy>) -> Self {
+        self.rating_engine = RatingEngine::with_strategy(strategy)
+            .with_min_commits_per_skill(self.config.min_commits_per_skill)
+            .with_as_of(self.config.as_of);
+        self
+    }
+
+    /// Registers a callback that receives each skill rating as soon as the
+    /// rating engine produces it, before the summary is generated.
+    pub fn with_skill_callback(
+        mut self,
+        callback: impl Fn(&SkillRating) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_skill_rated = Some(Arc::new(callback));
+        self
+    }
+
+    /// Registers a callback that receives the successful LLM analyses as
+    /// returned, before their skill names are normalized and aggregated.
+    /// The only change is the `security` domain signal, added to batches with
+    /// security-sensitive changes.
+    pub fn with_analyses_callback(
+        mut self,
+        callback: impl Fn(&[LLMAnalysisResult]) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_analyses = Some(Arc::new(callback));
+        self
+    }
+
+    /// Registers a hook that may modify the finished profile before it is
+    /// saved. Hooks run in the order they were registered.
+    pub fn with_hook(mut self, hook: Box<dyn AnalysisHook>) -> Self {
+        self.hooks.push(hook);
+        self
+    }
+
+    /// Whether to draw progress bars while fetching commits and running LLM
+    /// batches. On by default; when off, progress is logged every tenth of
+    /// the way instead.
+    pub fn with_progress(mut self, show: bool) -> Self {
+        self.show_progress = show;
+        self
+    }
+
+    /// Progress through `len` steps named `unit` (e.g. "repos"), as a bar
+    /// when progress is turned on, otherwise as `tracing` lines.
+    fn progress(&self, len: usize, unit: &'static str) -> Progress {
+        let bar = self.show_progress.then(|| {
+            let bar = ProgressBar::new(len as u64);
+            bar.set_style(
+                ProgressStyle::default_bar()
+                    .template(&format!(
+                        "{{spinner:.green}} [{{elapsed_precise}}] [{{bar:40.cyan/blue}}] {{pos}}/{{len}} {}",
+                        unit
+                    ))
+                    .unwrap()
+                    .progress_chars("#>-"),
+            );
+            bar
+        });
+        Progress {
+            bar,
+            len,
+            unit,
+            done: Arc::new(AtomicUsize::new(0)),
         }
     }
 
-    pub async fn analyze_user(&self, username: &str) -> Result<UserProfile> {
+    /// Token that, once cancelled, stops the pipeline from starting new work.
+    /// In-flight requests finish and a partial profile is saved and returned
+    /// with `incomplete` set.
+    pub fn shutdown_token(&self) -> CancellationToken {
+        self.shutdown.clone()
+    }
+
+    /// Counters of the current or most recent `analyze_user` run for
+    /// `username` (or `analyze_from_commits` run for that login). After a
+    /// run fails, they show how far it got.
+    pub fn diagnostics(&self, username: &str) -> RunDiagnostics {
+        self.diagnostics
+            .lock()
+            .unwrap()
+            .get(&username.to_lowercase())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn reset_diagnostics(&self, username: &str) {
+        self.diagnostics
+            .lock()
+            .unwrap()
+            .insert(username.to_lowercase(), RunDiagnostics::default());
+    }
+
+    fn record_diagnostics(&self, username: &str, update: impl FnOnce(&mut RunDiagnostics)) {
+        update(self.diagnostics.lock().unwrap().entry(username.to_lowercase()).or_default());
+    }
+
+    /// Fetches and analyzes a user's commits. Repositories, commits and LLM
+    /// batches that fail are left out and reported in the returned warnings;
+    /// only failures that prevent any analysis are returned as errors.
+    pub async fn analyze_user(&self, username: &str) -> Result<ProfileWithWarnings> {
+        self.reset_diagnostics(username);
+        self.warn_missing_scopes(username).await;
+
         // Step 1: Fetch user profile
         tracing::info!("Fetching GitHub profile for: {}", username);
-        let user = self.github.get_user(username).await?;
+        let user = self.fetch_user(username).await?;
+        // Organizations own repositories but author no commits, so their
+        // profile would be empty at best
+        if user.account_type == AccountType::Organization {
+            return Err(Error::Organization(user.login));
+        }
 
         // Step 2: Fetch all repositories
         tracing::info!("Fetching repositories...");
-        let repos = self.github.get_user_repos(username).await?;
+        let repos = self.fetch_user_repos(username).await?;
+        let repositories_listed = repos.len();
+        self.record_diagnostics(username, |d| d.repositories_listed = repositories_listed);
+        let mut skipped = Vec::new();
 
         // Filter out forks if configured
-        let repos: Vec<_> = repos
+        let mut repos: Vec<_> = repos
             .into_iter()
-            .filter(|r| self.config.include_forks || !r.fork)
+            .filter(|r| {
+                let keep = self.config.include_forks || !r.fork;
+                if !keep {
+                    skipped.push(skipped_repository(r, "fork"));
+                }
+                keep
+            })
             .collect();
 
+        // Add external repositories the user has contributed to
+        if self.config.include_contributions {
+            let contributed = self.github.get_user_contributed_repos(username).await?;
+            for repo in contributed {
+                if !repos.iter().any(|r| r.full_name == repo.full_name) {
+                    self.record_diagnostics(username, |d| d.repositories_listed += 1);
+                    repos.push(repo);
+                }
+            }
+        }
+
+        let repos = self.filter_excluded_repos(repos, &mut skipped);
+        let repos = self.filter_repo_languages(repos, &mut skipped);
+        let (mut repos, mut warnings) =
+            self.filter_substantive_repos(username, repos, &mut skipped).await;
+        // Fixed order so runs against the same data produce the same profile
+        repos.sort_by(|a, b| a.full_name.cmp(&b.full_name));
+        self.record_diagnostics(username, |d| d.repositories_skipped = skipped.clone());
+
         tracing::info!("Found {} repositories to analyze", repos.len());
 
         // Step 3: Fetch commits from all repos concurrently
-        let all_commits = self.fetch_all_commits(username, &repos).await?;
+        let (mut all_commits, fetch_warnings) = self.fetch_all_commits(username, &repos).await?;
         tracing::info!("Fetched {} commits total", all_commits.len());
+        self.record_diagnostics(username, |d| {
+            for warning in &fetch_warnings {
+                match warning {
+                    AnalysisWarning::RepositoryFailed { repository, error } => {
+                        d.repositories_skipped.push(SkippedRepository {
+                            repository: repository.clone(),
+                            reason: error.clone(),
+                        })
+                    }
+                    AnalysisWarning::CommitFailed { .. } => d.commits_failed += 1,
+                    _ => {}
+                }
+            }
+        });
+        warnings.extend(fetch_warnings);
+
+        // Each gist counts as one commit in a pseudo-repository of its own
+        if self.config.include_gists {
+            match self.github.get_user_gists(username).await {
+                Ok(gists) => {
+                    tracing::info!("Fetched {} gists", gists.len());
+                    self.record_diagnostics(username, |d| d.gists_fetched = gists.len());
+                    all_commits.extend(gists.iter().map(|gist| gist.to_commit(&user.login)));
+                }
+                Err(e) => tracing::warn!("Skipping gists: {}", e),
+            }
+        }
+
+        let pull_requests = if self.config.include_prs {
+            let concurrency = self.config.concurrency.commit_detail_per_repo();
+            match self
+                .github
+                .get_user_pull_requests(username, concurrency, &self.shutdown)
+                .await
+            {
+                Ok(pull_requests) => {
+                    tracing::info!("Fetched {} pull requests", pull_requests.len());
+                    Some(pull_requests)
+                }
+                Err(e) => {
+                    tracing::warn!("Skipping pull requests: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let mut result = self
+            .analyze_collected(username, user, repos, all_commits, pull_requests.as_deref())
+            .await?;
+        warnings.append(&mut result.warnings);
+        result.warnings = warnings;
+        Ok(result)
+    }
+
+    /// Runs batching, LLM analysis and rating on commits that were fetched
+    /// elsewhere, skipping all GitHub requests. The resulting profile is saved
+    /// to storage just like one produced by `analyze_user`.
+    pub async fn analyze_from_commits(
+        &self,
+        user: GitHubUser,
+        repos: Vec<Repository>,
+        all_commits: Vec<(Repository, Commit)>,
+    ) -> Result<ProfileWithWarnings> {
+        let username = user.login.clone();
+        self.reset_diagnostics(&username);
+        self.analyze_collected(&username, user, repos, all_commits, None).await
+    }
+
+    /// `analyze_from_commits`, plus process metrics from `pull_requests`
+    /// when they were fetched.
+    async fn analyze_collected(
+        &self,
+        username: &str,
+        user: GitHubUser,
+        repos: Vec<Repository>,
+        all_commits: Vec<(Repository, Commit)>,
+        pull_requests: Option<&[PullRequest]>,
+    ) -> Result<ProfileWithWarnings> {
+        let usage_before = self.llm.usage();
+        let commits_fetched = all_commits.len();
+        self.record_diagnostics(username, |d| d.commits_fetched = commits_fetched);
+
+        // Listing already stops at `as_of`; this also covers gists and
+        // commits fetched elsewhere
+        let (all_commits, process_metrics) = match self.config.as_of {
+            Some(as_of) => {
+                let all_commits: Vec<_> = all_commits
+                    .into_iter()
+                    .filter(|(_, c)| c.commit.author.date <= as_of)
+                    .collect();
+                let pull_requests = pull_requests.map(|prs| {
+                    prs.iter()
+                        .filter(|pr| pr.created_at <= as_of)
+                        .cloned()
+                        .collect::<Vec<_>>()
+                });
+                (all_commits, pull_requests.as_deref().map(process_metrics))
+            }
+            None => (all_commits, pull_requests.map(process_metrics)),
+        };
+        let commits_dropped_as_of = commits_fetched - all_commits.len();
+        self.record_diagnostics(username, |d| d.commits_dropped_as_of = commits_dropped_as_of);
+
+        let before = all_commits.len();
+        let all_commits = if self.config.dedup_by_diff {
+            dedup_by_diff(all_commits)
+        } else {
+            all_commits
+        };
+        let commits_dropped_duplicate = before - all_commits.len();
+        self.record_diagnostics(username, |d| d.commits_dropped_duplicate = commits_dropped_duplicate);
+
+        // Step 4: Prepare commits for analysis, dropping any whose files all
+        // match `exclude_paths` or none of which are in the `only_languages`
+        let before = all_commits.len();
+        let all_commits: Vec<_> = all_commits
+            .into_iter()
+            .filter(|(_, commit)| !self.only_excluded_files(commit))
+            .collect();
+        if all_commits.len() < before {
+            tracing::info!(
+                "Skipped {} commit(s) that only change excluded paths",
+                before - all_commits.len()
+            );
+        }
+        let commits_dropped_excluded = before - all_commits.len();
+        self.record_diagnostics(username, |d| d.commits_dropped_excluded = commits_dropped_excluded);
+
+        let before = all_commits.len();
+        let (all_commits, commits_for_analysis): (Vec<_>, Vec<_>) = all_commits
+            .into_iter()
+            .filter_map(|(repo, commit)| {
+                let prepared = self.prepare_commit_for_analysis(&repo, &commit)?;
+                Some(((repo, commit), prepared))
+            })
+            .unzip();
+        if all_commits.len() < before {
+            tracing::info!(
+                "Skipped {} commit(s) with no files in the selected languages",
+                before - all_commits.len()
+            );
+        }
+        let commits_dropped_language = before - all_commits.len();
+        self.record_diagnostics(username, |d| d.commits_dropped_language = commits_dropped_language);
 
         if all_commits.is_empty() {
-            tracing::warn!("No commits found for user {}", username);
-            return Ok(UserProfile {
-                user,
-                repositories: repos,
-                total_commits_analyzed: 0,
-                analysis_date: Utc::now(),
-                skills: Vec::new(),
-                summary: Default::default(),
+            tracing::warn!("No commits found for user {}", user.login);
+            return Ok(ProfileWithWarnings {
+                profile: UserProfile {
+                    schema_version: PROFILE_SCHEMA_VERSION,
+                    user,
+                    repositories: repos,
+                    total_commits_analyzed: 0,
+                    analysis_date: Utc::now(),
+                    skills: Vec::new(),
+                    summary: ProfileSummary {
+                        process_metrics,
+                        ..Default::default()
+                    },
+                    incomplete: self.shutdown.is_cancelled(),
+                    as_of: self.config.as_of,
+                },
+                warnings: Vec::new(),
+                diagnostics: self.diagnostics(username),
             });
         }
 
-        // Step 4: Prepare commits for analysis
-        let commits_for_analysis: Vec<_> = all_commits
-            .iter()
-            .map(|(repo, commit)| self.prepare_commit_for_analysis(repo, commit))
-            .collect();
+        let coverage = self.language_coverage(&commits_for_analysis);
 
         // Step 5: Batch commits for LLM analysis
         let batches = self.batcher.create_batches(commits_for_analysis.clone());
         tracing::info!("Created {} batches for LLM analysis", batches.len());
+        let batches_created = batches.len();
+        self.record_diagnostics(username, |d| d.batches_created = batches_created);
 
         // Step 6: Run LLM analysis on batches
-        let analyses = self.run_llm_analysis(batches, &all_commits).await?;
+        let llm_result = self
+            .run_llm_analysis(batches, &all_commits, &self.shutdown)
+            .await;
+        self.record_llm_usage(username, usage_before);
+        let (analyses, security_sensitive, warnings) = llm_result?;
         tracing::info!("Completed {} LLM analyses", analyses.len());
+        self.record_batch_outcomes(username, analyses.len(), &warnings);
+        if let Some(callback) = &self.on_analyses {
+            callback(&analyses);
+        }
 
         // Step 7: Extract and aggregate skills
         let analysis_pairs: Vec<_> = analyses
@@ -99,45 +543,383 @@ impl AnalysisPipeline {
             .map(|(a, c)| (a.clone(), c.clone()))
             .collect();
 
-        let aggregated_skills = self.skill_extractor.aggregate_skills(&analysis_pairs);
+        let mut aggregated_skills = self.skill_extractor.aggregate_skills(&analysis_pairs);
+        self.skill_extractor
+            .add_import_signals(&mut aggregated_skills, &commits_for_analysis);
+        self.skill_extractor
+            .add_ci_signals(&mut aggregated_skills, &commits_for_analysis);
+        self.skill_extractor
+            .add_security_signals(&mut aggregated_skills, &commits_for_analysis);
+        if self.config.use_bio_signals {
+            self.skill_extractor
+                .add_profile_signals(&mut aggregated_skills, &user, &repos, self.rating_engine.now());
+        }
         tracing::info!("Extracted {} unique skills", aggregated_skills.len());
 
         // Step 8: Calculate ratings
-        let skill_ratings = self.rating_engine.calculate_ratings(&aggregated_skills);
+        let skill_ratings = self
+            .rating_engine
+            .calculate_ratings_with(&aggregated_skills, |rating| {
+                if let Some(callback) = &self.on_skill_rated {
+                    callback(rating);
+                }
+            });
 
-        // Step 9: Generate summary
-        let summary = self.rating_engine.generate_summary(&skill_ratings, &analyses);
+        // Step 9: Generate summary; batches skipped at the cost cap were
+        // never sent, so they do not count as failures
+        let cost_capped = warnings
+            .iter()
+            .any(|w| matches!(w, AnalysisWarning::CostCapReached { .. }));
+        let batch_failures = warnings.len() - cost_capped as usize;
+        let stats = AnalysisStats {
+            commits_analyzed: commits_for_analysis.len(),
+            repositories: commits_for_analysis
+                .iter()
+                .map(|c| c.repository.as_str())
+                .collect::<HashSet<_>>()
+                .len(),
+            batches_attempted: analyses.len() + batch_failures,
+            batches_failed: batch_failures,
+        };
+        self.record_diagnostics(username, |d| {
+            d.commits_analyzed = stats.commits_analyzed;
+            d.repositories_analyzed = stats.repositories;
+        });
+        let mut summary = self
+            .rating_engine
+            .generate_summary(&skill_ratings, &with_security_domain(&analyses, &security_sensitive), &stats);
+        let commit_dates: Vec<_> = all_commits
+            .iter()
+            .map(|(_, c)| c.commit.author.date.with_timezone(&Utc))
+            .collect();
+        self.rating_engine.assess_activity(&mut summary, &commit_dates);
+        let local_dates: Vec<_> = all_commits.iter().map(|(_, c)| c.commit.author.date).collect();
+        if self.config.author_timezones {
+            summary.modal_timezone = modal_utc_offset(&local_dates).map(|offset| offset.to_string());
+        }
+        summary.working_hours = working_hours(&local_dates);
+        summary.language_coverage = coverage.ratio();
+        summary.commit_languages = commit_language_breakdown(&commits_for_analysis);
+        summary.repository_languages = repository_language_breakdown(&repos);
+        summary.process_metrics = process_metrics;
+        summary.contribution_breakdown =
+            contribution_breakdown(all_commits.iter().map(|(_, c)| c.commit.message.as_str()));
 
-        let profile = UserProfile {
+        let mut profile = UserProfile {
+            schema_version: PROFILE_SCHEMA_VERSION,
             user,
             repositories: repos,
             total_commits_analyzed: all_commits.len() as u32,
             analysis_date: Utc::now(),
             skills: skill_ratings,
             summary,
+            incomplete: self.shutdown.is_cancelled() || cost_capped,
+            as_of: self.config.as_of,
         };
 
         // Step 10: Save to storage
-        self.storage.save_profile(&profile)?;
-        tracing::info!("Profile saved to database");
+        for hook in &self.hooks {
+            hook.on_profile(&mut profile);
+        }
+        if self.config.redact_store {
+            profile.redact();
+        }
+        // A past profile would replace the current one as the cached profile
+        if self.config.as_of.is_some() {
+            tracing::info!("Not saving the profile as of a past date");
+        } else {
+            self.storage.save_profile(&profile)?;
+            if profile.incomplete {
+                tracing::warn!("Partial profile saved to database (analysis stopped early)");
+            } else {
+                tracing::info!("Profile saved to database");
+            }
+        }
+
+        Ok(ProfileWithWarnings {
+            profile,
+            warnings,
+            diagnostics: self.diagnostics(username),
+        })
+    }
+
+    /// Records how the LLM batches fared.
+    fn record_batch_outcomes(&self, username: &str, succeeded: usize, warnings: &[AnalysisWarning]) {
+        self.record_diagnostics(username, |d| {
+            d.batches_succeeded = succeeded;
+            for warning in warnings {
+                match warning {
+                    AnalysisWarning::BatchFailed { .. } => d.batches_failed += 1,
+                    AnalysisWarning::ParseFailed { .. } => d.batches_unparsed += 1,
+                    _ => {}
+                }
+            }
+            d.batches_skipped = d
+                .batches_created
+                .saturating_sub(succeeded + d.batches_failed + d.batches_unparsed);
+        });
+    }
+
+    /// Records the tokens spent on the LLM since `usage_before`.
+    fn record_llm_usage(&self, username: &str, usage_before: TokenUsage) {
+        let usage_after = self.llm.usage();
+        let usage = TokenUsage {
+            input_tokens: usage_after.input_tokens.saturating_sub(usage_before.input_tokens),
+            output_tokens: usage_after.output_tokens.saturating_sub(usage_before.output_tokens),
+        };
+        let estimated_cost = self.llm.pricing().map(|pricing| pricing.cost(usage));
+        self.record_diagnostics(username, |d| {
+            d.input_tokens = usage.input_tokens;
+            d.output_tokens = usage.output_tokens;
+            d.estimated_cost = estimated_cost;
+        });
+    }
+
+    /// Analyzes several users concurrently, yielding each profile as soon as it
+    /// completes. At most `concurrency.github` analyses run at once, all sharing
+    /// the same GitHub client and rate limiter.
+    pub fn analyze_users_stream(
+        &self,
+        usernames: Vec<String>,
+    ) -> impl Stream<Item = (String, Result<ProfileWithWarnings>)> + '_ {
+        stream::iter(usernames)
+            .map(move |username| async move {
+                let result = self.analyze_user(&username).await;
+                (username, result)
+            })
+            .buffer_unordered(self.config.concurrency.github.max(1))
+    }
+
+    /// Warns when a classic token lacks scopes needed to see everything.
+    /// Private repositories are only listed for the token's own user, so
+    /// other users are skipped, as are fine-grained and App tokens, which
+    /// don't report scopes.
+    async fn warn_missing_scopes(&self, username: &str) {
+        let own_account = self
+            .github
+            .authenticated_login()
+            .await
+            .is_some_and(|login| login.eq_ignore_ascii_case(username));
+        if !own_account {
+            return;
+        }
+        match self.github.check_scopes().await {
+            Ok(Some(granted)) => {
+                let missing = missing_scopes(&granted);
+                if !missing.is_empty() {
+                    tracing::warn!(
+                        "GITHUB_TOKEN lacks the {} scope(s); private repositories will be left out of the analysis",
+                        missing.join(", ")
+                    );
+                }
+            }
+            Ok(None) => tracing::debug!("Token reports no scopes (fine-grained or App token); skipping scope check"),
+            Err(e) => tracing::debug!("Could not check token scopes: {}", e),
+        }
+    }
+
+    async fn fetch_user(&self, username: &str) -> Result<GitHubUser> {
+        let resource = format!("users/{}", username);
+        let cached = self.storage.get_cached_response(&resource)?;
+        let result = self
+            .github
+            .get_user_conditional(username, cached.as_ref().map(|c| c.etag.as_str()))
+            .await?;
+        self.resolve_conditional(&resource, cached, result)
+    }
+
+    async fn fetch_user_repos(&self, username: &str) -> Result<Vec<Repository>> {
+        let resource = format!("users/{}/repos", username);
+        let cached = self.storage.get_cached_response(&resource)?;
+        let etag = cached.as_ref().map(|c| c.etag.as_str());
+        let mut attempt = 1;
+        let result = loop {
+            match self.github.get_user_repos_conditional(username, etag).await {
+                Err(e) if e.is_retryable() && attempt < REPO_LISTING_ATTEMPTS => {
+                    tracing::warn!(
+                        "Listing repositories for {} failed, resuming ({}/{}): {}",
+                        username,
+                        attempt,
+                        REPO_LISTING_ATTEMPTS - 1,
+                        e
+                    );
+                    tokio::time::sleep(std::time::Duration::from_secs(attempt as u64)).await;
+                    attempt += 1;
+                }
+                result => break result?,
+            }
+        };
+        self.resolve_conditional(&resource, cached, result)
+    }
+
+    /// Returns the cached body on 304, or stores the fresh data with its ETag.
+    fn resolve_conditional<T: Serialize + DeserializeOwned>(
+        &self,
+        resource: &str,
+        cached: Option<CachedResponse>,
+        result: Conditional<T>,
+    ) -> Result<T> {
+        match result {
+            Conditional::NotModified => {
+                tracing::debug!("{} not modified, using cached copy", resource);
+                let cached = cached.ok_or_else(|| {
+                    Error::GitHubApi(format!("Got 304 for {} without a cached copy", resource))
+                })?;
+                Ok(serde_json::from_str(&cached.body)?)
+            }
+            Conditional::Modified { data, etag } => {
+                if let Some(etag) = etag {
+                    self.storage
+                        .save_cached_response(resource, &etag, &serde_json::to_string(&data)?)?;
+                }
+                Ok(data)
+            }
+        }
+    }
+
+    /// Drops repositories matching `exclude_repositories`.
+    fn filter_excluded_repos(
+        &self,
+        repos: Vec<Repository>,
+        skipped: &mut Vec<SkippedRepository>,
+    ) -> Vec<Repository> {
+        if self.config.exclude_repositories.is_empty() {
+            return repos;
+        }
+
+        let before = repos.len();
+        let repos: Vec<_> = repos
+            .into_iter()
+            .filter(|r| {
+                let excluded = repository_excluded(&self.config.exclude_repositories, &r.full_name);
+                if excluded {
+                    skipped.push(skipped_repository(r, "excluded by configuration"));
+                }
+                !excluded
+            })
+            .collect();
+        if repos.len() < before {
+            tracing::info!("Skipped {} excluded repositories", before - repos.len());
+        }
+        repos
+    }
+
+    /// Drops repositories whose GitHub language is outside `only_languages`,
+    /// before any of their commits are fetched. Repositories without a
+    /// detected language are dropped too.
+    fn filter_repo_languages(
+        &self,
+        repos: Vec<Repository>,
+        skipped: &mut Vec<SkippedRepository>,
+    ) -> Vec<Repository> {
+        if self.config.only_languages.is_empty() {
+            return repos;
+        }
+
+        let before = repos.len();
+        let repos: Vec<_> = repos
+            .into_iter()
+            .filter(|r| {
+                let keep = language_selected(&self.config.only_languages, r.language.as_deref());
+                if !keep {
+                    skipped.push(skipped_repository(r, "outside the selected languages"));
+                }
+                keep
+            })
+            .collect();
+        if repos.len() < before {
+            tracing::info!(
+                "Skipped {} repositories outside the selected languages",
+                before - repos.len()
+            );
+        }
+        repos
+    }
+
+    /// Drops repositories below `min_stars`, then those where the user has
+    /// fewer than `min_repo_commits` commits. Counting costs one request per
+    /// repo, so it only runs when the commit threshold is set. Repositories
+    /// whose commits cannot be counted are dropped with a warning.
+    async fn filter_substantive_repos(
+        &self,
+        username: &str,
+        repos: Vec<Repository>,
+        skipped: &mut Vec<SkippedRepository>,
+    ) -> (Vec<Repository>, Vec<AnalysisWarning>) {
+        let before = repos.len();
+        let repos: Vec<_> = repos
+            .into_iter()
+            .filter(|r| {
+                let keep = r.stargazers_count >= self.config.min_stars;
+                if !keep {
+                    skipped.push(skipped_repository(r, "below the star threshold"));
+                }
+                keep
+            })
+            .collect();
+
+        let min_commits = self.config.min_repo_commits;
+        let mut warnings = Vec::new();
+        let repos: Vec<_> = if min_commits == 0 {
+            repos
+        } else {
+            let counted: Vec<_> = stream::iter(repos)
+                .map(|repo| async move {
+                    let count = self
+                        .github
+                        .count_repo_commits(&repo.owner.login, &repo.name, Some(username), None, self.config.as_of)
+                        .await;
+                    (repo, count)
+                })
+                .buffered(self.config.concurrency.github.max(1))
+                .collect()
+                .await;
+
+            let mut kept = Vec::new();
+            for (repo, count) in counted {
+                match count {
+                    Ok(count) if count >= min_commits => kept.push(repo),
+                    Ok(_) => skipped.push(skipped_repository(&repo, "below the commit threshold")),
+                    Err(e) => {
+                        tracing::warn!("Could not count commits for {}: {}", repo.full_name, e);
+                        skipped.push(skipped_repository(&repo, &format!("could not count commits: {}", e)));
+                        warnings.push(AnalysisWarning::RepositoryFailed {
+                            repository: repo.full_name,
+                            error: format!("could not count commits: {}", e),
+                        });
+                    }
+                }
+            }
+            kept
+        };
 
-        Ok(profile)
+        if repos.len() < before {
+            tracing::info!(
+                "Skipped {} repositories below the star/commit thresholds",
+                before - repos.len()
+            );
+        }
+        (repos, warnings)
     }
 
+    /// Fetches each repository's commits with their diffs. Repositories and
+    /// commits that fail to load are skipped and reported as warnings.
     async fn fetch_all_commits(
         &self,
         username: &str,
         repos: &[Repository],
-    ) -> Result<Vec<(Repository, Commit)>> {
-        let semaphore = Arc::new(Semaphore::new(self.config.concurrency_limit));
+    ) -> Result<(Vec<(Repository, Commit)>, Vec<AnalysisWarning>)> {
+        let semaphore = Arc::new(Semaphore::new(self.config.concurrency.github.max(1)));
+        let commit_detail_limit = self.config.concurrency.commit_detail_per_repo();
+        if commit_detail_limit < self.config.concurrency.commit_detail {
+            tracing::info!(
+                "Limiting commit detail requests to {} per repository to keep at most {} GitHub requests in flight",
+                commit_detail_limit,
+                MAX_GITHUB_REQUESTS_IN_FLIGHT
+            );
+        }
 
-        let pb = ProgressBar::new(repos.len() as u64);
-        pb.set_style(
-            ProgressStyle::default_bar()
-                .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} repos")
-                .unwrap()
-                .progress_chars("#>-"),
-        );
+        let pb = self.progress(repos.len(), "repos");
 
         let mut commit_futures = Vec::new();
 
@@ -147,122 +929,1114 @@ impl AnalysisPipeline {
             let owner = repo.owner.login.clone();
             let name = repo.name.clone();
             let author = username.to_string();
-            let max_commits = self.config.max_commits_per_repo;
+            let max_commits_per_repo = self.config.max_commits_per_repo;
+            let max_commits_big_repo = self.config.max_commits_big_repo;
+            let sampling = self.config.commit_sampling;
+            let author_email = self.config.author_email.clone();
+            let include_coauthored = self.config.include_coauthored;
+            let include_bots = self.config.include_bots;
+            let since_last_release = self.config.since_last_release;
+            let author_timezones = self.config.author_timezones;
+            let as_of = self.config.as_of;
             let repo_clone = repo.clone();
             let pb_clone = pb.clone();
+            let shutdown = self.shutdown.clone();
 
             commit_futures.push(async move {
-                let _permit = sem.acquire().await.ok()?;
+                let mut warnings = Vec::new();
+                let Ok(_permit) = sem.acquire().await else {
+                    return (Vec::new(), warnings);
+                };
+                if shutdown.is_cancelled() {
+                    return (Vec::new(), warnings);
+                }
+
+                // Without a release to anchor to, the whole history is analyzed
+                let since = if since_last_release {
+                    match github.get_latest_release(&owner, &name).await {
+                        Ok(Some(release)) => {
+                            tracing::info!(
+                                "{}: analyzing commits since release {} ({})",
+                                repo_clone.full_name,
+                                release.tag_name,
+                                release.date().format("%Y-%m-%d")
+                            );
+                            Some(release.date())
+                        }
+                        Ok(None) => {
+                            tracing::info!("{}: no releases, analyzing all commits", repo_clone.full_name);
+                            None
+                        }
+                        Err(e) => {
+                            tracing::warn!(
+                                "Could not fetch latest release for {}, analyzing all commits: {}",
+                                repo_clone.full_name,
+                                e
+                            );
+                            None
+                        }
+                    }
+                } else {
+                    None
+                };
+
+                // Counted by the same author the listing filters by. Without a
+                // count the repo is treated as small; listing will report the
+                // failure if the repo is actually unreachable
+                let counted_author = author_email.as_deref().unwrap_or(&author);
+                let total = match github.count_repo_commits(&owner, &name, Some(counted_author), since, as_of).await {
+                    Ok(total) => Some(total),
+                    Err(e) => {
+                        tracing::debug!("Could not count commits for {}: {}", repo_clone.full_name, e);
+                        None
+                    }
+                };
+                let max_commits =
+                    commit_cap(total, max_commits_per_repo, max_commits_big_repo);
+
+                let co_authored = |c: &CommitSummary| {
+                    include_coauthored && co_authored_by(c, &author, author_email.as_deref())
+                };
+                let listed = match &author_email {
+                    Some(email) => {
+                        github
+                            .get_repo_commits_matching(&owner, &name, since, as_of, max_commits, |c| {
+                                authored_by(c, &author, email) || co_authored(c)
+                            })
+                            .await
+                    }
+                    None => {
+                        let own = github
+                            .get_repo_commits(&owner, &name, Some(&author), since, as_of, max_commits, sampling, total)
+                            .await;
+                        match own {
+                            // GitHub's author filter misses commits the user only
+                            // co-authored, so those need a scan of the full
+                            // history; both together stay within `max_commits`
+                            Ok(mut own) if include_coauthored => {
+                                let listed: HashSet<_> = own.iter().map(|c| c.sha.clone()).collect();
+                                github
+                                    .get_repo_commits_matching(&owner, &name, since, as_of, max_commits, |c| {
+                                        !listed.contains(&c.sha) && co_authored(c)
+                                    })
+                                    .await
+                                    .map(|paired| {
+                                        own.extend(paired);
+                                        own.truncate(max_commits as usize);
+                                        own
+                                    })
+                            }
+                            own => own,
+                        }
+                    }
+                };
+                let commits = match listed {
+                    Ok(commits) => commits,
+                    Err(e) => {
+                        tracing::warn!("Could not list commits for {}: {}", repo_clone.full_name, e);
+                        warnings.push(AnalysisWarning::RepositoryFailed {
+                            repository: repo_clone.full_name.clone(),
+                            error: format!("could not list commits: {}", e),
+                        });
+                        pb_clone.inc();
+                        return (Vec::new(), warnings);
+                    }
+                };
+                let commits = if include_bots {
+                    commits
+                } else {
+                    let listed = commits.len();
+                    let commits: Vec<_> = commits.into_iter().filter(|c| !c.is_bot_authored()).collect();
+                    if commits.len() < listed {
+                        tracing::info!(
+                            "{}: skipping {} commits authored by bots",
+                            repo_clone.full_name,
+                            listed - commits.len()
+                        );
+                    }
+                    commits
+                };
+
+                if let Some(total) = total.filter(|&t| is_large_repo(t, max_commits_per_repo)) {
+                    if total > max_commits {
+                        tracing::warn!(
+                            "{} has {} commits by {}; analyzing only {}",
+                            repo_clone.full_name,
+                            total,
+                            counted_author,
+                            max_commits
+                        );
+                        warnings.push(AnalysisWarning::RepositoryTruncated {
+                            repository: repo_clone.full_name.clone(),
+                            analyzed: max_commits,
+                            total,
+                        });
+                    }
+                }
 
-                let commits = github
-                    .get_repo_commits(&owner, &name, Some(&author), max_commits)
-                    .await
-                    .ok()?;
+                let shas = commits
+                    .into_iter()
+                    .take(max_commits as usize)
+                    .map(|c| c.sha)
+                    .take_while(|_| !shutdown.is_cancelled());
+                let fetched = github
+                    .get_commits_with_diff(&owner, &name, shas, commit_detail_limit)
+                    .await;
 
                 let mut full_commits = Vec::new();
-                for commit_summary in commits.into_iter().take(max_commits as usize) {
-                    if let Ok(full_commit) = github
-                        .get_commit_with_diff(&owner, &name, &commit_summary.sha)
-                        .await
-                    {
+                for (sha, result) in fetched {
+                    match result {
                         // Only include commits that have actual file changes
-                        if full_commit.files.as_ref().map(|f| !f.is_empty()).unwrap_or(false) {
-                            full_commits.push((repo_clone.clone(), full_commit));
+                        Ok(c) if c.files.as_ref().is_some_and(|f| !f.is_empty()) => {
+                            full_commits.push(c);
+                        }
+                        Ok(_) => {}
+                        Err(e) => {
+                            tracing::warn!("Could not fetch commit {} in {}: {}", sha, repo_clone.full_name, e);
+                            warnings.push(AnalysisWarning::CommitFailed {
+                                repository: repo_clone.full_name.clone(),
+                                sha,
+                                error: e.to_string(),
+                            });
                         }
                     }
                 }
 
-                pb_clone.inc(1);
-                Some(full_commits)
+                if author_timezones && !shutdown.is_cancelled() {
+                    github
+                        .localize_author_dates(&owner, &name, &mut full_commits, commit_detail_limit)
+                        .await;
+                }
+
+                pb_clone.inc();
+                let full_commits = full_commits.into_iter().map(|c| (repo_clone.clone(), c)).collect();
+                (full_commits, warnings)
             });
         }
 
         let results = join_all(commit_futures).await;
-        pb.finish_with_message("Fetched all commits");
+        pb.finish("Fetched all commits");
 
-        Ok(results
-            .into_iter()
-            .flatten()
-            .flatten()
-            .collect())
+        let mut all_commits = Vec::new();
+        let mut warnings = Vec::new();
+        for (commits, repo_warnings) in results {
+            all_commits.extend(commits);
+            warnings.extend(repo_warnings);
+        }
+        Ok((all_commits, warnings))
     }
 
+    /// Analyzes each batch with the LLM, returning the successful analyses in
+    /// batch order, whether each one's batch has security-sensitive changes,
+    /// and one warning per failed batch.
     async fn run_llm_analysis(
         &self,
         batches: Vec<Vec<CommitForAnalysis>>,
         all_commits: &[(Repository, Commit)],
-    ) -> Result<Vec<LLMAnalysisResult>> {
-        let pb = ProgressBar::new(batches.len() as u64);
-        pb.set_style(
-            ProgressStyle::default_bar()
-                .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} batches")
-                .unwrap()
-                .progress_chars("#>-"),
-        );
+        shutdown: &CancellationToken,
+    ) -> Result<(Vec<LLMAnalysisResult>, Vec<bool>, Vec<AnalysisWarning>)> {
+        let total_batches = batches.len();
+        let pb = self.progress(total_batches, "batches");
 
-        let mut all_analyses = Vec::new();
+        // Build requests up front; context comes from the first commit in each batch
+        let repositories = repositories_by_name(all_commits);
+        let requests: Vec<AnalysisRequest> = batches
+            .into_iter()
+            .filter(|batch| !batch.is_empty())
+            .map(|batch| {
+                let context = if let Some(first) = batch.first() {
+                    let repo = repositories.get(first.repository.as_str()).copied();
 
-        for batch in batches {
-            if batch.is_empty() {
-                continue;
+                    AnalysisContext {
+                        repository_name: first.repository.clone(),
+                        repository_description: repo.and_then(|r| r.description.clone()),
+                        primary_language: repo.and_then(|r| r.language.clone()),
+                    }
+                } else {
+                    AnalysisContext::default()
+                };
+
+                AnalysisRequest::new(batch, context)
+                    .with_max_diff_chars(self.config.per_file_diff_limit)
+            })
+            .collect();
+
+        // With `max_cost`, a batch is only started if the spend so far plus
+        // the worst-case cost of every batch in flight, this one included,
+        // stays within the cap. Once one batch is refused, no more start.
+        let budget = self.config.max_cost.and_then(|max_cost| match self.llm.pricing() {
+            Some(pricing) => Some((max_cost, pricing)),
+            None => {
+                tracing::warn!(
+                    "No pricing known for the {} model; the cost cap is not enforced",
+                    self.llm.name()
+                );
+                None
             }
+        });
+        let in_flight_cost = std::sync::Mutex::new(0.0);
+        let cost_capped = std::sync::atomic::AtomicUsize::new(0);
 
-            // Get context from first commit in batch
-            let context = if let Some(first) = batch.first() {
-                let repo = all_commits
-                    .iter()
-                    .find(|(r, _)| r.full_name == first.repository)
-                    .map(|(r, _)| r);
+        // Up to `concurrency.llm` batches in flight; results keep batch order.
+        // `None` marks a batch skipped on shutdown or at the cost cap,
+        // `Some(Err)` one that failed.
+        let results: Vec<Option<std::result::Result<(LLMAnalysisResult, bool), AnalysisWarning>>> =
+            stream::iter(requests)
+            .map(|request| {
+                let pb = pb.clone();
+                let in_flight_cost = &in_flight_cost;
+                let cost_capped = &cost_capped;
+                async move {
+                    if shutdown.is_cancelled() {
+                        return None;
+                    }
+
+                    let mut estimate = 0.0;
+                    if let Some((max_cost, pricing)) = budget {
+                        estimate = pricing.cost(worst_case_batch_usage(
+                            request.estimate_tokens(),
+                            self.llm.max_output_tokens(),
+                        ));
+                        let mut pending = in_flight_cost.lock().unwrap();
+                        let spent = pricing.cost(self.llm.usage());
+                        if cost_capped.load(Ordering::SeqCst) > 0 || spent + *pending + estimate > max_cost {
+                            cost_capped.fetch_add(1, Ordering::SeqCst);
+                            return None;
+                        }
+                        *pending += estimate;
+                    }
+
+                    let repository = request.context.repository_name.clone();
+                    let commits = request.commits.len();
+                    let security_sensitive = request.commits.iter().any(has_security_changes);
+                    let result = self.llm.analyze_commits(request).await;
+                    let result = result.map(|analysis| (analysis, security_sensitive)).map_err(|e| {
+                        tracing::warn!("LLM analysis failed for batch: {}", e);
+                        match e {
+                            Error::ParseError(error) => {
+                                AnalysisWarning::ParseFailed { repository, commits, error }
+                            }
+                            e => AnalysisWarning::BatchFailed {
+                                repository,
+                                commits,
+                                error: e.to_string(),
+                            },
+                        }
+                    });
+                    if budget.is_some() {
+                        *in_flight_cost.lock().unwrap() -= estimate;
+                    }
 
-                AnalysisContext {
-                    repository_name: first.repository.clone(),
-                    repository_description: repo.and_then(|r| r.description.clone()),
-                    primary_language: repo.and_then(|r| r.language.clone()),
+                    pb.inc();
+                    Some(result)
                 }
-            } else {
-                AnalysisContext::default()
-            };
+            })
+            .buffered(self.config.concurrency.llm.max(1))
+            .collect()
+            .await;
 
-            let request = AnalysisRequest::new(batch, context);
+        let skipped_batches = cost_capped.into_inner();
+        if shutdown.is_cancelled() {
+            tracing::warn!(
+                "Shutdown requested, skipped {} remaining batch(es)",
+                total_batches - pb.position() - skipped_batches
+            );
+        }
 
-            match self.llm.analyze_commits(request).await {
-                Ok(analysis) => {
+        let mut all_analyses = Vec::new();
+        let mut security_sensitive = Vec::new();
+        let mut warnings = Vec::new();
+        for result in results.into_iter().flatten() {
+            match result {
+                Ok((analysis, sensitive)) => {
                     all_analyses.push(analysis);
+                    security_sensitive.push(sensitive);
                 }
-                Err(e) => {
-                    tracing::warn!("LLM analysis failed for batch: {}", e);
-                }
+                Err(warning) => warnings.push(warning),
             }
-
-            pb.inc(1);
+        }
+        if let (Some((max_cost, _)), true) = (budget, skipped_batches > 0) {
+            let warning = AnalysisWarning::CostCapReached { max_cost, skipped_batches };
+            tracing::warn!("{}", warning);
+            warnings.push(warning);
         }
 
-        pb.finish_with_message("LLM analysis complete");
-        Ok(all_analyses)
+        pb.finish("LLM analysis complete");
+        Ok((all_analyses, security_sensitive, warnings))
     }
 
-    fn prepare_commit_for_analysis(&self, repo: &Repository, commit: &Commit) -> CommitForAnalysis {
-        let files = commit.files.as_ref().map(|files| {
-            files
+    /// Measures how many of the changed files have a detected language and logs
+    /// the most common unrecognized extensions.
+    fn language_coverage(&self, commits: &[CommitForAnalysis]) -> LanguageCoverage {
+        let mut coverage = LanguageCoverage::default();
+        for file in commits.iter().flat_map(|c| &c.files_changed) {
+            coverage.record(&file.filename, file.language.as_deref());
+        }
+
+        if let Some(ratio) = coverage.ratio() {
+            tracing::info!(
+                "Language detection coverage: {:.0}% of {} files",
+                ratio * 100.0,
+                coverage.total()
+            );
+        }
+        let top = coverage.top_unrecognized(5);
+        if !top.is_empty() {
+            let listed: Vec<_> = top
                 .iter()
-                .filter(|f| f.patch.is_some())
-                .map(|f| FileForAnalysis {
-                    filename: f.filename.clone(),
-                    language: detect_language(&f.filename),
-                    diff: f.patch.clone().unwrap_or_default(),
-                    additions: f.additions,
-                    deletions: f.deletions,
-                })
-                .collect()
-        }).unwrap_or_default();
+                .map(|(ext, count)| format!("{} ({})", ext, count))
+                .collect();
+            tracing::info!("Top unrecognized extensions: {}", listed.join(", "));
+        }
+
+        coverage
+    }
+
+    /// Whether every textual file the commit changes matches `exclude_paths`.
+    fn only_excluded_files(&self, commit: &Commit) -> bool {
+        let mut files = commit.files.iter().flatten().filter(|f| !f.is_binary()).peekable();
+        let exclude_paths = &self.config.exclude_paths;
+        !exclude_paths.is_empty()
+            && files.peek().is_some()
+            && files.all(|f| exclude_paths.iter().any(|pattern| wildcard_match(pattern, &f.filename)))
+    }
+
+    /// Converts a commit into the form sent to the LLM, without files matching
+    /// `exclude_paths`. With `only_languages` set, files in other languages
+    /// (or none) are left out too, and `None` is returned when no files
+    /// remain.
+    fn prepare_commit_for_analysis(
+        &self,
+        repo: &Repository,
+        commit: &Commit,
+    ) -> Option<CommitForAnalysis> {
+        let all_files = commit.files.as_deref().unwrap_or_default();
+        let only_languages = &self.config.only_languages;
+        let exclude_paths = &self.config.exclude_paths;
+
+        let files: Vec<_> = all_files
+            .iter()
+            .filter(|f| !f.is_binary())
+            .filter(|f| !exclude_paths.iter().any(|pattern| wildcard_match(pattern, &f.filename)))
+            .map(|f| FileForAnalysis {
+                filename: f.filename.clone(),
+                language: detect_language(&f.filename),
+                diff: f.patch.clone().unwrap_or_default(),
+                additions: f.additions,
+                deletions: f.deletions,
+            })
+            .filter(|f| only_languages.is_empty() || language_selected(only_languages, f.language.as_deref()))
+            .collect();
+
+        if !only_languages.is_empty() && files.is_empty() {
+            return None;
+        }
 
-        CommitForAnalysis {
+        let binary_files_changed = all_files.iter().filter(|f| f.is_binary()).count() as u32;
+
+        Some(CommitForAnalysis {
             sha: commit.sha.clone(),
             repository: repo.full_name.clone(),
             message: commit.commit.message.clone(),
             stats: commit.stats.clone().unwrap_or_default(),
             files_changed: files,
-            committed_at: commit.commit.author.date,
+            binary_files_changed,
+            committed_at: commit.commit.author.date.with_timezone(&Utc),
+            collaborative: !commit.commit.co_authors().is_empty(),
+        })
+    }
+}
+
+/// Assembles an `AnalysisPipeline` from named parts. The GitHub client and
+/// LLM provider are required; everything else has a default.
+#[derive(Default)]
+pub struct AnalysisPipelineBuilder {
+    github: Option<GitHubClient>,
+    llm: Option<Arc<dyn LLMProvider>>,
+    storage: Option<Storage>,
+    config: Option<PipelineConfig>,
+    scoring: Option<Box<dyn ScoringStrategy>>,
+    hooks: Vec<Box<dyn AnalysisHook>>,
+    show_progress: Option<bool>,
+}
+
+impl AnalysisPipelineBuilder {
+    pub fn github(mut self, github: GitHubClient) -> Self {
+        self.github = Some(github);
+        self
+    }
+
+    pub fn llm(mut self, llm: impl LLMProvider + 'static) -> Self {
+        self.llm = Some(Arc::new(llm));
+        self
+    }
+
+    /// Where profiles are saved. Defaults to an in-memory database, so
+    /// nothing is written to disk unless a store is given.
+    pub fn store(mut self, storage: Storage) -> Self {
+        self.storage = Some(storage);
+        self
+    }
+
+    /// Defaults to `PipelineConfig::default()`.
+    pub fn config(mut self, config: PipelineConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// See `AnalysisPipeline::with_scoring_strategy`. Defaults to `WeightedScoring`.
+    pub fn scoring(mut self, strategy: Box<dyn ScoringStrategy>) -> Self {
+        self.scoring = Some(strategy);
+        self
+    }
+
+    /// See `AnalysisPipeline::with_hook`. May be called more than once.
+    pub fn hook(mut self, hook: Box<dyn AnalysisHook>) -> Self {
+        self.hooks.push(hook);
+        self
+    }
+
+    /// See `AnalysisPipeline::with_progress`. Defaults to true.
+    pub fn progress(mut self, show: bool) -> Self {
+        self.show_progress = Some(show);
+        self
+    }
+
+    /// Builds the pipeline, failing if the GitHub client or LLM provider is
+    /// missing, a signal score is outside 0-100, or the in-memory store
+    /// cannot be opened.
+    pub fn build(self) -> Result<AnalysisPipeline> {
+        let github = self
+            .github
+            .ok_or_else(|| Error::Config("pipeline builder needs a GitHub client".to_string()))?;
+        let llm = self
+            .llm
+            .ok_or_else(|| Error::Config("pipeline builder needs an LLM provider".to_string()))?;
+        let storage = match self.storage {
+            Some(storage) => storage,
+            None => Storage::in_memory()?,
+        };
+
+        let mut pipeline = AnalysisPipeline::from_parts(
+            github,
+            llm,
+            storage,
+            self.config.unwrap_or_default(),
+        )?;
+        if let Some(strategy) = self.scoring {
+            pipeline = pipeline.with_scoring_strategy(strategy);
+        }
+        for hook in self.hooks {
+            pipeline = pipeline.with_hook(hook);
+        }
+        if let Some(show) = self.show_progress {
+            pipeline = pipeline.with_progress(show);
+        }
+        Ok(pipeline)
+    }
+}
+
+fn is_large_repo(total: u32, max_commits_per_repo: u32) -> bool {
+    total > max_commits_per_repo.saturating_mul(LARGE_REPO_FACTOR)
+}
+
+/// How many commits to analyze in a repository with `total` commits by the
+/// user: `max_commits_big_repo` for large repositories when set, otherwise
+/// `max_commits_per_repo`.
+fn commit_cap(total: Option<u32>, max_commits_per_repo: u32, max_commits_big_repo: Option<u32>) -> u32 {
+    match (total, max_commits_big_repo) {
+        (Some(total), Some(big)) if is_large_repo(total, max_commits_per_repo) => big,
+        _ => max_commits_per_repo,
+    }
+}
+
+/// Whether a commit belongs to the user: attributed to `login` by GitHub, or
+/// authored with `email` (both compared case-insensitively).
+fn authored_by(commit: &CommitSummary, login: &str, email: &str) -> bool {
+    commit
+        .author
+        .as_ref()
+        .is_some_and(|a| a.login.eq_ignore_ascii_case(login))
+        || commit.commit.author.email.eq_ignore_ascii_case(email.trim())
+}
+
+/// Whether a commit credits the user in a `Co-authored-by:` trailer, by
+/// `email`, by their GitHub noreply address, or by a name equal to `login`.
+fn co_authored_by(commit: &CommitSummary, login: &str, email: Option<&str>) -> bool {
+    let noreply = format!("{}@users.noreply.github.com", login.to_lowercase());
+    commit.commit.co_authors().iter().any(|co| {
+        let co_email = co.email.to_lowercase();
+        email.is_some_and(|e| co_email == e.trim().to_lowercase())
+            // Newer noreply addresses are prefixed with the account ID: "123+login@..."
+            || co_email == noreply
+            || co_email.ends_with(&format!("+{}", noreply))
+            || co.name.eq_ignore_ascii_case(login)
+    })
+}
+
+/// Whether `language` is one of `only_languages`, ignoring case. An unknown
+/// language never matches.
+fn language_selected(only_languages: &[String], language: Option<&str>) -> bool {
+    language.is_some_and(|language| {
+        only_languages
+            .iter()
+            .any(|selected| selected.eq_ignore_ascii_case(language))
+    })
+}
+
+/// Whether `text` matches `pattern`, where `*` matches any run of
+/// characters, `/` included. A pattern ending in `/` matches everything
+/// under that directory.
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    if pattern.ends_with('/') {
+        return wildcard_match(&format!("{}*", pattern), text);
+    }
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<_> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+/// Whether the repository named `full_name` matches one of
+/// `exclude_repositories`, ignoring case.
+fn repository_excluded(excludes: &[String], full_name: &str) -> bool {
+    let full_name = full_name.to_lowercase();
+    excludes
+        .iter()
+        .any(|pattern| wildcard_match(&pattern.to_lowercase(), &full_name))
+}
+
+/// Whether any file the commit changes is security-sensitive (see
+/// `detect_security_change`).
+fn has_security_changes(commit: &CommitForAnalysis) -> bool {
+    commit
+        .files_changed
+        .iter()
+        .any(|f| detect_security_change(&f.filename, &f.diff).is_some())
+}
+
+/// Most tokens one batch can be billed for: the prompt and a full-length
+/// reply, then, when that reply does not parse, the prompt again with the
+/// reply and `JSON_REPROMPT` as follow-up turns, and a second full reply.
+fn worst_case_batch_usage(prompt_tokens: usize, max_output_tokens: usize) -> TokenUsage {
+    let reprompt_tokens = JSON_REPROMPT.len() / 4 + 1;
+    TokenUsage {
+        input_tokens: (2 * prompt_tokens + max_output_tokens + reprompt_tokens) as u64,
+        output_tokens: 2 * max_output_tokens as u64,
+    }
+}
+
+/// `analyses` with the security domain added to those whose batch has
+/// security-sensitive changes (`security_sensitive`, in the same order), so
+/// the summary counts it even where the LLM missed it.
+fn with_security_domain(analyses: &[LLMAnalysisResult], security_sensitive: &[bool]) -> Vec<LLMAnalysisResult> {
+    analyses
+        .iter()
+        .zip(security_sensitive)
+        .map(|(analysis, &sensitive)| {
+            let mut analysis = analysis.clone();
+            if sensitive
+                && !analysis
+                    .domain_signals
+                    .iter()
+                    .any(|d| d.eq_ignore_ascii_case(SECURITY_SKILL))
+            {
+                analysis.domain_signals.push(SECURITY_SKILL.to_string());
+            }
+            analysis
+        })
+        .collect()
+}
+
+fn skipped_repository(repo: &Repository, reason: &str) -> SkippedRepository {
+    SkippedRepository {
+        repository: repo.full_name.clone(),
+        reason: reason.to_string(),
+    }
+}
+
+/// Each repository in `commits` by full name, so per-batch lookups don't
+/// scan every fetched commit.
+fn repositories_by_name(commits: &[(Repository, Commit)]) -> HashMap<&str, &Repository> {
+    commits
+        .iter()
+        .map(|(repo, _)| (repo.full_name.as_str(), repo))
+        .collect()
+}
+
+/// Drops commits whose combined patches match an earlier commit's, which is
+/// what rewritten or force-pushed history looks like: same diff, new SHA.
+fn dedup_by_diff(commits: Vec<(Repository, Commit)>) -> Vec<(Repository, Commit)> {
+    let before = commits.len();
+    let mut seen = HashSet::new();
+
+    let deduped: Vec<_> = commits
+        .into_iter()
+        .filter(|(_, commit)| {
+            let mut hasher = DefaultHasher::new();
+            for file in commit.files.as_deref().unwrap_or_default() {
+                file.filename.hash(&mut hasher);
+                file.patch.hash(&mut hasher);
+            }
+            seen.insert(hasher.finish())
+        })
+        .collect();
+
+    let collapsed = before - deduped.len();
+    if collapsed > 0 {
+        tracing::info!("Collapsed {} commit(s) with duplicate diffs", collapsed);
+    }
+    deduped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_requires_github_and_llm() {
+        let github = || GitHubClient::new("test-token").unwrap();
+        let llm = || crate::llm::ClaudeProvider::new("test-key".to_string(), None);
+
+        let missing_llm = AnalysisPipeline::builder().github(github()).build();
+        assert!(matches!(missing_llm, Err(Error::Config(_))));
+        let missing_github = AnalysisPipeline::builder().llm(llm()).build();
+        assert!(matches!(missing_github, Err(Error::Config(_))));
+        let out_of_range = AnalysisPipeline::builder()
+            .github(github())
+            .llm(llm())
+            .config(PipelineConfig {
+                unknown_signal_score: Some(120.0),
+                ..PipelineConfig::default()
+            })
+            .build();
+        assert!(matches!(out_of_range, Err(Error::Config(_))));
+
+        let pipeline = AnalysisPipeline::builder()
+            .github(github())
+            .llm(llm())
+            .hook(Box::new(crate::analysis::NoopHook))
+            .progress(false)
+            .build()
+            .unwrap();
+        assert_eq!(pipeline.config.max_commits_per_repo, 100);
+        assert_eq!(pipeline.hooks.len(), 1);
+        assert!(!pipeline.show_progress);
+        let progress = pipeline.progress(3, "repos");
+        assert!(progress.bar.is_none());
+        progress.clone().inc();
+        assert_eq!(progress.position(), 1);
+    }
+
+    /// `count` commits spread over the repositories octocat/repo0 to
+    /// octocat/repo{repos - 1}, each described by its name.
+    fn commits_in_repositories(count: usize, repos: usize) -> Vec<(Repository, Commit)> {
+        use crate::test_support::{commit, repository};
+
+        (0..count)
+            .map(|i| {
+                let mut repo = repository(&format!("octocat/repo{}", i % repos));
+                repo.description = Some(format!("repo {}", i % repos));
+                (repo, commit(&format!("{:07x}", i), &[("main.rs", "+fn main() {}")]))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_repositories_by_name() {
+        let commits = commits_in_repositories(1000, 2);
+
+        // One entry per repository, however many commits each has, so a
+        // lookup costs the same with 1,000 commits as with 2
+        let repositories = repositories_by_name(&commits);
+        assert_eq!(repositories.len(), 2);
+        let repo = repositories.get("octocat/repo1").unwrap();
+        assert_eq!(repo.description.as_deref(), Some("repo 1"));
+        assert!(!repositories.contains_key("octocat/missing"));
+        assert_eq!(repositories_by_name(&commits_in_repositories(100_000, 2)).len(), 2);
+    }
+
+    /// Timing based, so not run by default: `cargo test -- --ignored`.
+    #[test]
+    #[ignore]
+    fn bench_repository_lookup_does_not_grow_with_commits() {
+        let lookups = |commits: &[(Repository, Commit)]| {
+            let repositories = repositories_by_name(commits);
+            let started = std::time::Instant::now();
+            for i in 0..100_000 {
+                let name = format!("octocat/repo{}", i % 10);
+                assert!(repositories.contains_key(name.as_str()));
+            }
+            started.elapsed()
+        };
+        let few = lookups(&commits_in_repositories(100, 10));
+        let many = lookups(&commits_in_repositories(100_000, 10));
+        // A scan of the commits would make this 1,000 times slower
+        assert!(many < few * 10, "{:?} with 100,000 commits, {:?} with 100", many, few);
+    }
+
+    #[tokio::test]
+    async fn test_diagnostics_count_dropped_commits_and_failed_batches() {
+        use crate::test_support::{commit, repository, user};
+
+        let repo = repository("octocat/hello");
+        // The second is a duplicate of the first, the third is not Rust and
+        // the fourth only changes an excluded path
+        let commits = vec![
+            (repo.clone(), commit("a", &[("main.rs", "+fn main() {}")])),
+            (repo.clone(), commit("b", &[("main.rs", "+fn main() {}")])),
+            (repo.clone(), commit("c", &[("notes.txt", "+fn main() {}")])),
+            (repo.clone(), commit("d", &[("vendor/lib.rs", "+pub fn lib() {}")])),
+        ];
+
+        let recordings = std::env::temp_dir().join(format!("gitanalyzer-diagnostics-{}", std::process::id()));
+        let pipeline = AnalysisPipeline::builder()
+            .github(GitHubClient::new("test-token").unwrap())
+            .llm(crate::llm::ReplayLLMProvider::new(recordings))
+            .store(Storage::in_memory().unwrap())
+            .config(PipelineConfig {
+                dedup_by_diff: true,
+                only_languages: vec!["rust".to_string()],
+                exclude_paths: vec!["vendor/".to_string()],
+                ..Default::default()
+            })
+            .progress(false)
+            .build()
+            .unwrap();
+
+        let result = pipeline
+            .analyze_from_commits(user("octocat"), vec![repo], commits)
+            .await
+            .unwrap();
+        assert_eq!(pipeline.diagnostics("Octocat"), result.diagnostics);
+        let diagnostics = result.diagnostics;
+        assert_eq!(diagnostics.commits_fetched, 4);
+        assert_eq!(diagnostics.commits_dropped_duplicate, 1);
+        assert_eq!(diagnostics.commits_dropped_excluded, 1);
+        assert_eq!(diagnostics.commits_dropped_language, 1);
+        assert_eq!(diagnostics.commits_analyzed, 1);
+        assert_eq!(diagnostics.batches_created, 1);
+        // Nothing was recorded, so the only batch fails
+        assert_eq!(diagnostics.batches_failed, 1);
+        assert_eq!(diagnostics.batches_succeeded + diagnostics.batches_skipped, 0);
+        assert_eq!(diagnostics.estimated_cost, None);
+    }
+
+    #[tokio::test]
+    async fn test_diagnostics_count_as_of_drops_apart_from_duplicates() {
+        use crate::test_support::{commit, repository, user};
+
+        let repo = repository("octocat/hello");
+        let old = |sha| {
+            let mut c = commit(sha, &[("main.rs", "+fn main() {}")]);
+            c.commit.author.date = "2020-06-01T12:00:00Z".parse().unwrap();
+            (repo.clone(), c)
+        };
+        // The second is a duplicate of the first; the third is after `as_of`
+        let commits = vec![old("a"), old("b"), (repo.clone(), commit("c", &[("lib.rs", "+pub fn f() {}")]))];
+
+        let pipeline = AnalysisPipeline::builder()
+            .github(GitHubClient::new("test-token").unwrap())
+            .llm(crate::llm::HeuristicProvider::new())
+            .store(Storage::in_memory().unwrap())
+            .config(PipelineConfig {
+                dedup_by_diff: true,
+                as_of: Some("2021-01-01T00:00:00Z".parse().unwrap()),
+                ..Default::default()
+            })
+            .progress(false)
+            .build()
+            .unwrap();
+
+        let diagnostics = pipeline
+            .analyze_from_commits(user("octocat"), vec![repo], commits)
+            .await
+            .unwrap()
+            .diagnostics;
+        assert_eq!(diagnostics.commits_fetched, 3);
+        assert_eq!(diagnostics.commits_dropped_as_of, 1);
+        assert_eq!(diagnostics.commits_dropped_duplicate, 1);
+        assert_eq!(diagnostics.commits_analyzed, 1);
+    }
+
+    #[tokio::test]
+    async fn test_failed_run_keeps_its_diagnostics() {
+        use crate::test_support::{repository, user, MockResponse, MockServer};
+
+        let mut fork = repository("octocat/fork");
+        fork.fork = true;
+        let repos = vec![repository("octocat/hello"), fork];
+        let server = MockServer::start(vec![
+            // The token's own login, looked up before the scope check
+            MockResponse::status(reqwest::StatusCode::UNAUTHORIZED),
+            MockResponse::json(serde_json::to_string(&user("octocat")).unwrap()),
+            MockResponse::json(serde_json::to_string(&repos).unwrap()),
+            // Listing contributed repositories fails the run
+            MockResponse::status(reqwest::StatusCode::INTERNAL_SERVER_ERROR),
+        ])
+        .await;
+        let pipeline = AnalysisPipeline::builder()
+            .github(GitHubClient::new("test-token").unwrap().with_base_url(server.url()))
+            .llm(crate::llm::ClaudeProvider::new("test-key".to_string(), None))
+            .config(PipelineConfig {
+                include_contributions: true,
+                ..Default::default()
+            })
+            .progress(false)
+            .build()
+            .unwrap();
+
+        assert!(pipeline.analyze_user("octocat").await.is_err());
+        let diagnostics = pipeline.diagnostics("octocat");
+        assert_eq!(diagnostics.repositories_listed, 2);
+        assert_eq!(diagnostics.commits_fetched, 0);
+    }
+
+    fn listing_pipeline(server: &crate::test_support::MockServer, config: PipelineConfig) -> AnalysisPipeline {
+        AnalysisPipeline::builder()
+            .github(GitHubClient::new("test-token").unwrap().with_base_url(server.url()))
+            .llm(crate::llm::HeuristicProvider::new())
+            .config(config)
+            .progress(false)
+            .build()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_commits_counted_by_author_email() {
+        use crate::test_support::{repository, MockResponse, MockServer};
+
+        let server = MockServer::start(vec![
+            MockResponse::json("[]"),
+            MockResponse::json("[]"),
+        ])
+        .await;
+        let pipeline = listing_pipeline(&server, PipelineConfig {
+            author_email: Some("dev@example.com".to_string()),
+            ..Default::default()
+        });
+
+        let (commits, warnings) = pipeline
+            .fetch_all_commits("octocat", &[repository("octocat/hello")])
+            .await
+            .unwrap();
+        assert!(commits.is_empty() && warnings.is_empty());
+        let requests = server.finish().await;
+        assert!(requests[0].target.contains("author=dev@example.com"), "{}", requests[0].target);
+        assert!(!requests[1].target.contains("author="), "{}", requests[1].target);
+    }
+
+    #[tokio::test]
+    async fn test_co_authored_commits_share_the_commit_cap() {
+        use crate::test_support::{commit, repository, MockResponse, MockServer};
+
+        let own = commit("a", &[("main.rs", "+fn main() {}")]);
+        let mut paired = commit("b", &[("lib.rs", "+pub fn f() {}")]);
+        paired.commit.message = "Pair on f\n\nCo-authored-by: octocat <octocat@users.noreply.github.com>".to_string();
+        let listing = |commits: &[&Commit]| MockResponse::json(serde_json::to_string(commits).unwrap());
+        let server = MockServer::start(vec![
+            // Count, own listing, co-author scan, then one commit's details
+            listing(&[&own]),
+            listing(&[&own]),
+            listing(&[&own, &paired]),
+            MockResponse::json(serde_json::to_string(&own).unwrap()),
+        ])
+        .await;
+        let pipeline = listing_pipeline(&server, PipelineConfig {
+            max_commits_per_repo: 1,
+            include_coauthored: true,
+            ..Default::default()
+        });
+
+        let (commits, warnings) = pipeline
+            .fetch_all_commits("octocat", &[repository("octocat/hello")])
+            .await
+            .unwrap();
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        let shas: Vec<_> = commits.iter().map(|(_, c)| c.sha.as_str()).collect();
+        assert_eq!(shas, vec!["a"]);
+        assert_eq!(server.finish().await.len(), 4);
+    }
+
+    /// Bills a fixed number of tokens per batch at $1 per token.
+    struct PricedLLM {
+        usage: std::sync::Mutex<TokenUsage>,
+    }
+
+    #[async_trait::async_trait]
+    impl LLMProvider for PricedLLM {
+        async fn analyze_commits(&self, _request: AnalysisRequest) -> Result<LLMAnalysisResult> {
+            let mut usage = self.usage.lock().unwrap();
+            usage.input_tokens += 1_000;
+            usage.output_tokens += 500;
+            Ok(LLMAnalysisResult {
+                skills: Vec::new(),
+                patterns: Vec::new(),
+                complexity_assessment: Default::default(),
+                quality_assessment: Default::default(),
+                domain_signals: Vec::new(),
+                notable_aspects: Vec::new(),
+            })
+        }
+
+        fn max_context_tokens(&self) -> usize {
+            200_000
+        }
+
+        fn name(&self) -> &str {
+            "Priced"
+        }
+
+        fn max_output_tokens(&self) -> usize {
+            1_000
+        }
+
+        fn usage(&self) -> TokenUsage {
+            *self.usage.lock().unwrap()
+        }
+
+        fn pricing(&self) -> Option<crate::llm::TokenPricing> {
+            Some(crate::llm::TokenPricing {
+                input_per_million: 1_000_000.0,
+                output_per_million: 1_000_000.0,
+            })
         }
     }
+
+    #[tokio::test]
+    async fn test_cost_cap_skips_batches_that_could_exceed_it() {
+        use crate::test_support::{commit, repository, user};
+
+        let repo = repository("octocat/hello");
+        let commits: Vec<_> = (0..4)
+            .map(|i| {
+                let patch = format!("+fn main() {{ {} }}", i);
+                (repo.clone(), commit(&format!("c{}", i), &[(&format!("main{}.rs", i), &patch)]))
+            })
+            .collect();
+
+        // Each batch bills $1,500, but up to $3,000 plus twice its prompt
+        // counting the JSON re-prompt. $4,600 leaves room for two batches;
+        // without the re-prompt in the estimate a third would start.
+        let pipeline = AnalysisPipeline::builder()
+            .github(GitHubClient::new("test-token").unwrap())
+            .llm(PricedLLM { usage: Default::default() })
+            .store(Storage::in_memory().unwrap())
+            .config(PipelineConfig {
+                max_cost: Some(4_600.0),
+                max_commits_per_batch: 1,
+                concurrency: crate::config::Concurrency { llm: 1, ..Default::default() },
+                ..Default::default()
+            })
+            .progress(false)
+            .build()
+            .unwrap();
+
+        let result = pipeline
+            .analyze_from_commits(user("octocat"), vec![repo], commits)
+            .await
+            .unwrap();
+        assert!(result.profile.incomplete);
+        assert_eq!(result.diagnostics.batches_created, 4);
+        assert_eq!(result.diagnostics.batches_succeeded, 2);
+        assert_eq!(result.diagnostics.batches_skipped, 2);
+        assert!(result.warnings.iter().any(|w| matches!(
+            w,
+            AnalysisWarning::CostCapReached { skipped_batches: 2, .. }
+        )));
+        assert_eq!(result.diagnostics.estimated_cost, Some(3_000.0));
+    }
+
+    #[test]
+    fn test_excludes() {
+        assert!(wildcard_match("vendor/", "vendor/lib/a.rs"));
+        assert!(!wildcard_match("vendor/", "src/vendor/a.rs"));
+        assert!(wildcard_match("*.min.js", "static/app.min.js"));
+        assert!(!wildcard_match("*.min.js", "static/app.js"));
+        assert!(wildcard_match("src/*/gen_*.rs", "src/api/gen_types.rs"));
+        assert!(wildcard_match("Cargo.lock", "Cargo.lock"));
+        assert!(!wildcard_match("Cargo.lock", "sub/Cargo.lock"));
+
+        let excludes = vec!["octocat/archive-*".to_string(), "hubot/Dotfiles".to_string()];
+        assert!(repository_excluded(&excludes, "octocat/archive-2019"));
+        assert!(repository_excluded(&excludes, "Hubot/dotfiles"));
+        assert!(!repository_excluded(&excludes, "octocat/hello"));
+    }
+
+    #[test]
+    fn test_language_selected() {
+        let only = vec!["rust".to_string(), "Go".to_string()];
+        assert!(language_selected(&only, Some("Rust")));
+        assert!(language_selected(&only, Some("go")));
+        assert!(!language_selected(&only, Some("JavaScript")));
+        assert!(!language_selected(&only, None));
+    }
+
+    #[test]
+    fn test_authored_by() {
+        use crate::models::commit::{CommitAuthor, CommitAuthorInfo, CommitDetails};
+
+        let commit = |login: Option<&str>, email: &str| CommitSummary {
+            sha: "abc".to_string(),
+            commit: CommitDetails {
+                message: String::new(),
+                author: CommitAuthor {
+                    name: "Octo Cat".to_string(),
+                    email: email.to_string(),
+                    date: Utc::now().into(),
+                },
+            },
+            author: login.map(|login| CommitAuthorInfo { login: login.to_string() }),
+        };
+
+        assert!(authored_by(&commit(Some("Octocat"), "work@example.com"), "octocat", "me@home.net"));
+        assert!(authored_by(&commit(None, "Me@Home.net"), "octocat", "me@home.net"));
+        assert!(!authored_by(&commit(Some("hubot"), "hubot@example.com"), "octocat", "me@home.net"));
+    }
+
+    #[test]
+    fn test_co_authored_by() {
+        use crate::models::commit::{CommitAuthor, CommitAuthorInfo, CommitDetails};
+
+        let commit = |trailer: &str| CommitSummary {
+            sha: "abc".to_string(),
+            commit: CommitDetails {
+                message: format!("Fix flaky test\n\nCo-authored-by: {}", trailer),
+                author: CommitAuthor {
+                    name: "Hubot".to_string(),
+                    email: "hubot@example.com".to_string(),
+                    date: Utc::now().into(),
+                },
+            },
+            author: Some(CommitAuthorInfo { login: "hubot".to_string() }),
+        };
+
+        assert!(co_authored_by(&commit("Mona <583231+Octocat@users.noreply.github.com>"), "octocat", None));
+        assert!(co_authored_by(&commit("octocat <octocat@users.noreply.github.com>"), "octocat", None));
+        assert!(co_authored_by(&commit("Mona <Me@Home.net>"), "octocat", Some("me@home.net")));
+        assert!(!co_authored_by(&commit("Mona <mona@example.com>"), "octocat", Some("me@home.net")));
+        assert!(!co_authored_by(&commit("Mona <1+notoctocat@users.noreply.github.com>"), "octocat", None));
+    }
+
+    #[test]
+    fn test_commit_cap() {
+        assert_eq!(commit_cap(Some(500), 50, Some(300)), 50);
+        assert_eq!(commit_cap(Some(501), 50, Some(300)), 300);
+        assert_eq!(commit_cap(Some(20_000), 50, None), 50);
+        assert_eq!(commit_cap(None, 50, Some(300)), 50);
+        assert!(is_large_repo(501, 50));
+        assert!(!is_large_repo(500, 50));
+    }
 }
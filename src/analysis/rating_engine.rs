@@ -1,232 +1,393 @@
-use std::collections::HashMap;
-use chrono::{Duration, Utc};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use chrono::{DateTime, Datelike, FixedOffset, TimeZone, Timelike, Utc};
 
+use crate::analysis::recommendations::{COMPLEMENT_RULES, DORMANT_SKILL_MIN_SCORE, STYLE_RULES};
+use crate::analysis::scoring::{ScoringContext, ScoringStrategy, WeightedScoring};
 use crate::models::analysis::{
-    CodingStyle, ExperienceLevel, LLMAnalysisResult, ProfileSummary, StrengthWeakness,
+    CodingStyle, ExperienceLevel, LLMAnalysisResult, ProcessMetrics, ProfileSummary,
+    Recommendation, StrengthWeakness,
 };
-use crate::models::skill::{
-    AggregatedSkill, SkillCategory, SkillDomain, SkillEvidence, SkillOccurrence, SkillRating,
-    SkillTrend,
-};
-
-pub struct RatingEngine {
-    weights: RatingWeights,
+use crate::models::commit::CommitForAnalysis;
+use crate::models::skill::{AggregatedSkill, SkillCategory, SkillDomain, SkillRating, SkillTrend};
+use crate::models::{PullRequest, Repository};
+
+pub use crate::analysis::scoring::RatingWeights;
+
+/// Maximum number of notable highlights kept in the summary.
+const MAX_NOTABLE_HIGHLIGHTS: usize = 8;
+
+/// Word-overlap (Jaccard) similarity above which two highlights are duplicates.
+const HIGHLIGHT_SIMILARITY_THRESHOLD: f32 = 0.7;
+
+/// Minimum proficiency score for a skill to lead its category.
+pub const CATEGORY_LEADER_MIN_SCORE: u8 = 30;
+
+/// Commits analyzed at or above which the commit-count component of
+/// `overall_confidence` is saturated.
+const CONFIDENT_COMMIT_COUNT: f32 = 200.0;
+
+/// Distinct repositories at or above which the diversity component of
+/// `overall_confidence` is saturated.
+const CONFIDENT_REPO_COUNT: f32 = 5.0;
+
+/// How much evidence went into a profile, used to derive `overall_confidence`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AnalysisStats {
+    pub commits_analyzed: usize,
+    /// Distinct repositories the analyzed commits came from
+    pub repositories: usize,
+    /// LLM batches sent, excluding any skipped on shutdown
+    pub batches_attempted: usize,
+    /// Batches whose request or response parsing failed
+    pub batches_failed: usize,
 }
 
-#[derive(Debug, Clone)]
-pub struct RatingWeights {
-    pub frequency_weight: f32,
-    pub recency_weight: f32,
-    pub complexity_weight: f32,
-    pub quality_weight: f32,
-    pub consistency_weight: f32,
-    pub proficiency_weight: f32,
-}
-
-impl Default for RatingWeights {
-    fn default() -> Self {
-        Self {
-            frequency_weight: 0.15,
-            recency_weight: 0.15,
-            complexity_weight: 0.20,
-            quality_weight: 0.20,
-            consistency_weight: 0.10,
-            proficiency_weight: 0.20,
-        }
+impl AnalysisStats {
+    /// Blends commit volume (log-scaled), repository diversity and the LLM
+    /// batch success rate into a single 0.0-1.0 figure.
+    pub fn overall_confidence(&self) -> f32 {
+        let commits = ((self.commits_analyzed as f32).ln_1p() / CONFIDENT_COMMIT_COUNT.ln_1p()).min(1.0);
+        let repos = (self.repositories as f32 / CONFIDENT_REPO_COUNT).min(1.0);
+        let parsed = if self.batches_attempted == 0 {
+            0.0
+        } else {
+            self.batches_attempted.saturating_sub(self.batches_failed) as f32
+                / self.batches_attempted as f32
+        };
+        0.4 * commits + 0.2 * repos + 0.4 * parsed
     }
 }
 
-impl RatingEngine {
-    pub fn new() -> Self {
-        Self {
-            weights: RatingWeights::default(),
+/// Kinds of contribution a commit can be classified as, in display order.
+pub const CONTRIBUTION_TYPES: [&str; 7] = ["feature", "fix", "refactor", "docs", "test", "chore", "other"];
+
+/// Classifies a commit as one of `CONTRIBUTION_TYPES` from its message. A
+/// Conventional Commits prefix (`feat(api)!: ...`) decides when present;
+/// otherwise the first keyword in the subject line does.
+pub fn classify_commit(message: &str) -> &'static str {
+    let subject = message.lines().next().unwrap_or("").trim().to_lowercase();
+
+    if let Some((prefix, _)) = subject.split_once(':') {
+        let kind = prefix
+            .trim_end_matches('!')
+            .split('(')
+            .next()
+            .unwrap_or("");
+        let conventional = match kind {
+            "feat" | "feature" => Some("feature"),
+            "fix" | "bugfix" | "hotfix" => Some("fix"),
+            "refactor" | "perf" | "style" => Some("refactor"),
+            "docs" | "doc" => Some("docs"),
+            "test" | "tests" => Some("test"),
+            "chore" | "build" | "ci" | "deps" | "release" => Some("chore"),
+            _ => None,
+        };
+        if let Some(kind) = conventional {
+            return kind;
         }
     }
 
-    pub fn calculate_ratings(
-        &self,
-        aggregated_skills: &HashMap<String, AggregatedSkill>,
-    ) -> Vec<SkillRating> {
-        let mut ratings: Vec<SkillRating> = aggregated_skills
-            .values()
-            .map(|agg| self.calculate_single_rating(agg))
-            .collect();
-
-        // Sort by proficiency score (descending)
-        ratings.sort_by(|a, b| b.proficiency_score.cmp(&a.proficiency_score));
+    subject
+        .split(|c: char| !c.is_alphanumeric())
+        .find_map(|word| match word {
+            "fix" | "fixes" | "fixed" | "fixing" | "bug" | "bugfix" | "hotfix" | "resolve"
+            | "resolves" | "patch" => Some("fix"),
+            "add" | "adds" | "added" | "implement" | "implements" | "implemented"
+            | "introduce" | "introduces" | "support" | "feature" | "feat" => Some("feature"),
+            "refactor" | "refactored" | "refactoring" | "restructure" | "cleanup" | "simplify"
+            | "simplified" | "rename" | "renamed" | "extract" | "optimize" => Some("refactor"),
+            "doc" | "docs" | "readme" | "documentation" | "changelog" => Some("docs"),
+            "test" | "tests" | "testing" | "spec" | "specs" | "coverage" => Some("test"),
+            "chore" | "bump" | "bumps" | "merge" | "release" | "deps" | "dependencies"
+            | "ci" | "version" => Some("chore"),
+            _ => None,
+        })
+        .unwrap_or("other")
+}
 
-        ratings
+/// Share (0.0-1.0) of commits of each contribution type, keyed by the
+/// `CONTRIBUTION_TYPES` that occur at least once. Empty without commits.
+pub fn contribution_breakdown<'a>(messages: impl IntoIterator<Item = &'a str>) -> HashMap<String, f32> {
+    let mut counts: HashMap<&str, u32> = HashMap::new();
+    for message in messages {
+        *counts.entry(classify_commit(message)).or_insert(0) += 1;
     }
+    let total: u32 = counts.values().sum();
+    counts
+        .into_iter()
+        .map(|(kind, count)| (kind.to_string(), count as f32 / total as f32))
+        .collect()
+}
 
-    fn calculate_single_rating(&self, agg: &AggregatedSkill) -> SkillRating {
-        let now = Utc::now();
-
-        // 1. Frequency score (normalized by log scale, max at ~100 occurrences)
-        let frequency_score = ((agg.occurrences.len() as f32).ln() + 1.0).min(5.0) / 5.0 * 100.0;
-
-        // 2. Recency score
-        let most_recent = agg
-            .occurrences
-            .iter()
-            .map(|o| o.timestamp)
-            .max()
-            .unwrap_or(now);
-        let days_since = (now - most_recent).num_days().max(0) as f32;
-        let recency_score = (1.0 - (days_since / 365.0).min(1.0)) * 100.0;
-
-        // 3. Complexity score (average of LLM assessments, scaled to 100)
-        let complexity_score = if agg.complexity_scores.is_empty() {
-            50.0
-        } else {
-            agg.complexity_scores.iter().sum::<f32>() / agg.complexity_scores.len() as f32 * 10.0
-        };
+/// Share (0.0-1.0) of the lines the user changed (additions plus deletions)
+/// in each language, from the files in their analyzed commits. Files in no
+/// recognized language are left out. Empty when no lines were recognized.
+pub fn commit_language_breakdown(commits: &[CommitForAnalysis]) -> HashMap<String, f32> {
+    let mut lines: HashMap<&str, u32> = HashMap::new();
+    for file in commits.iter().flat_map(|c| &c.files_changed) {
+        if let Some(language) = file.language.as_deref() {
+            *lines.entry(language).or_insert(0) += file.additions + file.deletions;
+        }
+    }
+    shares(lines)
+}
 
-        // 4. Quality score (average of LLM assessments, scaled to 100)
-        let quality_score = if agg.quality_scores.is_empty() {
-            50.0
-        } else {
-            agg.quality_scores.iter().sum::<f32>() / agg.quality_scores.len() as f32 * 10.0
-        };
+/// Share (0.0-1.0) of repositories with each primary language, as GitHub
+/// reports it for the whole repository regardless of who wrote what.
+/// Repositories without a language are left out.
+pub fn repository_language_breakdown(repos: &[Repository]) -> HashMap<String, f32> {
+    let mut counts: HashMap<&str, u32> = HashMap::new();
+    for language in repos.iter().filter_map(|r| r.language.as_deref()) {
+        *counts.entry(language).or_insert(0) += 1;
+    }
+    shares(counts)
+}
 
-        // 5. Consistency score (how regularly the skill is used)
-        let consistency_score = self.calculate_consistency(&agg.occurrences);
+/// Branch name prefixes that mark a branching model such as Git Flow.
+const BRANCH_PREFIXES: [&str; 9] = [
+    "feature/", "feat/", "fix/", "bugfix/", "hotfix/", "release/", "chore/", "docs/", "refactor/",
+];
+
+/// Merge rate, size and branch naming across `pull_requests`. Rates and
+/// averages are `None` when no pull request has the data they need.
+pub fn process_metrics(pull_requests: &[PullRequest]) -> ProcessMetrics {
+    let ratio = |count: usize, total: usize| (total > 0).then(|| count as f32 / total as f32);
+
+    let closed: Vec<_> = pull_requests.iter().filter(|pr| pr.closed_at.is_some()).collect();
+    let merged = closed.iter().filter(|pr| pr.is_merged()).count();
+
+    let details: Vec<_> = pull_requests.iter().filter_map(|pr| pr.details.as_ref()).collect();
+    let lines: u32 = details.iter().map(|d| d.additions + d.deletions).sum();
+    let prefixed = details
+        .iter()
+        .filter(|d| {
+            let branch = d.head.branch.to_lowercase();
+            BRANCH_PREFIXES.iter().any(|prefix| branch.starts_with(prefix))
+        })
+        .count();
+
+    ProcessMetrics {
+        pull_requests: pull_requests.len() as u32,
+        merge_rate: ratio(merged, closed.len()),
+        average_size: (!details.is_empty()).then(|| lines as f32 / details.len() as f32),
+        prefixed_branch_rate: ratio(prefixed, details.len()),
+    }
+}
 
-        // 6. Proficiency score from LLM assessments
-        let proficiency_score = self.calculate_proficiency_from_signals(&agg.occurrences);
+/// Each key's share of the total, or empty when the total is zero.
+fn shares(counts: HashMap<&str, u32>) -> HashMap<String, f32> {
+    let total: u32 = counts.values().sum();
+    if total == 0 {
+        return HashMap::new();
+    }
+    counts
+        .into_iter()
+        .map(|(key, count)| (key.to_string(), count as f32 / total as f32))
+        .collect()
+}
 
-        // Weighted combination
-        let final_score = (frequency_score * self.weights.frequency_weight
-            + recency_score * self.weights.recency_weight
-            + complexity_score * self.weights.complexity_weight
-            + quality_score * self.weights.quality_weight
-            + consistency_score * self.weights.consistency_weight
-            + proficiency_score * self.weights.proficiency_weight)
-            .round() as u8;
+/// Bins commit dates by calendar month, from the earliest month to the latest
+/// (inclusive). Months without commits are kept as zero so the bins are evenly
+/// spaced in time.
+pub fn commit_histogram(commit_dates: &[DateTime<Utc>]) -> Vec<u32> {
+    let month_index = |date: &DateTime<Utc>| date.year() * 12 + date.month0() as i32;
+
+    let (Some(first), Some(last)) = (
+        commit_dates.iter().map(month_index).min(),
+        commit_dates.iter().map(month_index).max(),
+    ) else {
+        return Vec::new();
+    };
+
+    let mut bins = vec![0; (last - first + 1) as usize];
+    for date in commit_dates {
+        bins[(month_index(date) - first) as usize] += 1;
+    }
+    bins
+}
 
-        // Calculate confidence based on evidence quantity
-        let confidence = (agg.occurrences.len() as f32 / 20.0).min(1.0);
+/// The UTC offset most commits were authored in, or `None` without commits.
+/// Ties go to the offset furthest west.
+pub fn modal_utc_offset(commit_dates: &[DateTime<FixedOffset>]) -> Option<FixedOffset> {
+    let mut counts: HashMap<i32, u32> = HashMap::new();
+    for date in commit_dates {
+        *counts.entry(date.offset().local_minus_utc()).or_insert(0) += 1;
+    }
+    counts
+        .into_iter()
+        .max_by_key(|&(offset, count)| (count, -offset))
+        .and_then(|(offset, _)| FixedOffset::east_opt(offset))
+}
 
-        // Determine trend
-        let trend = self.calculate_trend(&agg.occurrences);
+/// Commits per hour of the day (24 bins, 0 = midnight), in each date's own
+/// offset: local time for dates localized with
+/// `GitHubClient::localize_author_dates`, UTC otherwise. Empty without
+/// commits.
+pub fn working_hours(commit_dates: &[DateTime<FixedOffset>]) -> Vec<u32> {
+    if commit_dates.is_empty() {
+        return Vec::new();
+    }
+    let mut bins = vec![0; 24];
+    for date in commit_dates {
+        bins[date.hour() as usize] += 1;
+    }
+    bins
+}
 
-        // Build evidence
-        let first_seen = agg
-            .occurrences
-            .iter()
-            .map(|o| o.timestamp)
-            .min()
-            .unwrap_or(now);
-
-        let evidence = SkillEvidence {
-            commit_count: agg.occurrences.len() as u32,
-            total_lines_changed: agg.total_lines,
-            first_seen,
-            last_seen: most_recent,
-            repositories: agg.repositories(),
-        };
+/// Midnight UTC on the first day of the quarter that is `offset` quarters
+/// after the one containing `date`.
+fn quarter_start(date: DateTime<Utc>, offset: i32) -> DateTime<Utc> {
+    let quarter = date.year() * 4 + date.month0() as i32 / 3 + offset;
+    Utc.with_ymd_and_hms(quarter.div_euclid(4), quarter.rem_euclid(4) as u32 * 3 + 1, 1, 0, 0, 0)
+        .unwrap()
+}
 
-        SkillRating {
-            skill: agg.skill.clone(),
-            proficiency_score: final_score.max(1).min(100),
-            percentile_rank: None,
-            confidence,
-            evidence,
-            trend,
+/// Returns the highest-rated skill in each category, skipping categories
+/// whose best skill scores below `CATEGORY_LEADER_MIN_SCORE`.
+pub fn top_by_category(ratings: &[SkillRating]) -> HashMap<SkillCategory, SkillRating> {
+    let mut leaders: HashMap<SkillCategory, SkillRating> = HashMap::new();
+
+    for rating in ratings
+        .iter()
+        .filter(|r| r.proficiency_score >= CATEGORY_LEADER_MIN_SCORE)
+    {
+        let is_better = leaders
+            .get(&rating.skill.category)
+            .map(|current| rating.proficiency_score > current.proficiency_score)
+            .unwrap_or(true);
+        if is_better {
+            leaders.insert(rating.skill.category.clone(), rating.clone());
         }
     }
 
-    fn calculate_proficiency_from_signals(&self, occurrences: &[SkillOccurrence]) -> f32 {
-        if occurrences.is_empty() {
-            return 50.0;
-        }
+    leaders
+}
 
-        let level_scores: Vec<(f32, f32)> = occurrences
-            .iter()
-            .map(|o| {
-                let score = match o.proficiency_signal.to_lowercase().as_str() {
-                    "expert" => 95.0,
-                    "advanced" => 80.0,
-                    "intermediate" => 60.0,
-                    "beginner" => 35.0,
-                    _ => 50.0,
-                };
-                (score, o.confidence)
-            })
-            .collect();
+pub struct RatingEngine {
+    strategy: Box<dyn ScoringStrategy>,
+    /// Skills seen in fewer commits than this are dropped before rating.
+    /// Since `confidence` scales with commit count (20 commits = 100%), this
+    /// effectively removes the lowest-confidence ratings from the output.
+    min_commits_per_skill: u32,
+    /// Rate as of this moment instead of the current time (`--as-of`)
+    as_of: Option<DateTime<Utc>>,
+}
 
-        // Weighted average by confidence
-        let total_weight: f32 = level_scores.iter().map(|(_, c)| c).sum();
-        if total_weight == 0.0 {
-            return 50.0;
-        }
+impl RatingEngine {
+    pub fn new() -> Self {
+        Self::with_strategy(Box::new(WeightedScoring::default()))
+    }
 
-        let weighted_sum: f32 = level_scores.iter().map(|(s, c)| s * c).sum();
-        weighted_sum / total_weight
+    /// Fails if `weights` does not pass `RatingWeights::validate`.
+    pub fn with_weights(weights: RatingWeights) -> crate::error::Result<Self> {
+        Ok(Self::with_strategy(Box::new(WeightedScoring::new(weights)?)))
     }
 
-    fn calculate_consistency(&self, occurrences: &[SkillOccurrence]) -> f32 {
-        if occurrences.len() < 2 {
-            return 50.0;
+    pub fn with_strategy(strategy: Box<dyn ScoringStrategy>) -> Self {
+        Self {
+            strategy,
+            min_commits_per_skill: 1,
+            as_of: None,
         }
+    }
 
-        let mut timestamps: Vec<_> = occurrences.iter().map(|o| o.timestamp).collect();
-        timestamps.sort();
-
-        let gaps: Vec<i64> = timestamps.windows(2).map(|w| (w[1] - w[0]).num_days()).collect();
+    /// Measures recency, age decay and trends from `as_of` rather than the
+    /// current time, to see a profile as it would have looked then. Callers
+    /// must leave out occurrences after `as_of` themselves.
+    pub fn with_as_of(mut self, as_of: Option<DateTime<Utc>>) -> Self {
+        self.as_of = as_of;
+        self
+    }
 
-        if gaps.is_empty() {
-            return 50.0;
-        }
+    /// The moment ratings are computed as of: `as_of` if set, otherwise the
+    /// current time. The only place the engine reads the clock.
+    pub fn now(&self) -> DateTime<Utc> {
+        self.as_of.unwrap_or_else(Utc::now)
+    }
 
-        let avg_gap = gaps.iter().sum::<i64>() as f32 / gaps.len() as f32;
-        // Good consistency = gaps of ~7 days or less
-        // Poor consistency = gaps of 90+ days
-        let consistency = (1.0 - (avg_gap / 90.0).min(1.0)) * 100.0;
+    pub fn with_min_commits_per_skill(mut self, min_commits: u32) -> Self {
+        self.min_commits_per_skill = min_commits;
+        self
+    }
 
-        consistency.max(0.0)
+    pub fn calculate_ratings(
+        &self,
+        aggregated_skills: &HashMap<String, AggregatedSkill>,
+    ) -> Vec<SkillRating> {
+        self.calculate_ratings_with(aggregated_skills, |_| {})
     }
 
-    fn calculate_trend(&self, occurrences: &[SkillOccurrence]) -> SkillTrend {
-        let now = Utc::now();
-        let six_months_ago = now - Duration::days(180);
-        let one_year_ago = now - Duration::days(365);
+    /// Like `calculate_ratings`, but calls `on_rating` with each rating as soon
+    /// as it is computed (in skill name order, before the final sort).
+    pub fn calculate_ratings_with(
+        &self,
+        aggregated_skills: &HashMap<String, AggregatedSkill>,
+        mut on_rating: impl FnMut(&SkillRating),
+    ) -> Vec<SkillRating> {
+        // Rate in key order so results don't depend on HashMap iteration order
+        let mut skills: Vec<_> = aggregated_skills.iter().collect();
+        skills.sort_by(|a, b| a.0.cmp(b.0));
 
-        let recent_count = occurrences
-            .iter()
-            .filter(|o| o.timestamp > six_months_ago)
-            .count();
-        let older_count = occurrences
-            .iter()
-            .filter(|o| o.timestamp <= six_months_ago && o.timestamp > one_year_ago)
-            .count();
+        let context = ScoringContext::from_skills(aggregated_skills, self.now());
 
-        if occurrences.len() <= 2 {
-            return SkillTrend::New;
-        }
+        let mut ratings: Vec<SkillRating> = skills
+            .into_iter()
+            .map(|(_, agg)| agg)
+            .filter(|agg| agg.weighted_commits().ceil() >= self.min_commits_per_skill as f32)
+            .map(|agg| SkillRating {
+                trend_series: self.trend_series(agg, &context),
+                ..self.strategy.score(agg, &context)
+            })
+            .inspect(|rating| on_rating(rating))
+            .collect();
 
-        if recent_count == 0 && older_count > 0 {
-            return SkillTrend::Dormant;
-        }
+        // Sort by proficiency score (descending), ties broken by name
+        ratings.sort_by_key(|r| (Reverse(r.proficiency_score), r.skill.name.to_lowercase()));
 
-        let ratio = if older_count > 0 {
-            recent_count as f32 / older_count as f32
-        } else if recent_count > 0 {
-            2.0 // Active recently with no older history = improving
-        } else {
-            1.0 // No activity = stable (shouldn't happen)
-        };
+        ratings
+    }
 
-        match ratio {
-            r if r > 1.5 => SkillTrend::Improving,
-            r if r < 0.5 => SkillTrend::Declining,
-            _ => SkillTrend::Stable,
+    /// Scores each calendar quarter's occurrences of a skill on their own,
+    /// oldest quarter first. Each quarter is scored as if it had just ended,
+    /// so recency reflects activity within the quarter rather than its age.
+    pub fn trend_series(
+        &self,
+        agg: &AggregatedSkill,
+        context: &ScoringContext,
+    ) -> Vec<(DateTime<Utc>, u8)> {
+        let now = context.now;
+        let scored = agg.complexity_scores.iter().zip(&agg.quality_scores).map(Some);
+
+        let mut quarters: BTreeMap<DateTime<Utc>, AggregatedSkill> = BTreeMap::new();
+        for (occurrence, scores) in agg.occurrences.iter().zip(scored.chain(std::iter::repeat(None))) {
+            let start = quarter_start(occurrence.timestamp, 0);
+            let shift = (now - quarter_start(occurrence.timestamp, 1)).max(chrono::Duration::zero());
+
+            let bucket = quarters
+                .entry(start)
+                .or_insert_with(|| AggregatedSkill::new(agg.skill.clone()));
+            let mut occurrence = occurrence.clone();
+            occurrence.timestamp += shift;
+            bucket.total_lines += occurrence.lines_changed;
+            bucket.occurrences.push(occurrence);
+            if let Some((&complexity, &quality)) = scores {
+                bucket.complexity_scores.push(complexity);
+                bucket.quality_scores.push(quality);
+            }
         }
+
+        quarters
+            .into_iter()
+            .map(|(start, bucket)| (start, self.strategy.score(&bucket, context).proficiency_score))
+            .collect()
     }
 
     pub fn generate_summary(
         &self,
         skill_ratings: &[SkillRating],
         analyses: &[LLMAnalysisResult],
+        stats: &AnalysisStats,
     ) -> ProfileSummary {
         let primary_languages = self.extract_primary_languages(skill_ratings);
         let primary_domains = self.extract_primary_domains(analyses);
@@ -234,15 +395,133 @@ impl RatingEngine {
         let weaknesses = self.detect_weaknesses(skill_ratings, analyses);
         let experience_level = self.assess_experience_level(skill_ratings);
         let coding_style = self.assess_coding_style(analyses);
+        let notable_highlights = self.extract_notable_highlights(analyses);
 
-        ProfileSummary {
+        let mut summary = ProfileSummary {
             primary_languages,
             primary_domains,
             strengths,
             weaknesses,
             experience_level,
             coding_style,
+            recommendations: Vec::new(),
+            notable_highlights,
+            overall_confidence: stats.overall_confidence(),
+            ..ProfileSummary::default()
+        };
+        summary.recommendations = self.generate_recommendations(&summary, skill_ratings);
+        summary
+    }
+
+    /// Produces rule-based suggestions from weak coding style metrics, missing
+    /// complementary skills, and dormant high-value skills. Rules live in
+    /// `analysis::recommendations`.
+    pub fn generate_recommendations(
+        &self,
+        summary: &ProfileSummary,
+        ratings: &[SkillRating],
+    ) -> Vec<Recommendation> {
+        let mut recommendations: Vec<Recommendation> = Vec::new();
+        let skill_names: Vec<String> = ratings.iter().map(|r| r.skill.name.to_lowercase()).collect();
+        let has_skill = |name: &str| skill_names.iter().any(|s| s == name);
+
+        // Coding style metrics below threshold
+        for rule in STYLE_RULES {
+            if (rule.metric)(&summary.coding_style) < rule.threshold {
+                recommendations.push(Recommendation {
+                    area: rule.area.to_string(),
+                    suggestion: rule.suggestion.to_string(),
+                    priority: 1,
+                });
+            }
+        }
+
+        // Missing complementary skills
+        for rule in COMPLEMENT_RULES {
+            if recommendations.iter().any(|r| r.area == rule.area) {
+                continue;
+            }
+            if rule.has_any.iter().any(|s| has_skill(s))
+                && !rule.missing_all.iter().any(|s| has_skill(s))
+            {
+                recommendations.push(Recommendation {
+                    area: rule.area.to_string(),
+                    suggestion: rule.suggestion.to_string(),
+                    priority: 2,
+                });
+            }
+        }
+
+        // Dormant skills that were once strong
+        for rating in ratings.iter().filter(|r| {
+            r.trend == SkillTrend::Dormant && r.proficiency_score >= DORMANT_SKILL_MIN_SCORE
+        }) {
+            recommendations.push(Recommendation {
+                area: rating.skill.display_name.clone(),
+                suggestion: format!(
+                    "Previously strong {} skill ({}/100) unused for 6+ months; consider refreshing it",
+                    rating.skill.display_name, rating.proficiency_score
+                ),
+                priority: 3,
+            });
+        }
+
+        recommendations
+    }
+
+    /// Collects `notable_aspects` and quality `observations` across batches,
+    /// merges near-identical strings, and returns the most frequent ones.
+    fn extract_notable_highlights(&self, analyses: &[LLMAnalysisResult]) -> Vec<String> {
+        // (representative text, normalized words, occurrence count)
+        let mut clusters: Vec<(String, HashSet<String>, u32)> = Vec::new();
+
+        let candidates = analyses.iter().flat_map(|a| {
+            a.notable_aspects
+                .iter()
+                .chain(a.quality_assessment.observations.iter())
+        });
+
+        for text in candidates {
+            let text = text.trim();
+            let words = highlight_words(text);
+            if words.is_empty() {
+                continue;
+            }
+
+            match clusters
+                .iter_mut()
+                .find(|(_, existing, _)| jaccard(existing, &words) >= HIGHLIGHT_SIMILARITY_THRESHOLD)
+            {
+                Some(cluster) => cluster.2 += 1,
+                None => clusters.push((text.to_string(), words, 1)),
+            }
+        }
+
+        // Stable sort keeps first-seen order among equally frequent highlights
+        clusters.sort_by_key(|c| Reverse(c.2));
+        clusters
+            .into_iter()
+            .take(MAX_NOTABLE_HIGHLIGHTS)
+            .map(|(text, _, _)| text)
+            .collect()
+    }
+
+    /// Fills the activity span and peak year from commit timestamps.
+    pub fn assess_activity(&self, summary: &mut ProfileSummary, commit_dates: &[DateTime<Utc>]) {
+        summary.activity_start = commit_dates.iter().min().copied();
+        summary.activity_end = commit_dates.iter().max().copied();
+
+        let mut per_year: HashMap<i32, u32> = HashMap::new();
+        for date in commit_dates {
+            *per_year.entry(date.year()).or_insert(0) += 1;
         }
+        // Ties go to the more recent year
+        summary.activity_peak_year = per_year
+            .into_iter()
+            .max_by_key(|&(year, count)| (count, year))
+            .map(|(year, _)| year);
+
+        summary.commit_histogram = commit_histogram(commit_dates);
     }
 
     fn extract_primary_languages(&self, ratings: &[SkillRating]) -> Vec<String> {
@@ -251,7 +530,7 @@ impl RatingEngine {
             .filter(|r| r.skill.category == SkillCategory::Language)
             .filter(|r| r.proficiency_score >= 40)
             .take(5)
-            .map(|r| r.skill.name.clone())
+            .map(|r| r.skill.display_name.clone())
             .collect()
     }
 
@@ -265,7 +544,7 @@ impl RatingEngine {
         }
 
         let mut domains: Vec<_> = domain_counts.into_iter().collect();
-        domains.sort_by(|a, b| b.1.cmp(&a.1));
+        domains.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
 
         domains
             .into_iter()
@@ -298,7 +577,7 @@ impl RatingEngine {
         // High proficiency skills
         for rating in ratings.iter().filter(|r| r.proficiency_score >= 70) {
             strengths.push(StrengthWeakness {
-                area: rating.skill.name.clone(),
+                area: rating.skill.display_name.clone(),
                 description: format!(
                     "Strong {} proficiency with {} commits",
                     rating.skill.category, rating.evidence.commit_count
@@ -341,7 +620,7 @@ impl RatingEngine {
             });
         }
 
-        strengths.sort_by(|a, b| b.score.cmp(&a.score));
+        strengths.sort_by_key(|s| Reverse(s.score));
         strengths.truncate(5);
         strengths
     }
@@ -391,10 +670,10 @@ impl RatingEngine {
         // Declining skills
         for rating in ratings.iter().filter(|r| r.trend == SkillTrend::Declining) {
             weaknesses.push(StrengthWeakness {
-                area: rating.skill.name.clone(),
+                area: rating.skill.display_name.clone(),
                 description: format!(
                     "{} usage declining over time",
-                    rating.skill.name
+                    rating.skill.display_name
                 ),
                 evidence: vec![format!(
                     "Last used: {}",
@@ -421,7 +700,7 @@ impl RatingEngine {
             });
         }
 
-        weaknesses.sort_by(|a, b| a.score.cmp(&b.score));
+        weaknesses.sort_by_key(|w| w.score);
         weaknesses.truncate(5);
         weaknesses
     }
@@ -501,8 +780,333 @@ impl RatingEngine {
     }
 }
 
+fn highlight_words(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect()
+}
+
+fn jaccard(a: &HashSet<String>, b: &HashSet<String>) -> f32 {
+    let union = a.union(b).count();
+    if union == 0 {
+        return 0.0;
+    }
+    a.intersection(b).count() as f32 / union as f32
+}
+
 impl Default for RatingEngine {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::analysis::{ComplexityAssessment, QualityAssessment};
+
+    fn analysis_with(aspects: &[&str], observations: &[&str]) -> LLMAnalysisResult {
+        LLMAnalysisResult {
+            skills: Vec::new(),
+            patterns: Vec::new(),
+            complexity_assessment: ComplexityAssessment::default(),
+            quality_assessment: QualityAssessment {
+                observations: observations.iter().map(|s| s.to_string()).collect(),
+                ..QualityAssessment::default()
+            },
+            domain_signals: Vec::new(),
+            notable_aspects: aspects.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_notable_highlights_dedupes_near_identical() {
+        let analyses = vec![
+            analysis_with(&["Consistent use of Result for error handling"], &["Good test coverage"]),
+            analysis_with(&["Consistent use of Result for error handling."], &[]),
+            analysis_with(&["consistent use of result for error-handling"], &["Good test coverage"]),
+        ];
+
+        let highlights = RatingEngine::new().extract_notable_highlights(&analyses);
+        assert_eq!(
+            highlights,
+            vec![
+                "Consistent use of Result for error handling".to_string(),
+                "Good test coverage".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_classify_commit() {
+        assert_eq!(classify_commit("feat(api)!: drop v1 endpoints"), "feature");
+        assert_eq!(classify_commit("perf: cache parsed configs"), "refactor");
+        assert_eq!(classify_commit("ci: run clippy on PRs"), "chore");
+        assert_eq!(classify_commit("Fix crash when the token is empty\n\nAdds a test."), "fix");
+        assert_eq!(classify_commit("Add dark mode toggle"), "feature");
+        assert_eq!(classify_commit("Update README with install steps"), "docs");
+        assert_eq!(classify_commit("Merge pull request #12 from octocat/main"), "chore");
+        assert_eq!(classify_commit("wip"), "other");
+        // Unknown conventional types fall back to keywords
+        assert_eq!(classify_commit("ui: fix button alignment"), "fix");
+
+        let breakdown = contribution_breakdown(["feat: a", "fix: b", "fix: c", "tweak"]);
+        assert_eq!(breakdown.get("fix"), Some(&0.5));
+        assert_eq!(breakdown.get("feature"), Some(&0.25));
+        assert_eq!(breakdown.get("other"), Some(&0.25));
+        assert!(!breakdown.contains_key("docs"));
+        assert!(contribution_breakdown([]).is_empty());
+    }
+
+    #[test]
+    fn test_process_metrics() {
+        use crate::models::{PullRequestDetails, PullRequestHead, PullRequestState};
+
+        let pull_request = |closed: bool, merged: bool, details: Option<(u32, &str)>| PullRequest {
+            number: 1,
+            title: String::new(),
+            repository_url: "https://api.github.com/repos/octocat/hello".to_string(),
+            created_at: Utc::now(),
+            closed_at: closed.then(Utc::now),
+            pull_request: PullRequestState {
+                merged_at: merged.then(Utc::now),
+            },
+            details: details.map(|(lines, branch)| PullRequestDetails {
+                additions: lines,
+                deletions: 0,
+                head: PullRequestHead {
+                    branch: branch.to_string(),
+                },
+            }),
+        };
+
+        let metrics = process_metrics(&[
+            pull_request(true, true, Some((100, "feature/login"))),
+            pull_request(true, false, Some((20, "patch-1"))),
+            pull_request(true, true, None),
+            pull_request(false, false, Some((30, "Fix/typo"))),
+        ]);
+        assert_eq!(metrics.pull_requests, 4);
+        // Open pull requests don't count towards the merge rate
+        assert!((metrics.merge_rate.unwrap() - 2.0 / 3.0).abs() < 1e-6);
+        assert_eq!(metrics.average_size, Some(50.0));
+        assert!((metrics.prefixed_branch_rate.unwrap() - 2.0 / 3.0).abs() < 1e-6);
+        assert_eq!(pull_request(false, false, None).repository(), Some(("octocat", "hello")));
+
+        let empty = process_metrics(&[]);
+        assert_eq!((empty.merge_rate, empty.average_size), (None, None));
+    }
+
+    #[test]
+    fn test_commit_language_breakdown_weights_by_lines() {
+        use crate::models::commit::{CommitStats, FileForAnalysis};
+
+        let file = |filename: &str, language: Option<&str>, additions: u32, deletions: u32| FileForAnalysis {
+            filename: filename.to_string(),
+            language: language.map(str::to_string),
+            diff: String::new(),
+            additions,
+            deletions,
+        };
+        let commit = |files_changed: Vec<FileForAnalysis>| CommitForAnalysis {
+            sha: String::new(),
+            repository: "octocat/web".to_string(),
+            message: String::new(),
+            stats: CommitStats::default(),
+            files_changed,
+            binary_files_changed: 0,
+            committed_at: Utc::now(),
+            collaborative: false,
+        };
+
+        let commits = vec![
+            commit(vec![file("api/app.py", Some("Python"), 50, 10), file("README", None, 100, 0)]),
+            commit(vec![file("api/models.py", Some("Python"), 15, 5), file("web/index.js", Some("JavaScript"), 15, 5)]),
+        ];
+        let breakdown = commit_language_breakdown(&commits);
+        assert_eq!(breakdown.len(), 2);
+        assert!((breakdown["Python"] - 0.8).abs() < 1e-6);
+        assert!((breakdown["JavaScript"] - 0.2).abs() < 1e-6);
+        assert!(commit_language_breakdown(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_timezone_and_working_hours() {
+        let date = |s: &str| DateTime::parse_from_rfc3339(s).unwrap();
+        let dates = [
+            date("2024-03-01T09:15:00+02:00"),
+            date("2024-03-02T22:40:00+02:00"),
+            date("2024-03-03T09:05:00-05:00"),
+        ];
+
+        assert_eq!(modal_utc_offset(&dates).unwrap().to_string(), "+02:00");
+        // Binned by local hour, not UTC
+        let hours = working_hours(&dates);
+        assert_eq!(hours.len(), 24);
+        assert_eq!((hours[9], hours[22], hours.iter().sum::<u32>()), (2, 1, 3));
+
+        // One commit each: the westernmost offset wins
+        assert_eq!(modal_utc_offset(&dates[1..]).unwrap().to_string(), "-05:00");
+        assert_eq!(modal_utc_offset(&[]), None);
+        assert!(working_hours(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_commit_histogram_bins_by_month() {
+        use chrono::TimeZone;
+        let date = |y, m, d| Utc.with_ymd_and_hms(y, m, d, 12, 0, 0).unwrap();
+
+        assert!(commit_histogram(&[]).is_empty());
+        assert_eq!(
+            commit_histogram(&[date(2023, 11, 3), date(2024, 2, 1), date(2023, 11, 30)]),
+            vec![2, 0, 0, 1]
+        );
+    }
+
+    #[test]
+    fn test_overall_confidence() {
+        assert_eq!(AnalysisStats::default().overall_confidence(), 0.0);
+
+        let full = AnalysisStats {
+            commits_analyzed: 500,
+            repositories: 8,
+            batches_attempted: 10,
+            batches_failed: 0,
+        };
+        assert!((full.overall_confidence() - 1.0).abs() < 1e-6);
+
+        // Half the batches failing costs a fifth of the confidence
+        let flaky = AnalysisStats { batches_failed: 5, ..full };
+        assert!((flaky.overall_confidence() - 0.8).abs() < 1e-6);
+    }
+
+    /// One skill per `(name, commit count)`, all seen in the same repo just now.
+    fn aggregated(skills: &[(&str, usize)]) -> HashMap<String, AggregatedSkill> {
+        use crate::models::skill::SkillOccurrence;
+        use crate::test_support::aggregated_skill;
+
+        skills
+            .iter()
+            .map(|&(name, commits)| {
+                let mut agg = aggregated_skill(name, SkillCategory::Language);
+                for i in 0..commits {
+                    agg.occurrences.push(SkillOccurrence {
+                        commit_sha: format!("sha{}", i),
+                        repository: "octocat/hello".to_string(),
+                        timestamp: Utc::now(),
+                        evidence: Vec::new(),
+                        proficiency_signal: "intermediate".to_string(),
+                        confidence: 0.5,
+                        lines_changed: 10,
+                        collaborative: false,
+                        weight: 1.0,
+                    });
+                }
+                (name.to_lowercase(), agg)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rating_ties_are_ordered_by_name() {
+        let skills = aggregated(&[("zig", 1), ("ada", 1), ("Lua", 1), ("c", 1)]);
+
+        let names: Vec<_> = RatingEngine::new()
+            .calculate_ratings(&skills)
+            .into_iter()
+            .map(|r| r.skill.name)
+            .collect();
+        assert_eq!(names, vec!["ada", "c", "Lua", "zig"]);
+    }
+
+    #[test]
+    fn test_ratings_as_of_a_past_date() {
+        let as_of: DateTime<Utc> = "2021-06-30T23:59:59Z".parse().unwrap();
+        let mut skills = aggregated(&[("rust", 4)]);
+        for (i, occurrence) in skills.get_mut("rust").unwrap().occurrences.iter_mut().enumerate() {
+            occurrence.timestamp = as_of - chrono::Duration::days(10 * i as i64);
+        }
+
+        let today = RatingEngine::new().calculate_ratings(&skills).remove(0);
+        let then = RatingEngine::new().with_as_of(Some(as_of)).calculate_ratings(&skills).remove(0);
+
+        // Years old now, but active in the months before the as-of date
+        assert_eq!(today.trend, SkillTrend::Dormant);
+        assert_eq!(then.trend, SkillTrend::Improving);
+        assert_eq!(then.breakdown.as_ref().unwrap().recency, 100.0);
+        assert!(then.proficiency_score > today.proficiency_score);
+        // Quarters are shifted to end at the as-of date, not today
+        assert!(then.trend_series.iter().all(|(start, _)| *start <= as_of));
+    }
+
+    #[test]
+    fn test_age_decay_discounts_old_occurrences() {
+        let mut skills = aggregated(&[("rust", 10)]);
+        let three_years_ago = Utc::now() - chrono::Duration::days(3 * 365);
+        for occurrence in &mut skills.get_mut("rust").unwrap().occurrences {
+            occurrence.timestamp = three_years_ago;
+        }
+
+        let frequency = |weights: RatingWeights| {
+            RatingEngine::with_weights(weights).unwrap().calculate_ratings(&skills)[0]
+                .breakdown
+                .as_ref()
+                .unwrap()
+                .frequency
+        };
+        let raw = frequency(RatingWeights::default());
+        let decayed = frequency(RatingWeights {
+            age_decay_half_life_days: Some(365.0),
+            ..RatingWeights::default()
+        });
+
+        // Ten commits three half-lives ago count as 1.25
+        assert!((raw - (10f32.ln() + 1.0) / 5.0 * 100.0).abs() < 1e-3);
+        assert!((decayed - (1.25f32.ln() + 1.0) / 5.0 * 100.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_trend_series_buckets_by_quarter() {
+        let mut skills = aggregated(&[("rust", 3)]);
+        let occurrences = &mut skills.get_mut("rust").unwrap().occurrences;
+        occurrences[0].timestamp = Utc.with_ymd_and_hms(2023, 2, 10, 9, 0, 0).unwrap();
+        occurrences[1].timestamp = Utc.with_ymd_and_hms(2023, 3, 28, 9, 0, 0).unwrap();
+        occurrences[2].timestamp = Utc.with_ymd_and_hms(2023, 11, 5, 9, 0, 0).unwrap();
+
+        let series = &RatingEngine::new().calculate_ratings(&skills)[0].trend_series;
+        let quarters: Vec<_> = series.iter().map(|(start, _)| *start).collect();
+        assert_eq!(
+            quarters,
+            vec![
+                Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap(),
+                Utc.with_ymd_and_hms(2023, 10, 1, 0, 0, 0).unwrap(),
+            ]
+        );
+        // Two commits outscore one in otherwise identical quarters
+        assert!(series[0].1 > series[1].1);
+    }
+
+    #[test]
+    fn test_custom_scoring_strategy() {
+        use crate::analysis::scoring::{rating_from_score, ScoringContext};
+
+        struct FrequencyScoring;
+        impl ScoringStrategy for FrequencyScoring {
+            fn score(&self, agg: &AggregatedSkill, context: &ScoringContext) -> SkillRating {
+                rating_from_score(agg, agg.occurrences.len() as u8 * 10, context.now)
+            }
+        }
+
+        let skills = aggregated(&[("rust", 7), ("go", 2)]);
+        let scores: Vec<_> = RatingEngine::with_strategy(Box::new(FrequencyScoring))
+            .calculate_ratings(&skills)
+            .into_iter()
+            .map(|r| (r.skill.name, r.proficiency_score, r.evidence.commit_count))
+            .collect();
+        assert_eq!(
+            scores,
+            vec![("rust".to_string(), 70, 7), ("go".to_string(), 20, 2)]
+        );
+    }
+}
@@ -0,0 +1,80 @@
+use crate::models::analysis::CodingStyle;
+
+/// Suggests a complementary skill when the profile shows any of `has_any`
+/// but none of `missing_all`. Skill names are matched case-insensitively.
+#[derive(Debug, Clone)]
+pub struct ComplementRule {
+    pub has_any: &'static [&'static str],
+    pub missing_all: &'static [&'static str],
+    pub area: &'static str,
+    pub suggestion: &'static str,
+}
+
+/// Flags a coding style metric that falls below `threshold`.
+#[derive(Debug, Clone)]
+pub struct StyleRule {
+    pub metric: fn(&CodingStyle) -> f32,
+    pub threshold: f32,
+    pub area: &'static str,
+    pub suggestion: &'static str,
+}
+
+/// Skills rated at or above this score that have gone dormant are worth refreshing.
+pub const DORMANT_SKILL_MIN_SCORE: u8 = 60;
+
+pub const COMPLEMENT_RULES: &[ComplementRule] = &[
+    ComplementRule {
+        has_any: &[
+            "backend", "django", "flask", "fastapi", "express", "spring", "rails", "actix",
+            "axum",
+        ],
+        missing_all: &["testing"],
+        area: "Testing",
+        suggestion: "Strong backend work but no testing practice detected; consider adding unit and integration tests",
+    },
+    ComplementRule {
+        has_any: &["react", "vue", "angular", "svelte", "nextjs"],
+        missing_all: &["typescript"],
+        area: "TypeScript",
+        suggestion: "Frontend framework experience without TypeScript; consider adopting it for safer components",
+    },
+    ComplementRule {
+        has_any: &["docker"],
+        missing_all: &["kubernetes"],
+        area: "Kubernetes",
+        suggestion: "Containerizes applications but shows no orchestration experience; consider learning Kubernetes",
+    },
+    ComplementRule {
+        has_any: &["docker", "kubernetes", "terraform"],
+        missing_all: &["ci/cd", "github actions", "gitlab ci", "jenkins"],
+        area: "CI/CD",
+        suggestion: "Infrastructure skills without CI/CD pipelines; consider automating builds and deployments",
+    },
+    ComplementRule {
+        has_any: &["postgresql", "mysql", "mongodb"],
+        missing_all: &["databases"],
+        area: "Database Design",
+        suggestion: "Uses databases but shows little schema or data modeling work; consider deepening database design skills",
+    },
+];
+
+pub const STYLE_RULES: &[StyleRule] = &[
+    StyleRule {
+        metric: |style| style.writes_tests,
+        threshold: 0.3,
+        area: "Testing",
+        suggestion: "Few commits include tests; consider writing tests alongside new features",
+    },
+    StyleRule {
+        metric: |style| style.documents_code,
+        threshold: 0.4,
+        area: "Documentation",
+        suggestion: "Code is sparsely documented; consider adding doc comments and READMEs",
+    },
+    StyleRule {
+        metric: |style| style.follows_conventions,
+        threshold: 0.5,
+        area: "Conventions",
+        suggestion: "Code often departs from idiomatic conventions; consider adopting linters and formatters",
+    },
+];
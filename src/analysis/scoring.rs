@@ -0,0 +1,667 @@
+use std::collections::HashMap;
+use chrono::{DateTime, Duration, Utc};
+
+use crate::error::{Error, Result};
+use crate::models::GIST_REPOSITORY_PREFIX;
+use crate::models::skill::{
+    AggregatedSkill, ScoreBreakdown, SkillEvidence, SkillOccurrence, SkillRating, SkillTrend,
+};
+
+/// Maximum number of evidence snippets kept per skill.
+const MAX_EVIDENCE_SNIPPETS: usize = 3;
+
+/// Skills with recent activity and at most this many occurrences are `New`;
+/// fewer data points than this are too sparse to call a direction.
+const NEW_TREND_MAX_OCCURRENCES: usize = 2;
+
+/// Score (0-100) for each proficiency level the LLM reports, used unless
+/// `RatingWeights::signal_scores` overrides it.
+pub const DEFAULT_SIGNAL_SCORES: [(&str, f32); 4] = [
+    ("expert", 95.0),
+    ("advanced", 80.0),
+    ("intermediate", 60.0),
+    ("beginner", 35.0),
+];
+
+/// Points added per mention of a skill in the user's bio or repository
+/// descriptions, up to `MAX_SELF_REPORTED_BONUS` in total.
+const SELF_REPORTED_BONUS_PER_MENTION: f32 = 2.0;
+
+/// Cap on the points self-reported mentions can add, so claims nudge a
+/// rating without outweighing the commits behind it.
+pub const MAX_SELF_REPORTED_BONUS: f32 = 5.0;
+
+/// Score for proficiency levels missing from the signal table.
+pub const DEFAULT_UNKNOWN_SIGNAL_SCORE: f32 = 50.0;
+
+/// Lines changed that count as much as one commit when frequency is based
+/// on lines.
+pub const LINES_PER_COMMIT: f32 = 50.0;
+
+/// What the frequency component of a rating counts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FrequencyBasis {
+    /// Commits using the skill, however large
+    #[default]
+    Commits,
+    /// Lines changed in those commits, per `LINES_PER_COMMIT`
+    Lines,
+    /// The average of the commit and line scores
+    Blended,
+}
+
+impl std::str::FromStr for FrequencyBasis {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "commits" => Ok(FrequencyBasis::Commits),
+            "lines" => Ok(FrequencyBasis::Lines),
+            "blended" => Ok(FrequencyBasis::Blended),
+            _ => Err(format!(
+                "unknown frequency basis '{}' (expected one of: commits, lines, blended)",
+                s
+            )),
+        }
+    }
+}
+
+/// Turns one aggregated skill into a rating. Implement this to replace the
+/// default weighted-sum scoring; `rating_from_score` fills in the evidence,
+/// confidence and trend so a strategy only has to decide the score.
+pub trait ScoringStrategy: Send + Sync {
+    fn score(&self, skill: &AggregatedSkill, context: &ScoringContext) -> SkillRating;
+}
+
+/// Profile-wide figures available to every strategy, computed once over all
+/// of the user's skills before any are scored.
+#[derive(Debug, Clone)]
+pub struct ScoringContext {
+    /// User's average LLM complexity assessment (0-100), if any were made
+    pub average_complexity: Option<f32>,
+    /// User's average LLM quality assessment (0-100), if any were made
+    pub average_quality: Option<f32>,
+    /// The moment skills are rated as of, which recency, age decay and
+    /// trends are measured from. The current time except in `--as-of` runs.
+    pub now: DateTime<Utc>,
+}
+
+impl Default for ScoringContext {
+    fn default() -> Self {
+        Self {
+            average_complexity: None,
+            average_quality: None,
+            now: Utc::now(),
+        }
+    }
+}
+
+impl ScoringContext {
+    /// Context for rating `aggregated_skills` as of `now`.
+    pub fn from_skills(aggregated_skills: &HashMap<String, AggregatedSkill>, now: DateTime<Utc>) -> Self {
+        // Average of every assessment the user received, scaled to 100
+        let average = |scores: Vec<f32>| {
+            (!scores.is_empty()).then(|| scores.iter().sum::<f32>() / scores.len() as f32 * 10.0)
+        };
+        let skills = aggregated_skills.values();
+        Self {
+            average_complexity: average(
+                skills.clone().flat_map(|a| a.complexity_scores.iter().copied()).collect(),
+            ),
+            average_quality: average(skills.flat_map(|a| a.quality_scores.iter().copied()).collect()),
+            now,
+        }
+    }
+}
+
+/// Builds a rating with the given score (clamped to 1-100) and the standard
+/// evidence, confidence and trend derived from the skill's occurrences, with
+/// the trend measured from `now` (usually `ScoringContext::now`).
+pub fn rating_from_score(agg: &AggregatedSkill, score: u8, now: DateTime<Utc>) -> SkillRating {
+    let timestamps = agg.occurrences.iter().map(|o| o.timestamp);
+    // Import signals carry no commit, so they cannot be linked to
+    let latest = agg
+        .occurrences
+        .iter()
+        .filter(|o| !o.commit_sha.is_empty())
+        .max_by_key(|o| o.timestamp);
+
+    let evidence = SkillEvidence {
+        // Rounded up, so a skill seen only in down-weighted commits still
+        // shows at least one
+        commit_count: agg.weighted_commits().ceil() as u32,
+        total_lines_changed: agg.total_lines,
+        first_seen: timestamps.clone().min().unwrap_or(now),
+        last_seen: timestamps.max().unwrap_or(now),
+        repositories: agg.repositories().into_iter().map(|(repo, _)| repo).collect(),
+        snippets: top_evidence_snippets(&agg.occurrences),
+        collaborative_commits: agg.occurrences.iter().filter(|o| o.collaborative).count() as u32,
+        gist_commits: agg.occurrences.iter().filter(|o| o.from_gist()).count() as u32,
+        last_seen_repository: latest.map(|o| o.repository.clone()),
+        last_seen_commit: latest.map(|o| o.commit_sha.clone()),
+    };
+
+    SkillRating {
+        skill: agg.skill.clone(),
+        proficiency_score: score.clamp(1, 100),
+        percentile_rank: None,
+        // Confidence grows with evidence quantity
+        confidence: (agg.weighted_commits() / 20.0).min(1.0),
+        evidence,
+        trend: calculate_trend(&agg.occurrences, now),
+        breakdown: None,
+        trend_series: Vec::new(),
+    }
+}
+
+fn top_evidence_snippets(occurrences: &[SkillOccurrence]) -> Vec<String> {
+    let mut candidates: Vec<(f32, &String)> = occurrences
+        .iter()
+        .flat_map(|o| o.evidence.iter().map(move |e| (o.confidence, e)))
+        .collect();
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let mut snippets: Vec<String> = Vec::new();
+    for (_, evidence) in candidates {
+        if snippets.len() >= MAX_EVIDENCE_SNIPPETS {
+            break;
+        }
+        if !snippets.contains(evidence) {
+            snippets.push(evidence.clone());
+        }
+    }
+    snippets
+}
+
+fn calculate_trend(occurrences: &[SkillOccurrence], now: DateTime<Utc>) -> SkillTrend {
+    let six_months_ago = now - Duration::days(180);
+    let one_year_ago = now - Duration::days(365);
+
+    let recent_count = occurrences
+        .iter()
+        .filter(|o| o.timestamp > six_months_ago)
+        .count();
+    let older_count = occurrences
+        .iter()
+        .filter(|o| o.timestamp <= six_months_ago && o.timestamp > one_year_ago)
+        .count();
+
+    // Checked first so a single use years ago is dormant rather than new
+    if recent_count == 0 {
+        return SkillTrend::Dormant;
+    }
+
+    if occurrences.len() <= NEW_TREND_MAX_OCCURRENCES {
+        return SkillTrend::New;
+    }
+
+    let ratio = if older_count > 0 {
+        recent_count as f32 / older_count as f32
+    } else {
+        2.0 // Active recently with no older history = improving
+    };
+
+    match ratio {
+        r if r > 1.5 => SkillTrend::Improving,
+        r if r < 0.5 => SkillTrend::Declining,
+        _ => SkillTrend::Stable,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RatingWeights {
+    pub frequency_weight: f32,
+    pub recency_weight: f32,
+    pub complexity_weight: f32,
+    pub quality_weight: f32,
+    pub consistency_weight: f32,
+    pub proficiency_weight: f32,
+    /// Maximum score adjustment (as a fraction) applied for repository breadth.
+    /// Single-repo skills are penalized by this amount, skills spanning five or
+    /// more repositories are boosted by it. Set to 0.0 to disable.
+    pub breadth_weight: f32,
+    /// Complexity/quality score (0-100) assumed for skills without any LLM
+    /// assessments. `None` derives it from the user's own average across all
+    /// skills, falling back to 50 when nothing was assessed.
+    pub neutral_baseline: Option<f32>,
+    /// When set, each occurrence counts towards frequency and proficiency
+    /// with a weight that halves every this many days, so old bursts of
+    /// activity count for less. `None` counts every occurrence fully.
+    pub age_decay_half_life_days: Option<f32>,
+    /// Whether frequency counts commits, lines changed, or both
+    pub frequency_basis: FrequencyBasis,
+    /// Score (0-100) for each proficiency level the LLM reports, keyed by
+    /// lowercase level name
+    pub signal_scores: HashMap<String, f32>,
+    /// Score (0-100) for levels missing from `signal_scores`, and for skills
+    /// with no usable signals at all
+    pub unknown_signal_score: f32,
+}
+
+impl RatingWeights {
+    /// Checks that every signal score is within 0-100.
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        for (level, &score) in &self.signal_scores {
+            check_signal_score(score).map_err(|e| format!("signal score for '{}': {}", level, e))?;
+        }
+        check_signal_score(self.unknown_signal_score)
+            .map_err(|e| format!("unknown signal score: {}", e))?;
+        Ok(())
+    }
+}
+
+/// Accepts `score` if it is within 0-100.
+pub fn check_signal_score(score: f32) -> std::result::Result<f32, String> {
+    if (0.0..=100.0).contains(&score) {
+        Ok(score)
+    } else {
+        Err(format!("{} is outside 0-100", score))
+    }
+}
+
+/// Parses a `LEVEL=SCORE` pair, e.g. `advanced=75`. The level is lowercased
+/// and the score must be within 0-100.
+pub fn parse_signal_score(spec: &str) -> std::result::Result<(String, f32), String> {
+    let (level, score) = spec
+        .split_once('=')
+        .filter(|(level, _)| !level.trim().is_empty())
+        .ok_or_else(|| format!("expected LEVEL=SCORE, e.g. advanced=75, got '{}'", spec))?;
+    let score = score
+        .trim()
+        .parse::<f32>()
+        .map_err(|_| format!("invalid score '{}' for {}", score.trim(), level.trim()))?;
+    let score = check_signal_score(score).map_err(|e| format!("{}: {}", level.trim(), e))?;
+    Ok((level.trim().to_lowercase(), score))
+}
+
+impl Default for RatingWeights {
+    fn default() -> Self {
+        Self {
+            frequency_weight: 0.15,
+            recency_weight: 0.15,
+            complexity_weight: 0.20,
+            quality_weight: 0.20,
+            consistency_weight: 0.10,
+            proficiency_weight: 0.20,
+            breadth_weight: 0.10,
+            neutral_baseline: None,
+            age_decay_half_life_days: None,
+            frequency_basis: FrequencyBasis::default(),
+            signal_scores: DEFAULT_SIGNAL_SCORES
+                .iter()
+                .map(|&(level, score)| (level.to_string(), score))
+                .collect(),
+            unknown_signal_score: DEFAULT_UNKNOWN_SIGNAL_SCORE,
+        }
+    }
+}
+
+/// Default strategy: a weighted sum of frequency, recency, complexity,
+/// quality, consistency and LLM-assessed proficiency, adjusted for breadth.
+#[derive(Debug, Clone, Default)]
+pub struct WeightedScoring {
+    weights: RatingWeights,
+}
+
+impl WeightedScoring {
+    /// Fails with a configuration error if `weights` does not pass
+    /// `RatingWeights::validate`.
+    pub fn new(weights: RatingWeights) -> Result<Self> {
+        weights
+            .validate()
+            .map_err(|e| Error::Config(format!("invalid rating weights: {}", e)))?;
+        Ok(Self { weights })
+    }
+
+    fn breadth_multiplier(&self, repo_count: usize) -> f32 {
+        // 1 repo = full penalty, 3 repos = neutral, 5+ repos = full boost
+        let breadth = (repo_count.saturating_sub(1) as f32 / 4.0).min(1.0);
+        1.0 + self.weights.breadth_weight * (2.0 * breadth - 1.0)
+    }
+
+    /// How much an occurrence at `timestamp` counts, from 1.0 for one made
+    /// `now` down towards 0.0 as it ages. Always 1.0 without age decay.
+    fn age_weight(&self, timestamp: DateTime<Utc>, now: DateTime<Utc>) -> f32 {
+        match self.weights.age_decay_half_life_days {
+            Some(half_life) if half_life > 0.0 => {
+                let age_days = (now - timestamp).num_days().max(0) as f32;
+                0.5f32.powf(age_days / half_life)
+            }
+            _ => 1.0,
+        }
+    }
+
+    /// Frequency score (0-100), log-scaled so it tops out around 100
+    /// commits (or `100 * LINES_PER_COMMIT` lines). Each occurrence counts
+    /// by its `weight`, and with age decay old ones count for less still.
+    fn calculate_frequency(&self, occurrences: &[SkillOccurrence], now: DateTime<Utc>) -> f32 {
+        let log_score = |count: f32| (count.ln() + 1.0).clamp(0.0, 5.0) / 5.0 * 100.0;
+        let commits = || {
+            log_score(
+                occurrences
+                    .iter()
+                    .map(|o| o.weight * self.age_weight(o.timestamp, now))
+                    .sum(),
+            )
+        };
+        let lines = || {
+            let lines: f32 = occurrences
+                .iter()
+                .map(|o| o.lines_changed as f32 * o.weight * self.age_weight(o.timestamp, now))
+                .sum();
+            log_score(lines / LINES_PER_COMMIT)
+        };
+
+        match self.weights.frequency_basis {
+            FrequencyBasis::Commits => commits(),
+            FrequencyBasis::Lines => lines(),
+            FrequencyBasis::Blended => (commits() + lines()) / 2.0,
+        }
+    }
+
+    fn calculate_proficiency_from_signals(
+        &self,
+        occurrences: &[SkillOccurrence],
+        now: DateTime<Utc>,
+    ) -> f32 {
+        let neutral = self.weights.unknown_signal_score;
+        if occurrences.is_empty() {
+            return neutral;
+        }
+
+        let level_scores: Vec<(f32, f32)> = occurrences
+            .iter()
+            .map(|o| {
+                let score = self
+                    .weights
+                    .signal_scores
+                    .get(&o.proficiency_signal.to_lowercase())
+                    .copied()
+                    .unwrap_or(neutral);
+                (score, o.confidence * o.weight * self.age_weight(o.timestamp, now))
+            })
+            .collect();
+
+        // Weighted average by confidence (and age, when decaying)
+        let total_weight: f32 = level_scores.iter().map(|(_, c)| c).sum();
+        if total_weight == 0.0 {
+            return neutral;
+        }
+
+        let weighted_sum: f32 = level_scores.iter().map(|(s, c)| s * c).sum();
+        weighted_sum / total_weight
+    }
+
+    fn calculate_consistency(&self, occurrences: &[SkillOccurrence]) -> f32 {
+        if occurrences.len() < 2 {
+            return 50.0;
+        }
+
+        let mut timestamps: Vec<_> = occurrences.iter().map(|o| o.timestamp).collect();
+        timestamps.sort();
+
+        let gaps: Vec<i64> = timestamps.windows(2).map(|w| (w[1] - w[0]).num_days()).collect();
+
+        if gaps.is_empty() {
+            return 50.0;
+        }
+
+        let avg_gap = gaps.iter().sum::<i64>() as f32 / gaps.len() as f32;
+        // Good consistency = gaps of ~7 days or less
+        // Poor consistency = gaps of 90+ days
+        let consistency = (1.0 - (avg_gap / 90.0).min(1.0)) * 100.0;
+
+        consistency.max(0.0)
+    }
+}
+
+impl ScoringStrategy for WeightedScoring {
+    fn score(&self, agg: &AggregatedSkill, context: &ScoringContext) -> SkillRating {
+        let now = context.now;
+
+        // 1. Frequency score
+        let frequency_score = self.calculate_frequency(&agg.occurrences, now);
+
+        // 2. Recency score
+        let most_recent = agg
+            .occurrences
+            .iter()
+            .map(|o| o.timestamp)
+            .max()
+            .unwrap_or(now);
+        let days_since = (now - most_recent).num_days().max(0) as f32;
+        let recency_score = (1.0 - (days_since / 365.0).min(1.0)) * 100.0;
+
+        // 3. Complexity score (average of LLM assessments, scaled to 100)
+        let complexity_score = if agg.complexity_scores.is_empty() {
+            self.weights
+                .neutral_baseline
+                .or(context.average_complexity)
+                .unwrap_or(50.0)
+        } else {
+            agg.complexity_scores.iter().sum::<f32>() / agg.complexity_scores.len() as f32 * 10.0
+        };
+
+        // 4. Quality score (average of LLM assessments, scaled to 100)
+        let quality_score = if agg.quality_scores.is_empty() {
+            self.weights
+                .neutral_baseline
+                .or(context.average_quality)
+                .unwrap_or(50.0)
+        } else {
+            agg.quality_scores.iter().sum::<f32>() / agg.quality_scores.len() as f32 * 10.0
+        };
+
+        // 5. Consistency score (how regularly the skill is used)
+        let consistency_score = self.calculate_consistency(&agg.occurrences);
+
+        // 6. Proficiency score from LLM assessments
+        let proficiency_score = self.calculate_proficiency_from_signals(&agg.occurrences, now);
+
+        // 7. Capped nudge for skills the user claims in their bio or descriptions
+        let self_reported_bonus = (agg.self_reported.len() as f32
+            * SELF_REPORTED_BONUS_PER_MENTION)
+            .min(MAX_SELF_REPORTED_BONUS);
+
+        // Weighted combination, adjusted for repository breadth. Gists are
+        // one pseudo-repository each, so together they count as one
+        let repositories = agg.repositories();
+        let gists = repositories
+            .iter()
+            .filter(|(repo, _)| repo.starts_with(GIST_REPOSITORY_PREFIX))
+            .count();
+        let repo_count = repositories.len() - gists + gists.min(1);
+        let breadth_multiplier = self.breadth_multiplier(repo_count);
+        let final_score = ((frequency_score * self.weights.frequency_weight
+            + recency_score * self.weights.recency_weight
+            + complexity_score * self.weights.complexity_weight
+            + quality_score * self.weights.quality_weight
+            + consistency_score * self.weights.consistency_weight
+            + proficiency_score * self.weights.proficiency_weight)
+            * breadth_multiplier
+            + self_reported_bonus)
+            .round() as u8;
+
+        SkillRating {
+            breakdown: Some(ScoreBreakdown {
+                frequency: frequency_score,
+                recency: recency_score,
+                complexity: complexity_score,
+                quality: quality_score,
+                consistency: consistency_score,
+                proficiency: proficiency_score,
+                breadth_multiplier,
+                self_reported_bonus,
+            }),
+            ..rating_from_score(agg, final_score, now)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::skill::SkillCategory;
+    use crate::test_support::aggregated_skill;
+
+    /// Fixed clock, so recency and trends do not depend on when tests run.
+    fn now() -> DateTime<Utc> {
+        "2024-06-30T12:00:00Z".parse().unwrap()
+    }
+
+    fn context() -> ScoringContext {
+        ScoringContext {
+            now: now(),
+            ..ScoringContext::default()
+        }
+    }
+
+    fn occurrences_days_ago(days: &[i64]) -> Vec<SkillOccurrence> {
+        days.iter()
+            .map(|&d| SkillOccurrence {
+                commit_sha: format!("sha{}", d),
+                repository: "octocat/hello".to_string(),
+                timestamp: now() - Duration::days(d),
+                evidence: Vec::new(),
+                proficiency_signal: "intermediate".to_string(),
+                confidence: 0.5,
+                lines_changed: 10,
+                collaborative: false,
+                weight: 1.0,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_signal_scores_configurable() {
+        let stricter = WeightedScoring::new(RatingWeights {
+            signal_scores: HashMap::from([("advanced".to_string(), 75.0)]),
+            unknown_signal_score: 40.0,
+            ..RatingWeights::default()
+        })
+        .unwrap();
+        let mut occurrences = occurrences_days_ago(&[1, 2]);
+        occurrences[0].proficiency_signal = "Advanced".to_string();
+        occurrences[1].proficiency_signal = "wizard".to_string();
+        let score = stricter.calculate_proficiency_from_signals(&occurrences, now());
+        assert!((score - 57.5).abs() < 1e-4);
+        assert_eq!(
+            WeightedScoring::default().calculate_proficiency_from_signals(&occurrences[..1], now()),
+            80.0
+        );
+
+        assert_eq!(parse_signal_score(" Advanced = 75 "), Ok(("advanced".to_string(), 75.0)));
+        assert!(parse_signal_score("advanced=101").is_err());
+        assert!(parse_signal_score("advanced").is_err());
+        assert!(RatingWeights { unknown_signal_score: -1.0, ..RatingWeights::default() }
+            .validate()
+            .is_err());
+        assert!(RatingWeights::default().validate().is_ok());
+        assert!(WeightedScoring::new(RatingWeights {
+            signal_scores: HashMap::from([("advanced".to_string(), 150.0)]),
+            ..RatingWeights::default()
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_frequency_basis() {
+        let scoring = |frequency_basis| {
+            WeightedScoring::new(RatingWeights {
+                frequency_basis,
+                ..RatingWeights::default()
+            })
+            .unwrap()
+        };
+        // Many one-line fixes against one large feature
+        let mut fixes = occurrences_days_ago(&[1; 20]);
+        fixes.iter_mut().for_each(|o| o.lines_changed = 1);
+        let mut feature = occurrences_days_ago(&[1]);
+        feature[0].lines_changed = 500;
+
+        let now = now();
+        let by_commits = scoring(FrequencyBasis::Commits);
+        assert!(by_commits.calculate_frequency(&fixes, now) > by_commits.calculate_frequency(&feature, now));
+        let by_lines = scoring(FrequencyBasis::Lines);
+        assert!(by_lines.calculate_frequency(&fixes, now) < by_lines.calculate_frequency(&feature, now));
+
+        let blended = scoring(FrequencyBasis::Blended).calculate_frequency(&feature, now);
+        let expected = (by_commits.calculate_frequency(&feature, now)
+            + by_lines.calculate_frequency(&feature, now))
+            / 2.0;
+        assert!((blended - expected).abs() < 1e-4);
+
+        assert_eq!(" Lines ".parse::<FrequencyBasis>(), Ok(FrequencyBasis::Lines));
+        assert!("bytes".parse::<FrequencyBasis>().is_err());
+    }
+
+    #[test]
+    fn test_down_weighted_commits_score_lower() {
+        let mut agg = aggregated_skill("serde", SkillCategory::Library);
+        agg.occurrences = occurrences_days_ago(&[5, 40, 90, 120]);
+        let scoring = WeightedScoring::default();
+        let full = scoring.score(&agg, &context());
+
+        // The same commits as dependency bumps
+        agg.occurrences.iter_mut().for_each(|o| o.weight = 0.25);
+        let bumps = scoring.score(&agg, &context());
+        assert!(bumps.proficiency_score < full.proficiency_score);
+        assert!(bumps.breakdown.unwrap().frequency < full.breakdown.unwrap().frequency);
+        assert_eq!((full.evidence.commit_count, bumps.evidence.commit_count), (4, 1));
+        assert!((bumps.confidence - 0.05).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_self_reported_bonus_is_capped() {
+        let mut agg = aggregated_skill("rust", SkillCategory::Language);
+        agg.occurrences = occurrences_days_ago(&[5, 40, 90]);
+        let scoring = WeightedScoring::default();
+        let context = context();
+        let base = scoring.score(&agg, &context).proficiency_score;
+
+        agg.self_reported = occurrences_days_ago(&[0]);
+        let nudged = scoring.score(&agg, &context);
+        assert_eq!(nudged.proficiency_score, base + 2);
+        assert_eq!(nudged.evidence.commit_count, 3);
+        assert_eq!(nudged.evidence.last_seen_commit.as_deref(), Some("sha5"));
+
+        agg.self_reported = occurrences_days_ago(&[0, 0, 0, 0, 0, 0]);
+        let capped = scoring.score(&agg, &context);
+        assert_eq!(capped.proficiency_score, base + MAX_SELF_REPORTED_BONUS as u8);
+        assert_eq!(capped.breakdown.unwrap().self_reported_bonus, MAX_SELF_REPORTED_BONUS);
+    }
+
+    #[test]
+    fn test_recency_and_trend_measured_from_clock() {
+        let rust = |days: &[i64]| {
+            let mut agg = aggregated_skill("rust", SkillCategory::Language);
+            agg.occurrences = occurrences_days_ago(days);
+            WeightedScoring::default().score(&agg, &context())
+        };
+        let recency = |days: &[i64]| rust(days).breakdown.unwrap().recency;
+
+        // Linear over a year from the most recent occurrence
+        assert_eq!(recency(&[0]), 100.0);
+        assert!((recency(&[73, 400]) - 80.0).abs() < 1e-4);
+        assert_eq!(recency(&[365]), 0.0);
+        assert_eq!(recency(&[800]), 0.0);
+
+        // Six months is the edge of "recent"
+        assert_eq!(rust(&[179]).trend, SkillTrend::New);
+        assert_eq!(rust(&[180]).trend, SkillTrend::Dormant);
+        assert_eq!(rust(&[10, 20, 200]).trend, SkillTrend::Improving);
+        assert_eq!(rust(&[10, 200, 210, 220]).trend, SkillTrend::Declining);
+    }
+
+    #[test]
+    fn test_trend_checks_dormancy_before_new() {
+        let now = now();
+        assert_eq!(calculate_trend(&occurrences_days_ago(&[7]), now), SkillTrend::New);
+        assert_eq!(calculate_trend(&occurrences_days_ago(&[730]), now), SkillTrend::Dormant);
+        assert_eq!(calculate_trend(&occurrences_days_ago(&[3, 20]), now), SkillTrend::New);
+        assert_eq!(
+            calculate_trend(&occurrences_days_ago(&[700, 710, 720]), now),
+            SkillTrend::Dormant
+        );
+    }
+}
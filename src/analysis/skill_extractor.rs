@@ -1,10 +1,59 @@
+use chrono::{DateTime, Utc};
 use std::collections::HashMap;
-use chrono::Utc;
 
 use crate::models::analysis::LLMAnalysisResult;
 use crate::models::commit::CommitForAnalysis;
-use crate::models::skill::{AggregatedSkill, Skill, SkillOccurrence};
-use crate::taxonomy::SkillTaxonomy;
+use crate::models::{GitHubUser, Repository};
+use crate::models::skill::{AggregatedSkill, SkillCategory, SkillOccurrence};
+use crate::taxonomy::{
+    detect_frameworks, detect_security_change, is_ci_config, is_dependency_bump, SkillTaxonomy,
+    CI_SKILL, SECURITY_SKILL,
+};
+
+/// Confidence assigned to framework occurrences inferred from import statements.
+/// Kept low since an import shows usage but says little about proficiency.
+const IMPORT_SIGNAL_CONFIDENCE: f32 = 0.3;
+
+/// Confidence assigned to CI/CD occurrences inferred from changes to CI
+/// configuration files. Editing a pipeline is direct evidence of the
+/// practice, though not of how well it was done.
+const CI_SIGNAL_CONFIDENCE: f32 = 0.5;
+
+/// Confidence assigned to security occurrences inferred from changes to
+/// auth, crypto or secrets code (see `detect_security_change`). Lower than
+/// CI/CD's, since a path like `src/auth/` is only circumstantial: the change
+/// may not touch the security logic at all.
+const SECURITY_SIGNAL_CONFIDENCE: f32 = 0.4;
+
+/// Confidence assigned to skills the user states in their bio or repository
+/// descriptions. Kept lower still, since such claims can be aspirational.
+const PROFILE_SIGNAL_CONFIDENCE: f32 = 0.1;
+
+/// Weight of occurrences from commits that only touch dependency manifests
+/// and lockfiles (see `is_dependency_bump`), which count as this fraction of
+/// a commit. Bumping a version says little about the author's skill with it.
+const DEPENDENCY_BUMP_WEIGHT: f32 = 0.25;
+
+/// `SkillOccurrence::weight` for occurrences from `commit`.
+fn commit_weight(commit: &CommitForAnalysis) -> f32 {
+    if is_dependency_bump(commit) {
+        DEPENDENCY_BUMP_WEIGHT
+    } else {
+        1.0
+    }
+}
+
+/// How one raw skill name reported by the LLM was normalized.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct NormalizationEntry {
+    pub raw_name: String,
+    pub normalized_name: String,
+    pub category: SkillCategory,
+    /// Whether the name resolved to a skill in the taxonomy
+    pub mapped: bool,
+    /// Number of analyses that reported this name with this category
+    pub occurrences: usize,
+}
 
 pub struct SkillExtractor {
     taxonomy: SkillTaxonomy,
@@ -12,9 +61,11 @@ pub struct SkillExtractor {
 
 impl SkillExtractor {
     pub fn new() -> Self {
-        Self {
-            taxonomy: SkillTaxonomy::new(),
-        }
+        Self::with_taxonomy(SkillTaxonomy::new())
+    }
+
+    pub fn with_taxonomy(taxonomy: SkillTaxonomy) -> Self {
+        Self { taxonomy }
     }
 
     pub fn aggregate_skills(
@@ -25,12 +76,15 @@ impl SkillExtractor {
 
         for (analysis, commit) in analyses {
             let lines_changed = commit.stats.additions + commit.stats.deletions;
+            let weight = commit_weight(commit);
 
             for extracted in &analysis.skills {
-                let normalized_name = self.taxonomy.normalize_skill_name(&extracted.name);
                 let category = self.taxonomy.categorize(&extracted.category);
+                let normalized_name = self
+                    .taxonomy
+                    .normalize_skill_name_in(&extracted.name, &category);
 
-                let skill = self.taxonomy.get_or_create_skill(&extracted.name, category);
+                let skill = self.taxonomy.get_or_create_skill(&extracted.name, category.clone());
 
                 let occurrence = SkillOccurrence {
                     commit_sha: commit.sha.clone(),
@@ -40,12 +94,28 @@ impl SkillExtractor {
                     proficiency_signal: extracted.proficiency_level.clone(),
                     confidence: extracted.confidence,
                     lines_changed,
+                    collaborative: commit.collaborative,
+                    weight,
                 };
 
                 let entry = skill_map
                     .entry(normalized_name)
                     .or_insert_with(|| AggregatedSkill::new(skill));
 
+                // The LLM may file the same skill under different categories in
+                // different batches; keep one entry under the canonical category
+                if entry.skill.category != category {
+                    if let Some(canonical) = self.taxonomy.get_skill(&extracted.name) {
+                        entry.skill.category = canonical.category.clone();
+                    }
+                    tracing::debug!(
+                        "Merged {} reported as {} into existing {} entry",
+                        extracted.name,
+                        category,
+                        entry.skill.category
+                    );
+                }
+
                 entry.occurrences.push(occurrence);
                 entry.total_lines += lines_changed;
                 entry.complexity_scores.push(analysis.complexity_assessment.overall_score as f32);
@@ -56,6 +126,231 @@ impl SkillExtractor {
         skill_map
     }
 
+    /// Adds framework occurrences inferred from import statements in the diffs.
+    /// Commits where the LLM already reported the framework are skipped.
+    pub fn add_import_signals(
+        &self,
+        skill_map: &mut HashMap<String, AggregatedSkill>,
+        commits: &[CommitForAnalysis],
+    ) {
+        for commit in commits {
+            let lines_changed = commit.stats.additions + commit.stats.deletions;
+
+            for file in &commit.files_changed {
+                let Some(language) = &file.language else {
+                    continue;
+                };
+
+                for framework in detect_frameworks(language, &file.diff) {
+                    let normalized_name = self
+                        .taxonomy
+                        .normalize_skill_name_in(framework, &SkillCategory::Framework);
+                    let skill = self
+                        .taxonomy
+                        .get_or_create_skill(framework, SkillCategory::Framework);
+
+                    let entry = skill_map
+                        .entry(normalized_name)
+                        .or_insert_with(|| AggregatedSkill::new(skill));
+
+                    if entry.occurrences.iter().any(|o| o.commit_sha == commit.sha) {
+                        continue;
+                    }
+
+                    entry.occurrences.push(SkillOccurrence {
+                        commit_sha: commit.sha.clone(),
+                        repository: commit.repository.clone(),
+                        timestamp: commit.committed_at,
+                        evidence: vec![format!("Imports {} in {}", framework, file.filename)],
+                        proficiency_signal: "unknown".to_string(),
+                        confidence: IMPORT_SIGNAL_CONFIDENCE,
+                        lines_changed,
+                        collaborative: commit.collaborative,
+                        weight: commit_weight(commit),
+                    });
+                    entry.total_lines += lines_changed;
+                }
+            }
+        }
+    }
+
+    /// Adds CI/CD occurrences for commits that change CI configuration
+    /// (see `is_ci_config`), independent of what the LLM reported.
+    pub fn add_ci_signals(
+        &self,
+        skill_map: &mut HashMap<String, AggregatedSkill>,
+        commits: &[CommitForAnalysis],
+    ) {
+        self.add_heuristic_occurrences(
+            skill_map,
+            commits,
+            CI_SKILL,
+            SkillCategory::Practice,
+            CI_SIGNAL_CONFIDENCE,
+            |commit| {
+                let ci_files: Vec<_> = commit
+                    .files_changed
+                    .iter()
+                    .map(|f| f.filename.as_str())
+                    .filter(|f| is_ci_config(f))
+                    .collect();
+                if ci_files.is_empty() {
+                    return Vec::new();
+                }
+                vec![format!("Changes CI configuration in {}", ci_files.join(", "))]
+            },
+        );
+    }
+
+    /// Adds security occurrences for commits with security-sensitive changes
+    /// (see `detect_security_change`), independent of what the LLM reported.
+    /// The generic prompt tends to under-report security work.
+    pub fn add_security_signals(
+        &self,
+        skill_map: &mut HashMap<String, AggregatedSkill>,
+        commits: &[CommitForAnalysis],
+    ) {
+        self.add_heuristic_occurrences(
+            skill_map,
+            commits,
+            SECURITY_SKILL,
+            SkillCategory::Domain,
+            SECURITY_SIGNAL_CONFIDENCE,
+            |commit| {
+                commit
+                    .files_changed
+                    .iter()
+                    .filter_map(|f| detect_security_change(&f.filename, &f.diff))
+                    .collect()
+            },
+        );
+    }
+
+    /// Adds an occurrence of `skill` for every commit `evidence` returns
+    /// anything for, with that evidence and `confidence`. Commits where the
+    /// LLM already reported the skill are skipped.
+    ///
+    /// These occurrences always count as a full commit: a dependency bump
+    /// only triggers one by adding a security library, which is the signal
+    /// itself, and CI configuration is never part of a bump.
+    fn add_heuristic_occurrences(
+        &self,
+        skill_map: &mut HashMap<String, AggregatedSkill>,
+        commits: &[CommitForAnalysis],
+        skill: &str,
+        category: SkillCategory,
+        confidence: f32,
+        evidence: impl Fn(&CommitForAnalysis) -> Vec<String>,
+    ) {
+        let normalized_name = self.taxonomy.normalize_skill_name_in(skill, &category);
+
+        for commit in commits {
+            let evidence = evidence(commit);
+            if evidence.is_empty() {
+                continue;
+            }
+
+            let entry = skill_map.entry(normalized_name.clone()).or_insert_with(|| {
+                AggregatedSkill::new(self.taxonomy.get_or_create_skill(skill, category.clone()))
+            });
+            if entry.occurrences.iter().any(|o| o.commit_sha == commit.sha) {
+                continue;
+            }
+
+            let lines_changed = commit.stats.additions + commit.stats.deletions;
+            entry.occurrences.push(SkillOccurrence {
+                commit_sha: commit.sha.clone(),
+                repository: commit.repository.clone(),
+                timestamp: commit.committed_at,
+                evidence,
+                proficiency_signal: "unknown".to_string(),
+                confidence,
+                lines_changed,
+                collaborative: commit.collaborative,
+                weight: 1.0,
+            });
+            entry.total_lines += lines_changed;
+        }
+    }
+
+    /// Records skills named in the user's bio and repository descriptions as
+    /// `self_reported` mentions. Only skills already seen in commits are
+    /// nudged; a claim alone never adds a skill to the profile. The mentions
+    /// are dated `now`, the moment the profile is rated as of.
+    pub fn add_profile_signals(
+        &self,
+        skill_map: &mut HashMap<String, AggregatedSkill>,
+        user: &GitHubUser,
+        repos: &[Repository],
+        now: DateTime<Utc>,
+    ) {
+        let bio = user.bio.as_deref().map(|bio| (None, bio));
+        let descriptions = repos
+            .iter()
+            .filter_map(|r| r.description.as_deref().map(|d| (Some(r.full_name.as_str()), d)));
+
+        for (repository, text) in bio.into_iter().chain(descriptions) {
+            for name in self.taxonomy.skills_mentioned(text) {
+                let Some(entry) = skill_map.get_mut(&name) else {
+                    continue;
+                };
+                let evidence = match repository {
+                    Some(repository) => format!("Described in {}: {}", repository, text),
+                    None => format!("Stated in bio: {}", text),
+                };
+                entry.self_reported.push(SkillOccurrence {
+                    commit_sha: String::new(),
+                    repository: repository.unwrap_or_default().to_string(),
+                    timestamp: now,
+                    evidence: vec![evidence],
+                    proficiency_signal: "unknown".to_string(),
+                    confidence: PROFILE_SIGNAL_CONFIDENCE,
+                    lines_changed: 0,
+                    collaborative: false,
+                    weight: 1.0,
+                });
+            }
+        }
+    }
+
+    /// Lists each distinct raw skill name and reported category with the name
+    /// and category it normalizes to, unmapped names first, then by name.
+    pub fn normalization_report(&self, analyses: &[LLMAnalysisResult]) -> Vec<NormalizationEntry> {
+        let mut entries: HashMap<(String, String), NormalizationEntry> = HashMap::new();
+
+        for extracted in analyses.iter().flat_map(|a| &a.skills) {
+            let category = self.taxonomy.categorize(&extracted.category);
+            let normalized_name = self
+                .taxonomy
+                .normalize_skill_name_in(&extracted.name, &category);
+
+            entries
+                .entry((extracted.name.clone(), extracted.category.to_lowercase()))
+                .or_insert_with(|| {
+                    let mapped = self.taxonomy.get_skill(&normalized_name).is_some();
+                    let skill = self.taxonomy.get_or_create_skill(&extracted.name, category);
+                    NormalizationEntry {
+                        raw_name: extracted.name.clone(),
+                        normalized_name,
+                        category: skill.category,
+                        mapped,
+                        occurrences: 0,
+                    }
+                })
+                .occurrences += 1;
+        }
+
+        let mut entries: Vec<_> = entries.into_values().collect();
+        entries.sort_by(|a, b| {
+            a.mapped
+                .cmp(&b.mapped)
+                .then_with(|| a.raw_name.to_lowercase().cmp(&b.raw_name.to_lowercase()))
+                .then_with(|| a.raw_name.cmp(&b.raw_name))
+                .then_with(|| a.category.to_string().cmp(&b.category.to_string()))
+        });
+        entries
+    }
+
     pub fn extract_domain_signals(
         &self,
         analyses: &[LLMAnalysisResult],
@@ -108,3 +403,149 @@ impl Default for SkillExtractor {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::analysis::{ComplexityAssessment, ExtractedSkill, QualityAssessment};
+    use crate::models::commit::{CommitStats, FileForAnalysis};
+
+    fn analysis_reporting(name: &str, category: &str) -> LLMAnalysisResult {
+        LLMAnalysisResult {
+            skills: vec![ExtractedSkill {
+                name: name.to_string(),
+                category: category.to_string(),
+                proficiency_level: "intermediate".to_string(),
+                confidence: 0.8,
+                evidence: Vec::new(),
+            }],
+            patterns: Vec::new(),
+            complexity_assessment: ComplexityAssessment::default(),
+            quality_assessment: QualityAssessment::default(),
+            domain_signals: Vec::new(),
+            notable_aspects: Vec::new(),
+        }
+    }
+
+    fn commit(sha: &str) -> CommitForAnalysis {
+        CommitForAnalysis {
+            sha: sha.to_string(),
+            repository: "octocat/hello".to_string(),
+            message: String::new(),
+            stats: CommitStats::default(),
+            files_changed: Vec::new(),
+            binary_files_changed: 0,
+            committed_at: Utc::now(),
+            collaborative: false,
+        }
+    }
+
+    #[test]
+    fn test_conflicting_categories_merge_into_canonical() {
+        let analyses = vec![
+            (analysis_reporting("Docker", "domain"), commit("a")),
+            (analysis_reporting("docker", "tool"), commit("b")),
+            (analysis_reporting("Containerization", "practice"), commit("c")),
+        ];
+
+        let skills = SkillExtractor::new().aggregate_skills(&analyses);
+        assert_eq!(skills.len(), 1);
+        let docker = &skills["docker"];
+        assert_eq!(docker.skill.category, SkillCategory::Tool);
+        assert_eq!(docker.occurrences.len(), 3);
+    }
+
+    #[test]
+    fn test_ci_config_changes_add_ci_occurrences() {
+        let file = |filename: &str| FileForAnalysis {
+            filename: filename.to_string(),
+            language: None,
+            diff: String::new(),
+            additions: 1,
+            deletions: 0,
+        };
+        let mut workflow = commit("a");
+        workflow.files_changed = vec![file("src/lib.rs"), file(".github/workflows/ci.yml")];
+        let mut docs = commit("b");
+        docs.files_changed = vec![file("README.md")];
+        let reported = commit("c");
+        let mut jenkins = reported.clone();
+        jenkins.files_changed = vec![file("Jenkinsfile")];
+
+        let extractor = SkillExtractor::new();
+        let mut skills =
+            extractor.aggregate_skills(&[(analysis_reporting("Continuous Integration", "practice"), reported)]);
+        extractor.add_ci_signals(&mut skills, &[workflow, docs, jenkins]);
+
+        let ci = &skills["ci/cd"];
+        assert_eq!(ci.skill.category, SkillCategory::Practice);
+        let shas: Vec<_> = ci.occurrences.iter().map(|o| o.commit_sha.as_str()).collect();
+        assert_eq!(shas, vec!["c", "a"]);
+        assert_eq!(ci.occurrences[1].evidence, vec!["Changes CI configuration in .github/workflows/ci.yml"]);
+    }
+
+    #[test]
+    fn test_security_changes_add_security_occurrences() {
+        let mut login = commit("a");
+        login.files_changed = vec![FileForAnalysis {
+            filename: "src/auth/login.rs".to_string(),
+            language: Some("Rust".to_string()),
+            diff: String::new(),
+            additions: 1,
+            deletions: 0,
+        }];
+
+        let extractor = SkillExtractor::new();
+        let mut skills = HashMap::new();
+        extractor.add_security_signals(&mut skills, &[login, commit("b")]);
+
+        let security = &skills["security"];
+        assert_eq!(security.skill.category, SkillCategory::Domain);
+        assert_eq!(security.occurrences.len(), 1);
+        assert_eq!(security.occurrences[0].evidence, vec!["Changes security-sensitive file src/auth/login.rs"]);
+    }
+
+    #[test]
+    fn test_profile_signals_dated_at_rating_time() {
+        use crate::test_support::{repository, user};
+
+        let extractor = SkillExtractor::new();
+        let mut skills = extractor.aggregate_skills(&[(analysis_reporting("Rust", "language"), commit("a"))]);
+        let mut octocat = user("octocat");
+        octocat.bio = Some("Writes Rust and Go".to_string());
+        let mut repo = repository("octocat/hello");
+        repo.description = Some("A Rust CLI".to_string());
+        let as_of: DateTime<Utc> = "2021-01-01T00:00:00Z".parse().unwrap();
+
+        extractor.add_profile_signals(&mut skills, &octocat, &[repo], as_of);
+        let rust = &skills["rust"];
+        assert_eq!(rust.self_reported.len(), 2);
+        assert!(rust.self_reported.iter().all(|o| o.timestamp == as_of));
+        // Go is claimed but never seen in a commit
+        assert!(!skills.contains_key("go"));
+    }
+
+    #[test]
+    fn test_normalization_report() {
+        let analyses = vec![
+            analysis_reporting("k8s", "tool"),
+            analysis_reporting("Kubernetes", "tool"),
+            analysis_reporting("k8s", "tool"),
+            analysis_reporting("Event Sourcing", "pattern"),
+        ];
+
+        let report = SkillExtractor::new().normalization_report(&analyses);
+        assert_eq!(report.len(), 3);
+
+        assert_eq!(report[0].raw_name, "Event Sourcing");
+        assert_eq!(report[0].normalized_name, "event sourcing");
+        assert_eq!(report[0].category, SkillCategory::Concept);
+        assert!(!report[0].mapped);
+
+        assert_eq!(report[1].raw_name, "k8s");
+        assert_eq!(report[1].normalized_name, "kubernetes");
+        assert_eq!(report[1].category, SkillCategory::Tool);
+        assert!(report[1].mapped);
+        assert_eq!(report[1].occurrences, 2);
+    }
+}
@@ -1,23 +1,129 @@
 use crate::error::{Error, Result};
+use crate::github::CommitSampling;
+use crate::llm::{DEFAULT_MAX_COMMITS_PER_BATCH, DEFAULT_MAX_DIFF_CHARS};
+use crate::analysis::scoring::{check_signal_score, parse_signal_score, FrequencyBasis};
+use crate::taxonomy::{parse_alias, TaxonomyFile};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::BTreeMap;
 use std::env;
+use std::path::{Path, PathBuf};
+
+/// Most GitHub requests the pipeline keeps in flight at once. GitHub's
+/// secondary rate limits punish bursts of concurrent requests.
+pub const MAX_GITHUB_REQUESTS_IN_FLIGHT: usize = 20;
+
+/// Parallelism limits for each stage of the pipeline.
+#[derive(Debug, Clone)]
+pub struct Concurrency {
+    /// Repositories whose commits are fetched at once
+    pub github: usize,
+    /// Commit detail (diff) requests in flight per repository
+    pub commit_detail: usize,
+    /// LLM batches analyzed at once
+    pub llm: usize,
+}
+
+impl Default for Concurrency {
+    fn default() -> Self {
+        Self {
+            github: 5,
+            commit_detail: 4,
+            llm: 1,
+        }
+    }
+}
+
+impl Concurrency {
+    /// Commit detail requests to run at once within each repository:
+    /// `commit_detail`, lowered if needed so that `github` repositories
+    /// together stay within `MAX_GITHUB_REQUESTS_IN_FLIGHT`.
+    pub fn commit_detail_per_repo(&self) -> usize {
+        let repos = self.github.clamp(1, MAX_GITHUB_REQUESTS_IN_FLIGHT);
+        self.commit_detail.clamp(1, MAX_GITHUB_REQUESTS_IN_FLIGHT / repos)
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct Config {
     pub github_token: String,
-    pub anthropic_api_key: String,
+    pub anthropic_api_key: Option<String>,
+    /// API root for Anthropic requests, e.g. an internal gateway (`None` = default)
+    pub anthropic_base_url: Option<String>,
+    /// Claude model to analyze commits with (`None` = the provider's default)
+    pub anthropic_model: Option<String>,
     pub database_path: String,
     pub max_commits_per_repo: u32,
+    pub max_commits_big_repo: Option<u32>,
+    pub commit_sampling: CommitSampling,
+    pub author_email: Option<String>,
+    pub include_coauthored: bool,
+    pub include_bots: bool,
     pub include_forks: bool,
-    pub concurrency_limit: usize,
+    pub include_contributions: bool,
+    pub include_gists: bool,
+    pub include_prs: bool,
+    pub since_last_release: bool,
+    pub use_bio_signals: bool,
+    pub author_timezones: bool,
+    pub concurrency: Concurrency,
+    pub breadth_factor: bool,
+    pub min_commits_per_skill: u32,
+    pub per_file_diff_limit: usize,
+    pub min_stars: u32,
+    pub min_repo_commits: u32,
+    pub neutral_baseline: Option<f32>,
+    pub age_decay_half_life_days: Option<f32>,
+    pub frequency_basis: FrequencyBasis,
+    pub signal_scores: Vec<(String, f32)>,
+    pub unknown_signal_score: Option<f32>,
+    pub dedup_by_diff: bool,
+    pub max_commits_per_batch: usize,
+    pub reserved_tokens: Option<usize>,
+    pub max_cost: Option<f64>,
+    pub only_languages: Vec<String>,
+    pub exclude_repositories: Vec<String>,
+    pub exclude_paths: Vec<String>,
+    pub skill_aliases: Vec<(String, String)>,
+    pub taxonomy: TaxonomyFile,
+}
+
+/// Files to read secrets from instead of the environment, e.g. from
+/// `--github-token-file`. These take precedence over every other source.
+#[derive(Debug, Clone, Default)]
+pub struct SecretFiles {
+    pub github_token: Option<PathBuf>,
+    pub anthropic_api_key: Option<PathBuf>,
 }
 
 impl Config {
     pub fn from_env() -> Result<Self> {
-        let github_token = env::var("GITHUB_TOKEN")
-            .map_err(|_| Error::Config("GITHUB_TOKEN environment variable not set".to_string()))?;
+        Self::from_env_with_secrets(&SecretFiles::default())
+    }
 
-        let anthropic_api_key = env::var("ANTHROPIC_API_KEY")
-            .map_err(|_| Error::Config("ANTHROPIC_API_KEY environment variable not set".to_string()))?;
+    /// Like `from_env`, but reads secrets from `files` when given. Each secret
+    /// is looked up in order: the given file, the file named by `<VAR>_FILE`,
+    /// the OS keychain (with the `keychain` feature), then `<VAR>` itself.
+    pub fn from_env_with_secrets(files: &SecretFiles) -> Result<Self> {
+        let github_token = load_secret("GITHUB_TOKEN", files.github_token.as_deref())?
+            .ok_or_else(|| {
+                Error::Config(
+                    "GITHUB_TOKEN not set (use the environment, GITHUB_TOKEN_FILE or --github-token-file)"
+                        .to_string(),
+                )
+            })?;
+
+        // Only needed once an LLM provider is created; see `require_anthropic_api_key`
+        let anthropic_api_key =
+            load_secret("ANTHROPIC_API_KEY", files.anthropic_api_key.as_deref())?;
+
+        let anthropic_base_url = env::var("ANTHROPIC_BASE_URL")
+            .ok()
+            .filter(|v| !v.is_empty());
+
+        let anthropic_model = env::var("ANTHROPIC_MODEL")
+            .ok()
+            .filter(|v| !v.is_empty());
 
         let database_path = env::var("DATABASE_PATH")
             .unwrap_or_else(|_| "gitanalyzer.db".to_string());
@@ -27,40 +133,810 @@ impl Config {
             .and_then(|v| v.parse().ok())
             .unwrap_or(100);
 
+        // Unset means large repositories get the same cap as the rest
+        let max_commits_big_repo = env::var("MAX_COMMITS_BIG_REPO")
+            .ok()
+            .and_then(|v| v.parse().ok());
+
+        let commit_sampling = env::var("COMMIT_SAMPLING")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or_default();
+
+        let author_email = env::var("AUTHOR_EMAIL").ok().filter(|v| !v.trim().is_empty());
+
         let include_forks = env::var("INCLUDE_FORKS")
             .ok()
             .map(|v| v.to_lowercase() == "true")
             .unwrap_or(false);
 
-        let concurrency_limit = env::var("CONCURRENCY_LIMIT")
+        let include_coauthored = env::var("INCLUDE_COAUTHORED")
+            .ok()
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false);
+
+        let include_bots = env::var("INCLUDE_BOTS")
+            .ok()
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false);
+
+        let include_contributions = env::var("INCLUDE_CONTRIBUTIONS")
+            .ok()
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false);
+
+        let include_gists = env::var("INCLUDE_GISTS")
+            .ok()
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false);
+
+        let include_prs = env::var("INCLUDE_PRS")
+            .ok()
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false);
+
+        let since_last_release = env::var("SINCE_LAST_RELEASE")
+            .ok()
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false);
+
+        let use_bio_signals = env::var("USE_BIO_SIGNALS")
+            .ok()
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false);
+
+        let author_timezones = env::var("AUTHOR_TIMEZONES")
+            .ok()
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false);
+
+        let defaults = Concurrency::default();
+        let concurrency = Concurrency {
+            github: env::var("CONCURRENCY_LIMIT")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.github),
+            commit_detail: env::var("CONCURRENCY_COMMIT_DETAIL")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.commit_detail),
+            llm: env::var("CONCURRENCY_LLM")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.llm),
+        };
+
+        let breadth_factor = env::var("BREADTH_FACTOR")
+            .ok()
+            .map(|v| v.to_lowercase() != "false")
+            .unwrap_or(true);
+
+        let min_commits_per_skill = env::var("MIN_COMMITS_PER_SKILL")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(1);
+
+        let per_file_diff_limit = env::var("MAX_DIFF_CHARS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_MAX_DIFF_CHARS);
+
+        let min_stars = env::var("MIN_STARS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0);
+
+        let min_repo_commits = env::var("MIN_REPO_COMMITS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0);
+
+        // Unset means "derive from the user's own average"
+        let neutral_baseline = env::var("NEUTRAL_BASELINE")
+            .ok()
+            .and_then(|v| v.parse().ok());
+
+        // Unset means raw totals, with no decay for old commits
+        let age_decay_half_life_days = env::var("AGE_DECAY_HALF_LIFE_DAYS")
+            .ok()
+            .and_then(|v| v.parse().ok());
+
+        let frequency_basis = env::var("FREQUENCY_BASIS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or_default();
+
+        let signal_scores = env::var("SIGNAL_SCORES")
+            .map(|v| {
+                v.split(',')
+                    .filter(|spec| !spec.trim().is_empty())
+                    .map(|spec| {
+                        parse_signal_score(spec)
+                            .map_err(|e| Error::Config(format!("SIGNAL_SCORES: {}", e)))
+                    })
+                    .collect::<Result<Vec<_>>>()
+            })
+            .unwrap_or(Ok(Vec::new()))?;
+
+        let unknown_signal_score = env::var("UNKNOWN_SIGNAL_SCORE")
+            .ok()
+            .map(|v| {
+                v.trim()
+                    .parse::<f32>()
+                    .map_err(|_| format!("invalid number '{}'", v))
+                    .and_then(check_signal_score)
+                    .map_err(|e| Error::Config(format!("UNKNOWN_SIGNAL_SCORE: {}", e)))
+            })
+            .transpose()?;
+
+        let dedup_by_diff = env::var("DEDUP_BY_DIFF")
+            .ok()
+            .map(|v| v.to_lowercase() == "true")
+            .unwrap_or(false);
+
+        let max_commits_per_batch = env::var("MAX_COMMITS_PER_BATCH")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_MAX_COMMITS_PER_BATCH);
+
+        // Unset means derived from the provider's context and reply limits
+        let reserved_tokens = env::var("RESERVED_TOKENS")
+            .ok()
+            .map(|v| {
+                v.parse()
+                    .map_err(|_| Error::Config(format!("RESERVED_TOKENS: invalid number '{}'", v)))
+            })
+            .transpose()?;
+
+        // Unset means no cap on LLM spend
+        let max_cost = env::var("MAX_COST")
             .ok()
             .and_then(|v| v.parse().ok())
-            .unwrap_or(5);
+            .filter(|cost: &f64| *cost >= 0.0);
+
+        let only_languages = env::var("ONLY_LANGUAGES")
+            .map(|v| {
+                v.split(',')
+                    .map(|l| l.trim().to_string())
+                    .filter(|l| !l.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let skill_aliases = env::var("SKILL_ALIASES")
+            .map(|v| {
+                v.split(',')
+                    .filter(|spec| !spec.trim().is_empty())
+                    .filter_map(|spec| {
+                        parse_alias(spec)
+                            .map_err(|e| tracing::warn!("Ignoring SKILL_ALIASES entry: {}", e))
+                            .ok()
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
 
         Ok(Self {
             github_token,
             anthropic_api_key,
+            anthropic_base_url,
+            anthropic_model,
             database_path,
             max_commits_per_repo,
+            max_commits_big_repo,
+            commit_sampling,
+            author_email,
+            include_coauthored,
+            include_bots,
             include_forks,
-            concurrency_limit,
+            include_contributions,
+            include_gists,
+            include_prs,
+            since_last_release,
+            use_bio_signals,
+            author_timezones,
+            concurrency,
+            breadth_factor,
+            min_commits_per_skill,
+            per_file_diff_limit,
+            min_stars,
+            min_repo_commits,
+            neutral_baseline,
+            age_decay_half_life_days,
+            frequency_basis,
+            signal_scores,
+            unknown_signal_score,
+            dedup_by_diff,
+            max_commits_per_batch,
+            reserved_tokens,
+            max_cost,
+            only_languages,
+            exclude_repositories: Vec::new(),
+            exclude_paths: Vec::new(),
+            skill_aliases,
+            taxonomy: TaxonomyFile::default(),
+        })
+    }
+}
+
+impl Config {
+    /// Returns the Anthropic API key, or a configuration error explaining how
+    /// to proceed without one.
+    pub fn require_anthropic_api_key(&self) -> Result<&str> {
+        self.anthropic_api_key.as_deref().ok_or_else(|| {
+            Error::Config(
+                "ANTHROPIC_API_KEY not set; it is required for LLM analysis. Set it in your \
+                 environment or .env, point ANTHROPIC_API_KEY_FILE or --anthropic-key-file at a file \
+                 containing it, or pass --no-llm for a heuristics-only analysis without it"
+                    .to_string(),
+            )
         })
     }
 }
 
+/// Name of the project config file looked up in the current directory.
+pub const PROJECT_CONFIG_FILE: &str = ".gitanalyzer.toml";
+
+/// Settings read from a project config file such as `.gitanalyzer.toml`.
+/// Every key is optional; keys that are set override the environment and are
+/// in turn overridden by CLI flags. Secrets cannot be set here, so the file
+/// is safe to commit.
+///
+/// ```toml
+/// provider = "anthropic"
+/// model = "claude-sonnet-4-20250514"
+/// only_languages = ["rust", "go"]
+///
+/// [concurrency]
+/// github = 8
+/// llm = 2
+///
+/// [scoring]
+/// age_decay_half_life_days = 365
+/// frequency_basis = "blended"
+/// signal_scores = { advanced = 75, expert = 90 }
+///
+/// [skill_aliases]
+/// reactjs = "react"
+///
+/// [excludes]
+/// repositories = ["octocat/archive-*"]
+/// paths = ["vendor/", "*.min.js"]
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FileConfig {
+    /// LLM provider; only `anthropic` is supported
+    pub provider: Option<String>,
+    pub model: Option<String>,
+    pub max_commits_big_repo: Option<u32>,
+    pub commit_sampling: Option<String>,
+    pub author_email: Option<String>,
+    pub include_coauthored: Option<bool>,
+    pub include_bots: Option<bool>,
+    pub include_contributions: Option<bool>,
+    pub include_gists: Option<bool>,
+    pub include_prs: Option<bool>,
+    pub since_last_release: Option<bool>,
+    pub use_bio_signals: Option<bool>,
+    pub author_timezones: Option<bool>,
+    pub min_stars: Option<u32>,
+    pub min_repo_commits: Option<u32>,
+    pub dedup_by_diff: Option<bool>,
+    pub max_commits_per_batch: Option<usize>,
+    pub reserved_tokens: Option<usize>,
+    pub max_cost: Option<f64>,
+    pub only_languages: Option<Vec<String>>,
+    /// Alias to skill, added after `SKILL_ALIASES`
+    #[serde(default)]
+    pub skill_aliases: BTreeMap<String, String>,
+    /// Taxonomy file of extra skills (see `TaxonomyFile`), relative to the
+    /// config file
+    pub taxonomy: Option<PathBuf>,
+    /// Skills read from `taxonomy` by `load`
+    #[serde(skip)]
+    pub taxonomy_skills: TaxonomyFile,
+    #[serde(default)]
+    pub excludes: FileExcludes,
+    #[serde(default)]
+    pub concurrency: FileConcurrency,
+    #[serde(default)]
+    pub scoring: FileScoring,
+}
+
+/// `[concurrency]` table of a `FileConfig`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FileConcurrency {
+    pub github: Option<usize>,
+    pub commit_detail: Option<usize>,
+    pub llm: Option<usize>,
+}
+
+/// `[excludes]` table of a `FileConfig`. `*` matches any run of characters.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FileExcludes {
+    /// Repositories to skip, by full name, e.g. "octocat/archive-*"
+    #[serde(default)]
+    pub repositories: Vec<String>,
+    /// Changed files to leave out of the analysis, by path; a pattern ending
+    /// in `/` covers a whole directory, e.g. "vendor/"
+    #[serde(default)]
+    pub paths: Vec<String>,
+}
+
+/// `[scoring]` table of a `FileConfig`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FileScoring {
+    pub breadth_factor: Option<bool>,
+    pub neutral_baseline: Option<f32>,
+    pub age_decay_half_life_days: Option<f32>,
+    pub frequency_basis: Option<String>,
+    /// Proficiency level to score, added after `SIGNAL_SCORES`
+    #[serde(default)]
+    pub signal_scores: BTreeMap<String, f32>,
+    pub unknown_signal_score: Option<f32>,
+}
+
+impl FileConfig {
+    /// Parses and validates the contents of a config file.
+    pub fn parse(text: &str) -> Result<Self> {
+        let file: FileConfig = toml::from_str(text).map_err(|e| Error::Config(e.to_string()))?;
+
+        if let Some(provider) = &file.provider {
+            if !provider.eq_ignore_ascii_case("anthropic") {
+                return Err(Error::Config(format!(
+                    "unsupported provider '{}' (expected: anthropic)",
+                    provider
+                )));
+            }
+        }
+        if let Some(sampling) = &file.commit_sampling {
+            sampling.parse::<CommitSampling>().map_err(Error::Config)?;
+        }
+        if let Some(basis) = &file.scoring.frequency_basis {
+            basis.parse::<FrequencyBasis>().map_err(Error::Config)?;
+        }
+        if file.max_cost.is_some_and(|cost| cost < 0.0) {
+            return Err(Error::Config("max_cost must not be negative".to_string()));
+        }
+        for (level, score) in &file.scoring.signal_scores {
+            check_signal_score(*score)
+                .map_err(|e| Error::Config(format!("scoring.signal_scores.{}: {}", level, e)))?;
+        }
+        if let Some(score) = file.scoring.unknown_signal_score {
+            check_signal_score(score)
+                .map_err(|e| Error::Config(format!("scoring.unknown_signal_score: {}", e)))?;
+        }
+        Ok(file)
+    }
+
+    /// Reads the config file at `path`, or `PROJECT_CONFIG_FILE` in the
+    /// current directory when no path is given. A missing default file is not
+    /// an error; a missing explicit one is.
+    pub fn load(path: Option<&Path>) -> Result<Option<Self>> {
+        let path = match path {
+            Some(path) => path,
+            None if Path::new(PROJECT_CONFIG_FILE).is_file() => Path::new(PROJECT_CONFIG_FILE),
+            None => return Ok(None),
+        };
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| Error::Config(format!("Cannot read {}: {}", path.display(), e)))?;
+        let mut file =
+            Self::parse(&text).map_err(|e| Error::Config(format!("{}: {}", path.display(), e)))?;
+        if let Some(taxonomy) = &file.taxonomy {
+            let taxonomy = path.parent().unwrap_or(Path::new("")).join(taxonomy);
+            file.taxonomy_skills = TaxonomyFile::load(&taxonomy)?;
+        }
+        Ok(Some(file))
+    }
+}
+
+impl Config {
+    /// Overrides settings with the ones `file` sets. Pair lists (aliases and
+    /// signal scores) are extended rather than replaced, so file entries win
+    /// over environment entries for the same key.
+    pub fn apply_file(&mut self, file: &FileConfig) {
+        if let Some(model) = &file.model {
+            self.anthropic_model = Some(model.clone());
+        }
+        if file.max_commits_big_repo.is_some() {
+            self.max_commits_big_repo = file.max_commits_big_repo;
+        }
+        if let Some(sampling) = file.commit_sampling.as_deref().and_then(|s| s.parse().ok()) {
+            self.commit_sampling = sampling;
+        }
+        if let Some(email) = file.author_email.as_deref().filter(|v| !v.trim().is_empty()) {
+            self.author_email = Some(email.to_string());
+        }
+        self.include_coauthored = file.include_coauthored.unwrap_or(self.include_coauthored);
+        self.include_bots = file.include_bots.unwrap_or(self.include_bots);
+        self.include_contributions = file
+            .include_contributions
+            .unwrap_or(self.include_contributions);
+        self.include_gists = file.include_gists.unwrap_or(self.include_gists);
+        self.include_prs = file.include_prs.unwrap_or(self.include_prs);
+        self.since_last_release = file.since_last_release.unwrap_or(self.since_last_release);
+        self.use_bio_signals = file.use_bio_signals.unwrap_or(self.use_bio_signals);
+        self.author_timezones = file.author_timezones.unwrap_or(self.author_timezones);
+        self.min_stars = file.min_stars.unwrap_or(self.min_stars);
+        self.min_repo_commits = file.min_repo_commits.unwrap_or(self.min_repo_commits);
+        self.dedup_by_diff = file.dedup_by_diff.unwrap_or(self.dedup_by_diff);
+        self.max_commits_per_batch = file
+            .max_commits_per_batch
+            .unwrap_or(self.max_commits_per_batch);
+        if file.reserved_tokens.is_some() {
+            self.reserved_tokens = file.reserved_tokens;
+        }
+        if file.max_cost.is_some() {
+            self.max_cost = file.max_cost;
+        }
+        if let Some(languages) = &file.only_languages {
+            self.only_languages = languages.clone();
+        }
+        self.exclude_repositories.extend(file.excludes.repositories.iter().cloned());
+        self.exclude_paths.extend(file.excludes.paths.iter().cloned());
+        if file.taxonomy.is_some() {
+            self.taxonomy = file.taxonomy_skills.clone();
+        }
+        self.skill_aliases.extend(
+            file.skill_aliases
+                .iter()
+                .map(|(alias, skill)| (alias.clone(), skill.clone())),
+        );
+
+        let concurrency = &file.concurrency;
+        self.concurrency.github = concurrency.github.unwrap_or(self.concurrency.github);
+        self.concurrency.commit_detail = concurrency
+            .commit_detail
+            .unwrap_or(self.concurrency.commit_detail);
+        self.concurrency.llm = concurrency.llm.unwrap_or(self.concurrency.llm);
+
+        let scoring = &file.scoring;
+        self.breadth_factor = scoring.breadth_factor.unwrap_or(self.breadth_factor);
+        if scoring.neutral_baseline.is_some() {
+            self.neutral_baseline = scoring.neutral_baseline;
+        }
+        if scoring.age_decay_half_life_days.is_some() {
+            self.age_decay_half_life_days = scoring.age_decay_half_life_days;
+        }
+        if let Some(basis) = scoring.frequency_basis.as_deref().and_then(|s| s.parse().ok()) {
+            self.frequency_basis = basis;
+        }
+        self.signal_scores.extend(
+            scoring
+                .signal_scores
+                .iter()
+                .map(|(level, score)| (level.to_lowercase(), *score)),
+        );
+        if scoring.unknown_signal_score.is_some() {
+            self.unknown_signal_score = scoring.unknown_signal_score;
+        }
+    }
+}
+
+/// Resolves a secret named `var`; see `Config::from_env_with_secrets` for the order.
+fn load_secret(var: &str, file: Option<&Path>) -> Result<Option<String>> {
+    if let Some(path) = file {
+        return read_secret_file(path).map(Some);
+    }
+    if let Ok(path) = env::var(format!("{}_FILE", var)) {
+        return read_secret_file(Path::new(&path)).map(Some);
+    }
+    #[cfg(feature = "keychain")]
+    if let Some(secret) = keychain_secret(var) {
+        return Ok(Some(secret));
+    }
+    Ok(env::var(var).ok().filter(|v| !v.is_empty()))
+}
+
+/// Reads a secret from `path`, refusing files that other users can read.
+fn read_secret_file(path: &Path) -> Result<String> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(path)
+            .map_err(|e| Error::Config(format!("Cannot read {}: {}", path.display(), e)))?
+            .permissions()
+            .mode();
+        if mode & 0o077 != 0 {
+            return Err(Error::Config(format!(
+                "{} is accessible by other users (mode {:o}); run `chmod 600 {}`",
+                path.display(),
+                mode & 0o777,
+                path.display()
+            )));
+        }
+    }
+
+    let secret = std::fs::read_to_string(path)
+        .map_err(|e| Error::Config(format!("Cannot read {}: {}", path.display(), e)))?;
+    let secret = secret.trim();
+    if secret.is_empty() {
+        return Err(Error::Config(format!("{} is empty", path.display())));
+    }
+    Ok(secret.to_string())
+}
+
+/// Looks up `var` in the OS keychain under the `gitanalyzer` service.
+#[cfg(feature = "keychain")]
+fn keychain_secret(var: &str) -> Option<String> {
+    keyring::Entry::new("gitanalyzer", var)
+        .and_then(|entry| entry.get_password())
+        .ok()
+}
+
 #[derive(Debug, Clone)]
 pub struct PipelineConfig {
     pub max_commits_per_repo: u32,
+    /// Cap for large repositories, where the user has more than
+    /// `analysis::LARGE_REPO_FACTOR` times `max_commits_per_repo` commits
+    /// (`None` = use `max_commits_per_repo`)
+    pub max_commits_big_repo: Option<u32>,
+    /// Which commits to take from repositories with more than `max_commits_per_repo`
+    pub commit_sampling: CommitSampling,
+    /// Also analyze commits whose author email matches this, compared
+    /// case-insensitively. Listing then scans each repository's unfiltered
+    /// history instead of asking GitHub for the user's commits, and ignores
+    /// `commit_sampling`.
+    pub author_email: Option<String>,
+    /// Also analyze commits by others that name the user in a
+    /// `Co-authored-by:` trailer, found by scanning each repository's
+    /// unfiltered history
+    pub include_coauthored: bool,
+    /// Keep commits authored by bots such as `dependabot[bot]`, which are
+    /// otherwise dropped when listing commits
+    pub include_bots: bool,
     pub include_forks: bool,
-    pub concurrency_limit: usize,
+    pub include_contributions: bool,
+    /// Also analyze the user's public gists, each as one pseudo-commit that
+    /// adds all of its files
+    pub include_gists: bool,
+    /// Also fetch the user's recent pull requests for
+    /// `ProfileSummary::process_metrics`
+    pub include_prs: bool,
+    /// Only analyze commits made since each repository's latest release;
+    /// repositories without releases are analyzed in full
+    pub since_last_release: bool,
+    /// Nudge ratings of skills the user names in their bio or repository
+    /// descriptions (see `SkillExtractor::add_profile_signals`)
+    pub use_bio_signals: bool,
+    /// Fetch each analyzed commit's patch for the author's UTC offset, for
+    /// `ProfileSummary::modal_timezone` and local working hours. Costs one
+    /// extra request per commit
+    pub author_timezones: bool,
+    pub concurrency: Concurrency,
+    pub breadth_factor: bool,
+    pub min_commits_per_skill: u32,
+    pub per_file_diff_limit: usize,
+    pub min_stars: u32,
+    pub min_repo_commits: u32,
+    pub neutral_baseline: Option<f32>,
+    /// Half-life in days for discounting old occurrences in frequency and
+    /// proficiency scoring (`None` = count all occurrences equally)
+    pub age_decay_half_life_days: Option<f32>,
+    /// Whether skill frequency counts commits, lines changed, or both
+    pub frequency_basis: FrequencyBasis,
+    /// (level, score) pairs overriding `scoring::DEFAULT_SIGNAL_SCORES`, in
+    /// order, so later pairs win for the same level
+    pub signal_scores: Vec<(String, f32)>,
+    /// Score for unrecognized proficiency levels
+    /// (`None` = `scoring::DEFAULT_UNKNOWN_SIGNAL_SCORE`)
+    pub unknown_signal_score: Option<f32>,
+    pub dedup_by_diff: bool,
+    pub max_commits_per_batch: usize,
+    /// Tokens of each batch held back for the system prompt and the reply
+    /// (`None` = `llm::default_reserved_tokens` for the provider)
+    pub reserved_tokens: Option<usize>,
+    /// Estimated LLM spend in US dollars after which no more batches are
+    /// started; the profile is then marked incomplete (`None` = no cap)
+    pub max_cost: Option<f64>,
+    /// Languages to restrict analysis to, compared case-insensitively with
+    /// GitHub's repository language and `detect_language` (empty = all).
+    /// Files whose language cannot be detected are excluded while a filter is
+    /// set, so a commit touching only such files is dropped.
+    pub only_languages: Vec<String>,
+    /// Repositories to skip, by full name, compared case-insensitively; `*`
+    /// matches any run of characters
+    pub exclude_repositories: Vec<String>,
+    /// Changed files to leave out of the analysis, by path, with `*` as in
+    /// `exclude_repositories`; a pattern ending in `/` covers a directory.
+    /// A commit left without files is dropped
+    pub exclude_paths: Vec<String>,
+    /// Extra (alias, skill) pairs registered on top of the built-in taxonomy,
+    /// in order, so later pairs override earlier ones for the same alias
+    pub skill_aliases: Vec<(String, String)>,
+    /// Skills added to the built-in taxonomy before `skill_aliases`
+    pub taxonomy: TaxonomyFile,
+    /// Redact the profile (see `UserProfile::redact`) before saving it
+    pub redact_store: bool,
+    /// Analyze only commits made at or before this moment and rate skills as
+    /// of then; such profiles are not saved (`None` = now)
+    pub as_of: Option<DateTime<Utc>>,
+}
+
+/// Same defaults as `Config::from_env` with no variables set.
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self {
+            max_commits_per_repo: 100,
+            max_commits_big_repo: None,
+            commit_sampling: CommitSampling::default(),
+            author_email: None,
+            include_coauthored: false,
+            include_bots: false,
+            include_forks: false,
+            include_contributions: false,
+            include_gists: false,
+            include_prs: false,
+            since_last_release: false,
+            use_bio_signals: false,
+            author_timezones: false,
+            concurrency: Concurrency::default(),
+            breadth_factor: true,
+            min_commits_per_skill: 1,
+            per_file_diff_limit: DEFAULT_MAX_DIFF_CHARS,
+            min_stars: 0,
+            min_repo_commits: 0,
+            neutral_baseline: None,
+            age_decay_half_life_days: None,
+            frequency_basis: FrequencyBasis::default(),
+            signal_scores: Vec::new(),
+            unknown_signal_score: None,
+            dedup_by_diff: false,
+            max_commits_per_batch: DEFAULT_MAX_COMMITS_PER_BATCH,
+            reserved_tokens: None,
+            max_cost: None,
+            only_languages: Vec::new(),
+            exclude_repositories: Vec::new(),
+            exclude_paths: Vec::new(),
+            skill_aliases: Vec::new(),
+            taxonomy: TaxonomyFile::default(),
+            redact_store: false,
+            as_of: None,
+        }
+    }
 }
 
 impl From<&Config> for PipelineConfig {
     fn from(config: &Config) -> Self {
         Self {
             max_commits_per_repo: config.max_commits_per_repo,
+            max_commits_big_repo: config.max_commits_big_repo,
+            commit_sampling: config.commit_sampling,
+            author_email: config.author_email.clone(),
+            include_coauthored: config.include_coauthored,
+            include_bots: config.include_bots,
             include_forks: config.include_forks,
-            concurrency_limit: config.concurrency_limit,
+            include_contributions: config.include_contributions,
+            include_gists: config.include_gists,
+            include_prs: config.include_prs,
+            since_last_release: config.since_last_release,
+            use_bio_signals: config.use_bio_signals,
+            author_timezones: config.author_timezones,
+            concurrency: config.concurrency.clone(),
+            breadth_factor: config.breadth_factor,
+            min_commits_per_skill: config.min_commits_per_skill,
+            per_file_diff_limit: config.per_file_diff_limit,
+            min_stars: config.min_stars,
+            min_repo_commits: config.min_repo_commits,
+            neutral_baseline: config.neutral_baseline,
+            age_decay_half_life_days: config.age_decay_half_life_days,
+            frequency_basis: config.frequency_basis,
+            signal_scores: config.signal_scores.clone(),
+            unknown_signal_score: config.unknown_signal_score,
+            dedup_by_diff: config.dedup_by_diff,
+            max_commits_per_batch: config.max_commits_per_batch,
+            reserved_tokens: config.reserved_tokens,
+            max_cost: config.max_cost,
+            only_languages: config.only_languages.clone(),
+            exclude_repositories: config.exclude_repositories.clone(),
+            exclude_paths: config.exclude_paths.clone(),
+            skill_aliases: config.skill_aliases.clone(),
+            taxonomy: config.taxonomy.clone(),
+            redact_store: false,
+            as_of: None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_secret_file_checks_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = env::temp_dir().join(format!("gitanalyzer-secret-{}", std::process::id()));
+        std::fs::write(&path, "ghp_secret\n").unwrap();
+
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        assert!(read_secret_file(&path).is_err());
+
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600)).unwrap();
+        assert_eq!(read_secret_file(&path).unwrap(), "ghp_secret");
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_commit_detail_concurrency_is_bounded() {
+        let concurrency = |github, commit_detail| {
+            Concurrency { github, commit_detail, llm: 1 }.commit_detail_per_repo()
+        };
+        assert_eq!(concurrency(5, 4), 4);
+        assert_eq!(concurrency(8, 4), 2);
+        assert_eq!(concurrency(40, 4), 1);
+        assert_eq!(concurrency(1, 50), MAX_GITHUB_REQUESTS_IN_FLIGHT);
+        assert_eq!(concurrency(0, 0), 1);
+    }
+
+    #[test]
+    fn test_parse_file_config() {
+        let file = FileConfig::parse(
+            r#"
+            model = "claude-opus-4-20250514"
+            only_languages = ["rust", "go"]
+
+            [concurrency]
+            llm = 3
+
+            [scoring]
+            age_decay_half_life_days = 180
+            signal_scores = { Advanced = 80 }
+
+            [skill_aliases]
+            k8s = "kubernetes"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(file.model.as_deref(), Some("claude-opus-4-20250514"));
+        assert_eq!(file.only_languages, Some(vec!["rust".to_string(), "go".to_string()]));
+        assert_eq!((file.concurrency.github, file.concurrency.llm), (None, Some(3)));
+        assert_eq!(file.scoring.age_decay_half_life_days, Some(180.0));
+        assert_eq!(file.scoring.signal_scores.get("Advanced"), Some(&80.0));
+        assert_eq!(file.skill_aliases.get("k8s").map(String::as_str), Some("kubernetes"));
+
+        // Typos, secrets and out-of-range values are rejected rather than ignored
+        assert!(FileConfig::parse("modle = \"x\"").is_err());
+        assert!(FileConfig::parse("github_token = \"ghp_x\"").is_err());
+        assert!(FileConfig::parse("provider = \"openai\"").is_err());
+        assert!(FileConfig::parse("commit_sampling = \"random\"").is_err());
+        assert!(FileConfig::parse("[scoring]\nunknown_signal_score = 120").is_err());
+        assert!(FileConfig::parse("[scoring]\nfrequency_basis = \"bytes\"").is_err());
+    }
+
+    #[test]
+    fn test_file_config_excludes_and_taxonomy() {
+        let dir = env::temp_dir().join(format!("gitanalyzer-config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join(".gitanalyzer.toml"),
+            r#"
+            taxonomy = "skills.toml"
+
+            [excludes]
+            repositories = ["octocat/archive-*"]
+            paths = ["vendor/", "*.min.js"]
+            "#,
+        )
+        .unwrap();
+        std::fs::write(dir.join("skills.toml"), "[[skills]]\nname = \"zig\"\ncategory = \"language\"\n").unwrap();
+
+        // The taxonomy path is relative to the config file, not the working directory
+        let file = FileConfig::load(Some(&dir.join(".gitanalyzer.toml"))).unwrap().unwrap();
+        assert_eq!(file.taxonomy.as_deref(), Some(Path::new("skills.toml")));
+        assert_eq!(file.taxonomy_skills.skills[0].name, "zig");
+        assert_eq!(file.excludes.repositories, vec!["octocat/archive-*"]);
+        assert_eq!(file.excludes.paths, vec!["vendor/", "*.min.js"]);
+
+        assert!(FileConfig::parse("[excludes]\nrepos = [\"x\"]").is_err());
+        std::fs::write(dir.join("skills.toml"), "[[skills]]\nname = \"zig\"\n").unwrap();
+        assert!(FileConfig::load(Some(&dir.join(".gitanalyzer.toml"))).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -32,9 +32,15 @@ pub enum Error {
     #[error("User not found: {0}")]
     UserNotFound(String),
 
+    #[error("{0} is an organization, not a user; analyze one of its members with --username instead")]
+    Organization(String),
+
     #[error("Repository not found: {0}")]
     RepoNotFound(String),
 
+    #[error("Offline: {0}")]
+    Offline(String),
+
     #[error("Invalid header value: {0}")]
     InvalidHeader(#[from] reqwest::header::InvalidHeaderValue),
 }
@@ -0,0 +1,115 @@
+use chrono::format::{Item, StrftimeItems};
+
+/// Default for `--date-format`.
+pub const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S UTC";
+
+/// Parses `--date-format`, rejecting specifiers chrono does not support.
+pub fn parse_date_format(value: &str) -> Result<String, String> {
+    if StrftimeItems::new(value).any(|item| matches!(item, Item::Error)) {
+        return Err(format!(
+            "invalid date format '{}' (expected strftime syntax, e.g. %d.%m.%Y)",
+            value
+        ));
+    }
+    Ok(value.to_string())
+}
+
+/// Decimal and thousands separators for `--locale`. Only the language part
+/// of the tag matters, so `de`, `de-DE` and `de_AT` are the same.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberLocale {
+    decimal: char,
+    group: char,
+}
+
+impl Default for NumberLocale {
+    fn default() -> Self {
+        Self { decimal: '.', group: ',' }
+    }
+}
+
+impl std::str::FromStr for NumberLocale {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let language = s.split(['-', '_']).next().unwrap_or_default().to_lowercase();
+        let (decimal, group) = match language.as_str() {
+            "en" | "ja" | "ko" | "zh" | "he" | "th" | "hi" => ('.', ','),
+            "de" | "nl" | "it" | "es" | "pt" | "da" | "id" | "tr" | "el" | "ro" => (',', '.'),
+            "fr" | "ru" | "pl" | "cs" | "sk" | "sv" | "fi" | "nb" | "no" | "uk" | "hu" => {
+                (',', '\u{a0}')
+            }
+            _ => return Err(format!("unsupported locale '{}' (e.g. en, de, fr)", s)),
+        };
+        Ok(Self { decimal, group })
+    }
+}
+
+impl NumberLocale {
+    /// `n` with thousands grouped, e.g. "12,345" or "12.345".
+    pub fn integer(&self, n: u64) -> String {
+        let digits = n.to_string();
+        let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                grouped.push(self.group);
+            }
+            grouped.push(digit);
+        }
+        grouped
+    }
+
+    /// `value` rounded to `places` decimals, e.g. "1.05" or "1,05".
+    pub fn decimal(&self, value: f64, places: usize) -> String {
+        let formatted = format!("{:.*}", places, value);
+        let (sign, unsigned) = match formatted.strip_prefix('-') {
+            Some(rest) => ("-", rest),
+            None => ("", formatted.as_str()),
+        };
+        let (whole, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+        let mut result = format!("{}{}", sign, self.integer(whole.parse().unwrap_or(0)));
+        if !fraction.is_empty() {
+            result.push(self.decimal);
+            result.push_str(fraction);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number_grouping_by_locale() {
+        let en: NumberLocale = "en".parse().unwrap();
+        assert_eq!(en, NumberLocale::default());
+        assert_eq!(en.integer(1_234_567), "1,234,567");
+        assert_eq!(en.decimal(-1234.5, 2), "-1,234.50");
+
+        let de: NumberLocale = "de-DE".parse().unwrap();
+        assert_eq!(de, "de_AT".parse().unwrap());
+        assert_eq!(de.integer(999), "999");
+        assert_eq!(de.integer(12_345), "12.345");
+        assert_eq!(de.decimal(1234.567, 2), "1.234,57");
+        assert_eq!(de.decimal(0.5, 0), "0");
+
+        let fr: NumberLocale = "FR".parse().unwrap();
+        assert_eq!(fr.integer(1_000_000), "1\u{a0}000\u{a0}000");
+        assert_eq!(fr.decimal(1.05, 2), "1,05");
+
+        assert!("xx".parse::<NumberLocale>().unwrap_err().contains("unsupported locale 'xx'"));
+    }
+
+    #[test]
+    fn test_parse_date_format() {
+        assert_eq!(parse_date_format(DEFAULT_DATE_FORMAT).unwrap(), DEFAULT_DATE_FORMAT);
+        assert_eq!(parse_date_format("%d.%m.%Y %H:%M").unwrap(), "%d.%m.%Y %H:%M");
+
+        // Unknown specifiers and a trailing lone % are rejected
+        for invalid in ["%Q", "%Y-%m-%", "%-"] {
+            let err = parse_date_format(invalid).unwrap_err();
+            assert!(err.contains(&format!("'{}'", invalid)), "{}", err);
+        }
+    }
+}
@@ -1,15 +1,105 @@
+use chrono::{DateTime, FixedOffset, SecondsFormat, Utc};
+use futures::stream::{self, StreamExt};
 use reqwest::{header, Client};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
 
 use crate::error::{Error, Result};
-use crate::github::paginator::Paginator;
+use crate::github::diff_cache::DiffCache;
+use crate::github::paginator::{PageCheckpoints, Paginator};
 use crate::github::rate_limiter::RateLimiter;
-use crate::models::{Commit, CommitSummary, GitHubUser, Repository};
+use crate::models::{
+    Commit, CommitSummary, Gist, GitHubUser, PullRequest, PullRequestDetails, Release, Repository,
+    UserEvent,
+};
+
+/// Result of a conditional (`If-None-Match`) request.
+#[derive(Debug, Clone)]
+pub enum Conditional<T> {
+    /// The server answered 304; the caller's cached copy is still current.
+    NotModified,
+    /// Fresh data, along with the `ETag` to send on the next request.
+    Modified { data: T, etag: Option<String> },
+}
+
+/// How many times a `202 Accepted` (stats still computing) is retried.
+const STATS_MAX_RETRIES: u32 = 3;
+
+/// Page size used when listing commits.
+const COMMITS_PER_PAGE: u32 = 100;
+
+/// Scopes a classic token needs for a complete analysis. Without `repo`,
+/// private repositories are silently left out of listings.
+pub const EXPECTED_SCOPES: &[&str] = &["repo"];
+
+/// Commits kept in each client's `DiffCache`.
+const DIFF_CACHE_CAPACITY: usize = 512;
+
+/// Most gists fetched for one user; each costs an extra request for its content.
+const MAX_GISTS: u32 = 100;
+
+/// Most pull requests fetched for one user, newest first; each costs an
+/// extra request for its size and branch.
+const MAX_PULL_REQUESTS: u32 = 50;
+
+/// GitHub's limit on search requests per minute for an authenticated token.
+const SEARCH_REQUESTS_PER_MINUTE: u32 = 30;
+
+/// One page of issue search results.
+#[derive(serde::Deserialize)]
+struct SearchResults<T> {
+    items: Vec<T>,
+}
+
+/// Which commits to pick when a repository has more than `max_commits_per_repo`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CommitSampling {
+    /// The newest commits
+    #[default]
+    Recent,
+    /// Commits spaced evenly across the whole history, to capture how skills evolved
+    Spread,
+    /// The earliest commits
+    Oldest,
+}
+
+impl std::str::FromStr for CommitSampling {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "recent" => Ok(CommitSampling::Recent),
+            "spread" => Ok(CommitSampling::Spread),
+            "oldest" => Ok(CommitSampling::Oldest),
+            _ => Err(format!(
+                "unknown commit sampling '{}' (expected one of: recent, spread, oldest)",
+                s
+            )),
+        }
+    }
+}
 
 pub struct GitHubClient {
     client: Client,
     rate_limiter: RateLimiter,
+    /// Search has a rate limit of its own, tracked apart so its headers do
+    /// not overwrite the core limit's
+    search_rate_limiter: RateLimiter,
     base_url: String,
+    /// Delay before the first retry of a `202` stats response; doubles each attempt
+    stats_retry_delay: Duration,
+    diff_cache: DiffCache,
+    /// Progress of interrupted repository listings, so a retry resumes
+    page_checkpoints: PageCheckpoints,
+    /// Login the token belongs to, looked up once (`None` if the lookup failed)
+    authenticated_login: tokio::sync::OnceCell<Option<String>>,
+}
+
+/// The parts of a `GET /user` response `authenticated_login` needs.
+#[derive(serde::Deserialize)]
+struct AuthenticatedUser {
+    login: String,
 }
 
 impl GitHubClient {
@@ -37,18 +127,104 @@ impl GitHubClient {
         Ok(Self {
             client,
             rate_limiter: RateLimiter::new(),
+            search_rate_limiter: RateLimiter::new()
+                .with_requests_per_minute(SEARCH_REQUESTS_PER_MINUTE),
             base_url: "https://api.github.com".to_string(),
+            stats_retry_delay: Duration::from_secs(2),
+            diff_cache: DiffCache::new(DIFF_CACHE_CAPACITY),
+            page_checkpoints: PageCheckpoints::new(),
+            authenticated_login: tokio::sync::OnceCell::new(),
         })
     }
 
+    /// Points the client at a different API root, e.g. GitHub Enterprise
+    /// (`https://github.example.com/api/v3`).
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Sends a GET to a statistics-style endpoint. GitHub answers `202 Accepted`
+    /// while it computes stats for a repository that has none cached, so retry
+    /// with backoff until the data is ready or the retries run out.
+    async fn get_stats(&self, url: &str) -> Result<reqwest::Response> {
+        let mut delay = self.stats_retry_delay;
+        let mut attempt = 0;
+
+        loop {
+            self.rate_limiter.wait().await;
+            let response = self.client.get(url).send().await?;
+            self.rate_limiter.update_from_response(&response);
+
+            if response.status() != reqwest::StatusCode::ACCEPTED || attempt >= STATS_MAX_RETRIES {
+                return Ok(response);
+            }
+
+            attempt += 1;
+            tracing::debug!(
+                "Stats for {} are still being computed, retrying in {:?} ({}/{})",
+                url,
+                delay,
+                attempt,
+                STATS_MAX_RETRIES
+            );
+            tokio::time::sleep(delay).await;
+            delay *= 2;
+        }
+    }
+
+    /// Scopes granted to the token, from the `X-OAuth-Scopes` header of a
+    /// `/rate_limit` request (which does not count against the limit).
+    /// `None` for fine-grained and GitHub App tokens, which don't report
+    /// scopes.
+    pub async fn check_scopes(&self) -> Result<Option<Vec<String>>> {
+        let url = format!("{}/rate_limit", self.base_url);
+        let response = self.client.get(&url).send().await?;
+
+        if !response.status().is_success() {
+            return Err(Error::GitHubApi(format!(
+                "Failed to check token scopes: {}",
+                response.status()
+            )));
+        }
+
+        Ok(response
+            .headers()
+            .get("x-oauth-scopes")
+            .and_then(|v| v.to_str().ok())
+            .map(parse_scopes))
+    }
+
     pub async fn get_user(&self, username: &str) -> Result<GitHubUser> {
+        match self.get_user_conditional(username, None).await? {
+            Conditional::Modified { data, .. } => Ok(data),
+            Conditional::NotModified => Err(Error::GitHubApi(format!(
+                "Unexpected 304 for user {} without an ETag",
+                username
+            ))),
+        }
+    }
+
+    pub async fn get_user_conditional(
+        &self,
+        username: &str,
+        etag: Option<&str>,
+    ) -> Result<Conditional<GitHubUser>> {
         self.rate_limiter.wait().await;
         let url = format!("{}/users/{}", self.base_url, username);
         tracing::info!("Fetching user: {}", username);
 
-        let response = self.client.get(&url).send().await?;
+        let mut request = self.client.get(&url);
+        if let Some(etag) = etag {
+            request = request.header(header::IF_NONE_MATCH, etag);
+        }
+        let response = request.send().await?;
         self.rate_limiter.update_from_response(&response);
 
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(Conditional::NotModified);
+        }
+
         if response.status() == reqwest::StatusCode::NOT_FOUND {
             return Err(Error::UserNotFound(username.to_string()));
         }
@@ -62,42 +238,427 @@ impl GitHubClient {
             )));
         }
 
-        Ok(response.json().await?)
+        let etag = response
+            .headers()
+            .get(header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string());
+
+        Ok(Conditional::Modified {
+            data: response.json().await?,
+            etag,
+        })
+    }
+
+    /// Login of the user the token belongs to, from `GET /user`. Looked up
+    /// once per client; `None` when the lookup fails, e.g. for GitHub App
+    /// installation tokens, which don't belong to a user.
+    pub async fn authenticated_login(&self) -> Option<String> {
+        self.authenticated_login
+            .get_or_init(|| async {
+                self.rate_limiter.wait().await;
+                let url = format!("{}/user", self.base_url);
+                let response = match self.client.get(&url).send().await {
+                    Ok(response) => response,
+                    Err(e) => {
+                        tracing::debug!("Could not look up the authenticated user: {}", e);
+                        return None;
+                    }
+                };
+                self.rate_limiter.update_from_response(&response);
+
+                if !response.status().is_success() {
+                    tracing::debug!("Could not look up the authenticated user: {}", response.status());
+                    return None;
+                }
+                response
+                    .json::<AuthenticatedUser>()
+                    .await
+                    .ok()
+                    .map(|user| user.login)
+            })
+            .await
+            .clone()
+    }
+
+    /// Repository listing URL for `username`. The public `/users/{u}/repos`
+    /// never includes private repositories, so when `username` is the
+    /// token's own account, `/user/repos` is listed instead.
+    async fn user_repos_url(&self, username: &str) -> String {
+        let login = self.authenticated_login().await;
+        user_repos_url(&self.base_url, username, login.as_deref())
     }
 
     pub async fn get_user_repos(&self, username: &str) -> Result<Vec<Repository>> {
-        let url = format!("{}/users/{}/repos?type=owner&sort=updated", self.base_url, username);
-        let paginator = Paginator::new(&self.client, &self.rate_limiter);
+        let url = self.user_repos_url(username).await;
+        let paginator = Paginator::new(&self.client, &self.rate_limiter)
+            .with_checkpoints(&self.page_checkpoints);
         tracing::info!("Fetching repositories for: {}", username);
         paginator.fetch_all(&url, 100).await
     }
 
+    /// Conditional variant of `get_user_repos`. Repos are sorted by last update,
+    /// so any change surfaces on the first page and its `ETag` covers the list.
+    pub async fn get_user_repos_conditional(
+        &self,
+        username: &str,
+        etag: Option<&str>,
+    ) -> Result<Conditional<Vec<Repository>>> {
+        let url = self.user_repos_url(username).await;
+        let paginator = Paginator::new(&self.client, &self.rate_limiter)
+            .with_checkpoints(&self.page_checkpoints);
+        tracing::info!("Fetching repositories for: {}", username);
+        paginator.fetch_all_conditional(&url, 100, etag).await
+    }
+
+    pub async fn get_repo(&self, owner: &str, repo: &str) -> Result<Repository> {
+        self.rate_limiter.wait().await;
+        let url = format!("{}/repos/{}/{}", self.base_url, owner, repo);
+
+        let response = self.client.get(&url).send().await?;
+        self.rate_limiter.update_from_response(&response);
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(Error::RepoNotFound(format!("{}/{}", owner, repo)));
+        }
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::GitHubApi(format!(
+                "Failed to fetch repository {}/{}: {} - {}",
+                owner, repo, status, body
+            )));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    /// Finds repositories owned by others that the user has recently pushed to
+    /// or opened pull requests against, based on their public events feed.
+    /// GitHub only retains the last 300 events (up to 90 days).
+    pub async fn get_user_contributed_repos(&self, username: &str) -> Result<Vec<Repository>> {
+        let url = format!("{}/users/{}/events/public", self.base_url, username);
+        let paginator = Paginator::new(&self.client, &self.rate_limiter);
+        tracing::info!("Fetching contributed repositories for: {}", username);
+        let events: Vec<UserEvent> = paginator.fetch_limited(&url, 100, 300).await?;
+
+        let own_prefix = format!("{}/", username.to_lowercase());
+        let mut seen = HashSet::new();
+        let full_names: Vec<String> = events
+            .into_iter()
+            .filter(|e| e.event_type == "PushEvent" || e.event_type == "PullRequestEvent")
+            .map(|e| e.repo.name)
+            .filter(|name| !name.to_lowercase().starts_with(&own_prefix))
+            .filter(|name| seen.insert(name.clone()))
+            .collect();
+
+        let mut repos = Vec::new();
+        for full_name in full_names {
+            let Some((owner, name)) = full_name.split_once('/') else {
+                continue;
+            };
+            match self.get_repo(owner, name).await {
+                Ok(repo) => repos.push(repo),
+                Err(e) => tracing::warn!("Skipping contributed repo {}: {}", full_name, e),
+            }
+        }
+
+        Ok(repos)
+    }
+
+    /// Fetches the user's public gists, newest first, with file contents.
+    /// Listings omit contents, so each gist is fetched on its own; gists
+    /// that fail to load are skipped with a warning.
+    pub async fn get_user_gists(&self, username: &str) -> Result<Vec<Gist>> {
+        let url = format!("{}/users/{}/gists", self.base_url, username);
+        let paginator = Paginator::new(&self.client, &self.rate_limiter);
+        tracing::info!("Fetching gists for: {}", username);
+        let listed: Vec<Gist> = paginator.fetch_limited(&url, 100, MAX_GISTS).await?;
+
+        let mut gists = Vec::with_capacity(listed.len());
+        for gist in listed {
+            match self.get_gist(&gist.id).await {
+                Ok(gist) => gists.push(gist),
+                Err(e) => tracing::warn!("Skipping gist {}: {}", gist.id, e),
+            }
+        }
+        Ok(gists)
+    }
+
+    async fn get_gist(&self, id: &str) -> Result<Gist> {
+        self.rate_limiter.wait().await;
+        let url = format!("{}/gists/{}", self.base_url, id);
+
+        let response = self.client.get(&url).send().await?;
+        self.rate_limiter.update_from_response(&response);
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::GitHubApi(format!(
+                "Failed to fetch gist {}: {} - {}",
+                id, status, body
+            )));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    /// The user's most recent pull requests, across all repositories they
+    /// can see. Each is fetched on its own for its size and branch, up to
+    /// `concurrency` at once; when that fails, or `shutdown` is cancelled
+    /// first, it is kept without `details`.
+    pub async fn get_user_pull_requests(
+        &self,
+        username: &str,
+        concurrency: usize,
+        shutdown: &CancellationToken,
+    ) -> Result<Vec<PullRequest>> {
+        self.search_rate_limiter.wait().await;
+        let url = format!(
+            "{}/search/issues?q=author:{}+type:pr&sort=created&order=desc&per_page={}",
+            self.base_url, username, MAX_PULL_REQUESTS
+        );
+        tracing::info!("Fetching pull requests for: {}", username);
+
+        let response = self.client.get(&url).send().await?;
+        self.search_rate_limiter.update_from_response(&response);
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::GitHubApi(format!(
+                "Failed to search pull requests by {}: {} - {}",
+                username, status, body
+            )));
+        }
+
+        let mut pull_requests = response.json::<SearchResults<PullRequest>>().await?.items;
+        // Consumed lazily, like `get_commits_with_diff`'s SHAs, so shutdown
+        // stops further detail requests while those in flight finish
+        let pending = pull_requests.iter().take_while(|_| !shutdown.is_cancelled());
+        let details: Vec<_> = stream::iter(pending)
+            .map(|pull_request| async move {
+                let (owner, repo) = pull_request.repository()?;
+                match self.get_pull_request_details(owner, repo, pull_request.number).await {
+                    Ok(details) => Some(details),
+                    Err(e) => {
+                        tracing::debug!("No size for {}/{}#{}: {}", owner, repo, pull_request.number, e);
+                        None
+                    }
+                }
+            })
+            .buffered(concurrency.max(1))
+            .collect()
+            .await;
+        for (pull_request, details) in pull_requests.iter_mut().zip(details) {
+            pull_request.details = details;
+        }
+        Ok(pull_requests)
+    }
+
+    async fn get_pull_request_details(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+    ) -> Result<PullRequestDetails> {
+        self.rate_limiter.wait().await;
+        let url = format!("{}/repos/{}/{}/pulls/{}", self.base_url, owner, repo, number);
+
+        let response = self.client.get(&url).send().await?;
+        self.rate_limiter.update_from_response(&response);
+
+        if !response.status().is_success() {
+            return Err(Error::GitHubApi(format!(
+                "Failed to fetch pull request: {}",
+                response.status()
+            )));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    /// URL listing a repository's commits, optionally only those by
+    /// `author` and made at or after `since` and at or before `until`.
+    fn commits_url(
+        &self,
+        owner: &str,
+        repo: &str,
+        author: Option<&str>,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> String {
+        let mut params = Vec::new();
+        if let Some(author) = author {
+            params.push(format!("author={}", author));
+        }
+        if let Some(since) = since {
+            params.push(format!("since={}", since.to_rfc3339_opts(SecondsFormat::Secs, true)));
+        }
+        if let Some(until) = until {
+            params.push(format!("until={}", until.to_rfc3339_opts(SecondsFormat::Secs, true)));
+        }
+
+        let mut url = format!("{}/repos/{}/{}/commits", self.base_url, owner, repo);
+        if !params.is_empty() {
+            url.push('?');
+            url.push_str(&params.join("&"));
+        }
+        url
+    }
+
+    /// Lists up to `max_commits` commits made between `since` and `until`
+    /// (inclusive), newest first, chosen from the repository's history
+    /// according to `sampling`.
+    /// Sampling other than `Recent` costs one extra request to count the
+    /// commits, then fetches only the pages holding the chosen commits; pass
+    /// `total` when the caller has already counted them with
+    /// `count_repo_commits`. Empty repositories, for which GitHub answers
+    /// 409, have no commits rather than failing.
+    #[allow(clippy::too_many_arguments)]
     pub async fn get_repo_commits(
         &self,
         owner: &str,
         repo: &str,
         author: Option<&str>,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
         max_commits: u32,
+        sampling: CommitSampling,
+        total: Option<u32>,
     ) -> Result<Vec<CommitSummary>> {
-        let mut url = format!("{}/repos/{}/{}/commits", self.base_url, owner, repo);
-        if let Some(author) = author {
-            url.push_str(&format!("?author={}", author));
+        let url = self.commits_url(owner, repo, author, since, until);
+
+        let paginator = self.commits_paginator();
+        tracing::debug!("Fetching commits for: {}/{} ({:?})", owner, repo, sampling);
+        if sampling == CommitSampling::Recent {
+            return paginator.fetch_limited(&url, COMMITS_PER_PAGE, max_commits).await;
         }
 
-        let paginator = Paginator::new(&self.client, &self.rate_limiter);
-        tracing::debug!("Fetching commits for: {}/{}", owner, repo);
-        paginator.fetch_limited(&url, 100, max_commits).await
+        let total = match total {
+            Some(total) => total,
+            None => self.count_repo_commits(owner, repo, author, since, until).await?,
+        };
+        if total <= max_commits {
+            return paginator.fetch_limited(&url, COMMITS_PER_PAGE, max_commits).await;
+        }
+
+        let indices = sample_indices(total, max_commits, sampling);
+        let mut pages: Vec<u32> = indices.iter().map(|i| i / COMMITS_PER_PAGE + 1).collect();
+        pages.dedup();
+
+        let mut commits = Vec::with_capacity(indices.len());
+        let mut wanted = indices.iter().peekable();
+        for page in pages {
+            let items: Vec<CommitSummary> =
+                paginator.fetch_page(&url, COMMITS_PER_PAGE, page).await?;
+            let first = (page - 1) * COMMITS_PER_PAGE;
+            while let Some(&&index) = wanted.peek() {
+                if index >= first + COMMITS_PER_PAGE {
+                    break;
+                }
+                // History can shift between the count and the fetch
+                if let Some(commit) = items.get((index - first) as usize) {
+                    commits.push(commit.clone());
+                }
+                wanted.next();
+            }
+        }
+        Ok(commits)
+    }
+
+    /// Lists up to `max_commits` commits made between `since` and `until`
+    /// (inclusive), newest first, for which `matches` returns true. Without a server-side author
+    /// filter this pages through the repository's whole history until enough
+    /// commits match.
+    pub async fn get_repo_commits_matching(
+        &self,
+        owner: &str,
+        repo: &str,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+        max_commits: u32,
+        matches: impl Fn(&CommitSummary) -> bool,
+    ) -> Result<Vec<CommitSummary>> {
+        let url = self.commits_url(owner, repo, None, since, until);
+        tracing::debug!("Scanning commits for: {}/{}", owner, repo);
+        self.commits_paginator()
+            .fetch_matching(&url, COMMITS_PER_PAGE, max_commits, matches)
+            .await
+    }
+
+    /// A paginator for commit listings. GitHub answers those with
+    /// `409 Conflict` ("Git Repository is empty") instead of an empty page
+    /// when a repository has no commits yet.
+    fn commits_paginator(&self) -> Paginator<'_> {
+        Paginator::new(&self.client, &self.rate_limiter)
+            .with_empty_status(reqwest::StatusCode::CONFLICT)
     }
 
+    /// Counts commits in a repository (optionally by `author`, and made
+    /// between `since` and `until`) with a single `per_page=1` request, reading the total
+    /// from the `Link` header's last page. Empty repositories (409) count as
+    /// zero.
+    pub async fn count_repo_commits(
+        &self,
+        owner: &str,
+        repo: &str,
+        author: Option<&str>,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<u32> {
+        self.rate_limiter.wait().await;
+        let url = self.commits_url(owner, repo, author, since, until);
+        let separator = if url.contains('?') { "&" } else { "?" };
+        let url = format!("{}{}per_page=1", url, separator);
+
+        let response = self.client.get(&url).send().await?;
+        self.rate_limiter.update_from_response(&response);
+
+        if response.status() == reqwest::StatusCode::CONFLICT {
+            return Ok(0);
+        }
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::GitHubApi(format!(
+                "Failed to count commits for {}/{}: {} - {}",
+                owner, repo, status, body
+            )));
+        }
+
+        let last_page = response
+            .headers()
+            .get("link")
+            .and_then(|v| v.to_str().ok())
+            .and_then(last_page_from_link);
+
+        match last_page {
+            Some(count) => Ok(count),
+            // No Link header means everything fit on the single page
+            None => Ok(response.json::<Vec<CommitSummary>>().await?.len() as u32),
+        }
+    }
+
+    /// Fetches a commit with its file diffs. Commits already fetched during
+    /// this run, from any repository, are served from the `DiffCache`.
     pub async fn get_commit_with_diff(
         &self,
         owner: &str,
         repo: &str,
         sha: &str,
     ) -> Result<Commit> {
+        if let Some(commit) = self.diff_cache.get(sha) {
+            tracing::debug!("Commit diff {} served from cache", &sha[..7.min(sha.len())]);
+            return Ok(commit);
+        }
+
         self.rate_limiter.wait().await;
         let url = format!("{}/repos/{}/{}/commits/{}", self.base_url, owner, repo, sha);
-        tracing::debug!("Fetching commit diff: {}", &sha[..7]);
+        tracing::debug!("Fetching commit diff: {}", &sha[..7.min(sha.len())]);
 
         let response = self.client.get(&url).send().await?;
         self.rate_limiter.update_from_response(&response);
@@ -111,20 +672,125 @@ impl GitHubClient {
             )));
         }
 
-        Ok(response.json().await?)
+        let commit: Commit = response.json().await?;
+        self.diff_cache.insert(sha.to_string(), commit.clone());
+        Ok(commit)
     }
 
-    pub async fn get_repo_languages(
+    /// Fetches `get_commit_with_diff` for each SHA, up to `concurrency` at
+    /// once, and returns every result paired with its SHA in input order.
+    /// `shas` is consumed lazily, one item per request started, so an
+    /// iterator that stops early (e.g. on shutdown) prevents further requests
+    /// while those in flight finish.
+    pub async fn get_commits_with_diff(
         &self,
         owner: &str,
         repo: &str,
-    ) -> Result<HashMap<String, u64>> {
+        shas: impl IntoIterator<Item = String>,
+        concurrency: usize,
+    ) -> Vec<(String, Result<Commit>)> {
+        stream::iter(shas)
+            .map(|sha| async move {
+                let result = self.get_commit_with_diff(owner, repo, &sha).await;
+                (sha, result)
+            })
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// The UTC offset the commit's author made it in, from the `Date:`
+    /// header of the commit's patch. The JSON API only reports UTC.
+    pub async fn get_author_offset(
+        &self,
+        owner: &str,
+        repo: &str,
+        sha: &str,
+    ) -> Result<Option<FixedOffset>> {
         self.rate_limiter.wait().await;
-        let url = format!("{}/repos/{}/{}/languages", self.base_url, owner, repo);
+        let url = format!("{}/repos/{}/{}/commits/{}", self.base_url, owner, repo, sha);
+
+        let response = self
+            .client
+            .get(&url)
+            .header(header::ACCEPT, "application/vnd.github.patch")
+            .send()
+            .await?;
+        self.rate_limiter.update_from_response(&response);
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::GitHubApi(format!(
+                "Failed to fetch patch for commit {}: {} - {}",
+                sha, status, body
+            )));
+        }
+
+        Ok(patch_author_offset(&response.text().await?))
+    }
+
+    /// Moves each commit's author date into the offset its author made it
+    /// in (see `get_author_offset`), up to `concurrency` commits at once.
+    /// Commits whose patch cannot be fetched keep their UTC date.
+    pub async fn localize_author_dates(
+        &self,
+        owner: &str,
+        repo: &str,
+        commits: &mut [Commit],
+        concurrency: usize,
+    ) {
+        stream::iter(commits.iter_mut())
+            .for_each_concurrent(concurrency.max(1), |commit| async move {
+                match self.get_author_offset(owner, repo, &commit.sha).await {
+                    Ok(Some(offset)) => {
+                        commit.commit.author.date = commit.commit.author.date.with_timezone(&offset)
+                    }
+                    Ok(None) => {}
+                    Err(e) => tracing::debug!("No author timezone for {}: {}", commit.sha, e),
+                }
+            })
+            .await
+    }
+
+    /// The repository's latest published release, or `None` when it has no
+    /// releases.
+    pub async fn get_latest_release(&self, owner: &str, repo: &str) -> Result<Option<Release>> {
+        self.rate_limiter.wait().await;
+        let url = format!("{}/repos/{}/{}/releases/latest", self.base_url, owner, repo);
 
         let response = self.client.get(&url).send().await?;
         self.rate_limiter.update_from_response(&response);
 
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::GitHubApi(format!(
+                "Failed to fetch latest release for {}/{}: {} - {}",
+                owner, repo, status, body
+            )));
+        }
+
+        Ok(Some(response.json().await?))
+    }
+
+    pub async fn get_repo_languages(
+        &self,
+        owner: &str,
+        repo: &str,
+    ) -> Result<HashMap<String, u64>> {
+        let url = format!("{}/repos/{}/{}/languages", self.base_url, owner, repo);
+        let response = self.get_stats(&url).await?;
+
+        if response.status() == reqwest::StatusCode::ACCEPTED {
+            tracing::warn!("Language stats for {}/{} were not ready in time", owner, repo);
+            return Ok(HashMap::new());
+        }
+
         if !response.status().is_success() {
             return Ok(HashMap::new());
         }
@@ -132,6 +798,35 @@ impl GitHubClient {
         Ok(response.json().await?)
     }
 
+    /// Sums `get_repo_languages` byte counts across `repos`, fetching up to
+    /// `concurrency` repositories at once. Failed lookups are skipped.
+    pub async fn get_language_totals(
+        &self,
+        repos: &[Repository],
+        concurrency: usize,
+    ) -> HashMap<String, u64> {
+        let per_repo: Vec<_> = stream::iter(repos)
+            .map(|repo| async move {
+                self.get_repo_languages(&repo.owner.login, &repo.name)
+                    .await
+                    .unwrap_or_else(|e| {
+                        tracing::warn!("Could not fetch languages for {}: {}", repo.full_name, e);
+                        HashMap::new()
+                    })
+            })
+            .buffer_unordered(concurrency.max(1))
+            .collect()
+            .await;
+
+        let mut totals = HashMap::new();
+        for languages in per_repo {
+            for (language, bytes) in languages {
+                *totals.entry(language).or_insert(0) += bytes;
+            }
+        }
+        totals
+    }
+
     pub fn rate_limiter(&self) -> &RateLimiter {
         &self.rate_limiter
     }
@@ -140,3 +835,310 @@ impl GitHubClient {
         &self.client
     }
 }
+
+/// Offset of the `Date:` header in a commit patch's mail headers, e.g.
+/// "Date: Thu, 5 May 2011 16:37:02 -0700". `None` if the header is missing
+/// or malformed.
+fn patch_author_offset(patch: &str) -> Option<FixedOffset> {
+    let date = patch
+        .lines()
+        .take_while(|line| !line.is_empty())
+        .find_map(|line| line.strip_prefix("Date: "))?;
+    DateTime::parse_from_rfc2822(date.trim()).ok().map(|date| *date.offset())
+}
+
+/// Positions (0 = newest) of the commits to analyze out of `total`, ascending.
+fn sample_indices(total: u32, max_commits: u32, sampling: CommitSampling) -> Vec<u32> {
+    let count = max_commits.min(total);
+    match sampling {
+        CommitSampling::Recent => (0..count).collect(),
+        CommitSampling::Oldest => (total - count..total).collect(),
+        // Evenly spaced, always including the newest commit
+        CommitSampling::Spread => (0..count)
+            .map(|i| (i as u64 * total as u64 / count as u64) as u32)
+            .collect(),
+    }
+}
+
+/// `/user/repos` (private repositories included) when `username` is the
+/// authenticated user, otherwise the public `/users/{username}/repos`.
+fn user_repos_url(base_url: &str, username: &str, authenticated_login: Option<&str>) -> String {
+    if authenticated_login.is_some_and(|login| login.eq_ignore_ascii_case(username)) {
+        format!("{}/user/repos?visibility=all&affiliation=owner&sort=updated", base_url)
+    } else {
+        format!("{}/users/{}/repos?type=owner&sort=updated", base_url, username)
+    }
+}
+
+/// Splits an `X-OAuth-Scopes` value such as `"repo, read:org"`.
+fn parse_scopes(header: &str) -> Vec<String> {
+    header
+        .split(',')
+        .map(|scope| scope.trim().to_string())
+        .filter(|scope| !scope.is_empty())
+        .collect()
+}
+
+/// The `EXPECTED_SCOPES` missing from `granted`.
+pub fn missing_scopes(granted: &[String]) -> Vec<&'static str> {
+    EXPECTED_SCOPES
+        .iter()
+        .copied()
+        .filter(|expected| !granted.iter().any(|scope| scope == expected))
+        .collect()
+}
+
+/// Extracts the `page` number of the `rel="last"` entry in a `Link` header.
+fn last_page_from_link(link: &str) -> Option<u32> {
+    link.split(',')
+        .find(|part| part.contains("rel=\"last\""))
+        .and_then(|part| {
+            let url = part.split(';').next()?.trim().trim_start_matches('<').trim_end_matches('>');
+            url.split(['?', '&'])
+                .find_map(|param| param.strip_prefix("page="))
+                .and_then(|page| page.parse().ok())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockResponse, MockServer};
+
+    #[test]
+    fn test_last_page_from_link() {
+        let link = "<https://api.github.com/repositories/1/commits?per_page=1&page=2>; rel=\"next\", \
+                    <https://api.github.com/repositories/1/commits?per_page=1&page=42>; rel=\"last\"";
+        assert_eq!(last_page_from_link(link), Some(42));
+        assert_eq!(
+            last_page_from_link("<https://api.github.com/x?page=1>; rel=\"prev\""),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_author_dates_localized_from_patch() {
+        let patch = "From abc1234 Mon Sep 17 00:00:00 2001\n\
+                     From: Mona <mona@example.com>\n\
+                     Date: Thu, 5 May 2011 16:37:02 -0700\n\
+                     Subject: [PATCH] Fix\n\n\
+                     Date: Fri, 6 May 2011 00:00:00 +0900\n";
+        assert_eq!(patch_author_offset(patch).unwrap().to_string(), "-07:00");
+        assert_eq!(patch_author_offset("From: Mona <mona@example.com>\n\nDate: x\n"), None);
+
+        let server = MockServer::start(vec![
+            MockResponse::status(reqwest::StatusCode::OK).body(patch),
+            MockResponse::status(reqwest::StatusCode::NOT_FOUND),
+        ])
+        .await;
+        let client = GitHubClient::new("test-token").unwrap().with_base_url(server.url());
+
+        let mut commits = vec![
+            crate::test_support::commit("abc1234", &[]),
+            crate::test_support::commit("def5678", &[]),
+        ];
+        let utc_dates: Vec<_> = commits.iter().map(|c| c.commit.author.date).collect();
+        client.localize_author_dates("octocat", "hello", &mut commits, 1).await;
+
+        // Same instant, in the author's offset; the failed lookup stays UTC
+        assert_eq!(commits[0].commit.author.date, utc_dates[0]);
+        assert_eq!(commits[0].commit.author.date.offset().to_string(), "-07:00");
+        assert_eq!(commits[1].commit.author.date.offset().to_string(), "+00:00");
+
+        let requests = server.finish().await;
+        assert_eq!(requests[0].target, "/repos/octocat/hello/commits/abc1234");
+        assert_eq!(requests[0].header("accept"), Some("application/vnd.github.patch"));
+    }
+
+    #[test]
+    fn test_commits_url() {
+        let client = GitHubClient::new("test-token").unwrap().with_base_url("https://example.test");
+        let since = "2024-03-01T12:30:00Z".parse().unwrap();
+        assert_eq!(
+            client.commits_url("octocat", "hello", None, None, None),
+            "https://example.test/repos/octocat/hello/commits"
+        );
+        assert_eq!(
+            client.commits_url("octocat", "hello", Some("octocat"), Some(since), Some(since)),
+            "https://example.test/repos/octocat/hello/commits?author=octocat&since=2024-03-01T12:30:00Z&until=2024-03-01T12:30:00Z"
+        );
+    }
+
+    #[test]
+    fn test_sample_indices() {
+        assert_eq!(sample_indices(10, 3, CommitSampling::Recent), vec![0, 1, 2]);
+        assert_eq!(sample_indices(10, 3, CommitSampling::Oldest), vec![7, 8, 9]);
+        assert_eq!(sample_indices(10, 3, CommitSampling::Spread), vec![0, 3, 6]);
+        assert_eq!(sample_indices(1_000, 4, CommitSampling::Spread), vec![0, 250, 500, 750]);
+        assert_eq!(sample_indices(2, 5, CommitSampling::Spread), vec![0, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_languages_retries_while_stats_are_computing() {
+        let server = MockServer::start(vec![
+            MockResponse::status(reqwest::StatusCode::ACCEPTED),
+            MockResponse::json(r#"{"Rust": 900, "Go": 100}"#),
+        ])
+        .await;
+
+        let mut client = GitHubClient::new("test-token").unwrap().with_base_url(server.url());
+        client.stats_retry_delay = Duration::from_millis(10);
+
+        let languages = client.get_repo_languages("octocat", "hello").await.unwrap();
+        assert_eq!(languages.get("Rust"), Some(&900));
+        assert_eq!(languages.get("Go"), Some(&100));
+    }
+
+    #[tokio::test]
+    async fn test_repeated_commit_diff_is_cached() {
+        // Only one response: a second request would fail to connect
+        let server = MockServer::start(vec![MockResponse::json(
+            r#"{"sha":"abc1234","commit":{"message":"Fix","author":{"name":"o","email":"o@x","date":"2024-01-01T00:00:00Z"}}}"#,
+        )])
+        .await;
+        let client = GitHubClient::new("test-token").unwrap().with_base_url(server.url());
+
+        let first = client.get_commit_with_diff("octocat", "hello", "abc1234").await.unwrap();
+        let again = client.get_commit_with_diff("fork", "hello", "abc1234").await.unwrap();
+        assert_eq!(first.sha, again.sha);
+        assert_eq!(again.commit.message, "Fix");
+    }
+
+    #[tokio::test]
+    async fn test_check_scopes() {
+        let server = MockServer::start(vec![
+            MockResponse::json("{}").header("X-OAuth-Scopes", "public_repo, read:org"),
+            MockResponse::json("{}"),
+        ])
+        .await;
+        let client = GitHubClient::new("test-token").unwrap().with_base_url(server.url());
+
+        let granted = client.check_scopes().await.unwrap().unwrap();
+        assert_eq!(granted, vec!["public_repo", "read:org"]);
+        assert_eq!(missing_scopes(&granted), vec!["repo"]);
+        assert!(missing_scopes(&["repo".to_string()]).is_empty());
+
+        // Fine-grained tokens send no scopes header
+        assert_eq!(client.check_scopes().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_own_repos_include_private() {
+        let server = MockServer::start(vec![MockResponse::json(r#"{"login":"Octocat"}"#)]).await;
+        let base_url = server.url();
+        let client = GitHubClient::new("test-token").unwrap().with_base_url(base_url);
+
+        // Looked up once; a second request would fail to connect
+        assert_eq!(client.authenticated_login().await.as_deref(), Some("Octocat"));
+        assert_eq!(
+            client.user_repos_url("octocat").await,
+            format!("{}/user/repos?visibility=all&affiliation=owner&sort=updated", base_url)
+        );
+        assert_eq!(
+            client.user_repos_url("hubot").await,
+            format!("{}/users/hubot/repos?type=owner&sort=updated", base_url)
+        );
+        assert_eq!(
+            user_repos_url("https://api.github.com", "octocat", None),
+            "https://api.github.com/users/octocat/repos?type=owner&sort=updated"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_empty_repository_has_no_commits() {
+        let conflict = MockResponse::status(reqwest::StatusCode::CONFLICT)
+            .header("Content-Type", "application/json")
+            .body(r#"{"message": "Git Repository is empty."}"#);
+        let server = MockServer::start(vec![conflict; 3]).await;
+        let client = GitHubClient::new("test-token").unwrap().with_base_url(server.url());
+
+        let recent = client
+            .get_repo_commits("octocat", "empty", Some("octocat"), None, None, 50, CommitSampling::Recent, None)
+            .await
+            .unwrap();
+        assert!(recent.is_empty());
+        // Spread sampling counts first, which also sees the 409
+        let spread = client
+            .get_repo_commits("octocat", "empty", Some("octocat"), None, None, 50, CommitSampling::Spread, None)
+            .await
+            .unwrap();
+        assert!(spread.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_commits_with_diff_keeps_input_order() {
+        let server = MockServer::start(vec![
+            MockResponse::json(
+                r#"{"sha":"aaaaaaa1","commit":{"message":"Fix","author":{"name":"Mona","email":"mona@example.com","date":"2024-01-01T00:00:00Z"}},"stats":null,"files":[]}"#,
+            ),
+            MockResponse::status(reqwest::StatusCode::NOT_FOUND),
+        ])
+        .await;
+        let client = GitHubClient::new("test-token").unwrap().with_base_url(server.url());
+
+        let shas = ["aaaaaaa1", "bbbbbbb2", "ccccccc3"].map(String::from);
+        let mut started = 0;
+        // The third SHA is never requested once the iterator stops
+        let results = client
+            .get_commits_with_diff(
+                "octocat",
+                "hello",
+                shas.into_iter().take_while(|_| {
+                    started += 1;
+                    started <= 2
+                }),
+                1,
+            )
+            .await;
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0, "aaaaaaa1");
+        assert_eq!(results[0].1.as_ref().unwrap().commit.message, "Fix");
+        assert_eq!(results[1].0, "bbbbbbb2");
+        assert!(results[1].1.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_pull_request_details_fetched_concurrently_until_shutdown() {
+        let search = MockResponse::json(
+            serde_json::json!({
+                "items": (1..=3).map(|number| serde_json::json!({
+                    "number": number,
+                    "title": format!("PR {}", number),
+                    "repository_url": "https://api.github.com/repos/octocat/hello",
+                    "created_at": "2024-01-01T00:00:00Z",
+                    "closed_at": null,
+                    "pull_request": { "merged_at": null },
+                })).collect::<Vec<_>>(),
+            })
+            .to_string(),
+        );
+        // Identical, since concurrent requests may arrive in any order
+        let details = MockResponse::json(r#"{"additions":10,"deletions":2,"head":{"ref":"feature/x"}}"#);
+        let server = MockServer::start(vec![search.clone(), details.clone(), details.clone(), details]).await;
+        let client = GitHubClient::new("test-token").unwrap().with_base_url(server.url());
+
+        let shutdown = CancellationToken::new();
+        let pull_requests = client.get_user_pull_requests("octocat", 3, &shutdown).await.unwrap();
+        assert_eq!(pull_requests.len(), 3);
+        assert!(pull_requests.iter().all(|pr| pr.details.as_ref().is_some_and(|d| d.additions == 10)));
+
+        let requests = server.finish().await;
+        assert!(requests[0].target.starts_with("/search/issues?q=author:octocat+type:pr"));
+        let mut detail_targets: Vec<_> = requests[1..].iter().map(|r| r.target.as_str()).collect();
+        detail_targets.sort();
+        assert_eq!(
+            detail_targets,
+            vec!["/repos/octocat/hello/pulls/1", "/repos/octocat/hello/pulls/2", "/repos/octocat/hello/pulls/3"]
+        );
+
+        // After shutdown only the search is sent; the results lack details
+        let server = MockServer::start(vec![search]).await;
+        let client = GitHubClient::new("test-token").unwrap().with_base_url(server.url());
+        shutdown.cancel();
+        let pull_requests = client.get_user_pull_requests("octocat", 3, &shutdown).await.unwrap();
+        assert_eq!(pull_requests.len(), 3);
+        assert!(pull_requests.iter().all(|pr| pr.details.is_none()));
+        assert_eq!(server.finish().await.len(), 1);
+    }
+}
@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::models::Commit;
+
+/// Commits fetched with their diffs during a run, keyed by SHA. A SHA names
+/// the same content in every repository, so a commit reachable from more
+/// than one (a fork and its upstream, say) is only fetched once. Holds at
+/// most `capacity` commits; the least recently used is evicted first.
+pub struct DiffCache {
+    capacity: usize,
+    state: Mutex<CacheState>,
+}
+
+#[derive(Default)]
+struct CacheState {
+    /// Each commit with the tick it was last used at
+    entries: HashMap<String, (Commit, u64)>,
+    tick: u64,
+}
+
+impl DiffCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    /// A copy of the cached commit, marking it as recently used.
+    pub fn get(&self, sha: &str) -> Option<Commit> {
+        let mut state = self.state.lock().unwrap();
+        state.tick += 1;
+        let tick = state.tick;
+        let (commit, last_used) = state.entries.get_mut(sha)?;
+        *last_used = tick;
+        Some(commit.clone())
+    }
+
+    /// Caches `commit`, evicting the least recently used one when full.
+    pub fn insert(&self, sha: String, commit: Commit) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        if state.entries.len() >= self.capacity && !state.entries.contains_key(&sha) {
+            let oldest = state
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(sha, _)| sha.clone());
+            if let Some(oldest) = oldest {
+                state.entries.remove(&oldest);
+            }
+        }
+        state.tick += 1;
+        let tick = state.tick;
+        state.entries.insert(sha, (commit, tick));
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CommitAuthor, CommitDetails};
+
+    fn commit(sha: &str) -> Commit {
+        Commit {
+            sha: sha.to_string(),
+            commit: CommitDetails {
+                message: String::new(),
+                author: CommitAuthor {
+                    name: String::new(),
+                    email: String::new(),
+                    date: chrono::Utc::now().into(),
+                },
+            },
+            stats: None,
+            files: None,
+        }
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let cache = DiffCache::new(2);
+        cache.insert("a".to_string(), commit("a"));
+        cache.insert("b".to_string(), commit("b"));
+        // Using "a" makes "b" the oldest
+        assert_eq!(cache.get("a").unwrap().sha, "a");
+        cache.insert("c".to_string(), commit("c"));
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("a").is_some());
+        assert!(cache.get("c").is_some());
+
+        let disabled = DiffCache::new(0);
+        disabled.insert("a".to_string(), commit("a"));
+        assert!(disabled.is_empty());
+    }
+}
@@ -1,7 +1,9 @@
 pub mod client;
+pub mod diff_cache;
 pub mod rate_limiter;
 pub mod paginator;
 
-pub use client::GitHubClient;
+pub use client::{missing_scopes, CommitSampling, Conditional, GitHubClient, EXPECTED_SCOPES};
+pub use diff_cache::DiffCache;
 pub use rate_limiter::RateLimiter;
-pub use paginator::Paginator;
+pub use paginator::{PageCheckpoints, Paginator};
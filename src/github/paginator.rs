@@ -1,11 +1,66 @@
-use reqwest::Client;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use reqwest::{header, Client, StatusCode};
 use serde::de::DeserializeOwned;
+use crate::github::client::Conditional;
 use crate::github::rate_limiter::RateLimiter;
-use crate::error::Result;
+use crate::error::{Error, Result};
+
+/// How far an interrupted `fetch_all` got: the URL of the next page to
+/// fetch and every item fetched before it.
+#[derive(Debug, Clone)]
+struct Checkpoint {
+    next_url: String,
+    pages: u32,
+    etag: Option<String>,
+    items: Vec<serde_json::Value>,
+}
+
+/// Progress of `fetch_all` calls during a run, keyed by the first page's
+/// URL, so fetching the same listing again after a failure resumes from the
+/// last page that succeeded instead of starting over. A listing's
+/// checkpoint is dropped once it is fetched completely.
+#[derive(Default)]
+pub struct PageCheckpoints {
+    checkpoints: Mutex<HashMap<String, Checkpoint>>,
+}
+
+impl PageCheckpoints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn get(&self, first_url: &str) -> Option<Checkpoint> {
+        self.checkpoints.lock().unwrap().get(first_url).cloned()
+    }
+
+    /// Records a fetched page's `items`, and that `next_url` comes next.
+    fn record(&self, first_url: &str, next_url: &str, etag: Option<&str>, items: &[serde_json::Value]) {
+        let mut checkpoints = self.checkpoints.lock().unwrap();
+        let checkpoint = checkpoints
+            .entry(first_url.to_string())
+            .or_insert_with(|| Checkpoint {
+                next_url: String::new(),
+                pages: 0,
+                etag: etag.map(str::to_string),
+                items: Vec::new(),
+            });
+        checkpoint.next_url = next_url.to_string();
+        checkpoint.pages += 1;
+        checkpoint.items.extend_from_slice(items);
+    }
+
+    fn clear(&self, first_url: &str) {
+        self.checkpoints.lock().unwrap().remove(first_url);
+    }
+}
 
 pub struct Paginator<'a> {
     client: &'a Client,
     rate_limiter: &'a RateLimiter,
+    checkpoints: Option<&'a PageCheckpoints>,
+    empty_status: Option<StatusCode>,
 }
 
 impl<'a> Paginator<'a> {
@@ -13,88 +68,379 @@ impl<'a> Paginator<'a> {
         Self {
             client,
             rate_limiter,
+            checkpoints: None,
+            empty_status: None,
         }
     }
 
+    /// Records the progress of `fetch_all`, `fetch_all_from` and
+    /// `fetch_all_conditional` in `checkpoints`, and resumes from it.
+    pub fn with_checkpoints(mut self, checkpoints: &'a PageCheckpoints) -> Self {
+        self.checkpoints = Some(checkpoints);
+        self
+    }
+
+    /// Makes `fetch_page`, `fetch_limited` and `fetch_matching` treat a
+    /// `status` response as the end of the results, for endpoints that
+    /// answer an empty listing with an error status.
+    pub fn with_empty_status(mut self, status: StatusCode) -> Self {
+        self.empty_status = Some(status);
+        self
+    }
+
+    fn is_empty(&self, response: &reqwest::Response) -> bool {
+        self.empty_status == Some(response.status())
+    }
+
     pub async fn fetch_all<T: DeserializeOwned>(
         &self,
         base_url: &str,
         per_page: u32,
     ) -> Result<Vec<T>> {
-        let mut all_items = Vec::new();
-        let mut page = 1;
+        self.fetch_all_from(base_url, per_page, 1).await
+    }
+
+    /// Like `fetch_all`, but starts at page `start_page` (1-based) and
+    /// follows the `Link` header from there.
+    pub async fn fetch_all_from<T: DeserializeOwned>(
+        &self,
+        base_url: &str,
+        per_page: u32,
+        start_page: u32,
+    ) -> Result<Vec<T>> {
+        let mut url = first_page_url(base_url, per_page);
+        if start_page > 1 {
+            url = format!("{}&page={}", url, start_page);
+        }
+        match self.fetch_pages(&url, None).await? {
+            Conditional::Modified { data, .. } => Ok(data),
+            Conditional::NotModified => Ok(Vec::new()),
+        }
+    }
+
+    /// Like `fetch_all`, but sends `If-None-Match` with the first page request.
+    /// A 304 on the first page short-circuits to `Conditional::NotModified`;
+    /// otherwise the first page's `ETag` is returned alongside all items.
+    pub async fn fetch_all_conditional<T: DeserializeOwned>(
+        &self,
+        base_url: &str,
+        per_page: u32,
+        etag: Option<&str>,
+    ) -> Result<Conditional<Vec<T>>> {
+        self.fetch_pages(&first_page_url(base_url, per_page), etag).await
+    }
+
+    /// Fetches `first_url` and every page after it. With checkpoints, an
+    /// earlier attempt at the same listing that failed part way is resumed
+    /// from its next page; the first page's `If-None-Match` is skipped then,
+    /// since that page was already found to be modified.
+    async fn fetch_pages<T: DeserializeOwned>(
+        &self,
+        first_url: &str,
+        etag: Option<&str>,
+    ) -> Result<Conditional<Vec<T>>> {
+        let resumed = self.checkpoints.and_then(|c| c.get(first_url));
+        let (mut url, mut first_etag, mut all_items, mut first_page) = match resumed {
+            Some(checkpoint) => {
+                tracing::info!(
+                    "Resuming {} after {} pages ({} items)",
+                    first_url,
+                    checkpoint.pages,
+                    checkpoint.items.len()
+                );
+                (checkpoint.next_url, checkpoint.etag, checkpoint.items, false)
+            }
+            None => (first_url.to_string(), None, Vec::new(), true),
+        };
 
         loop {
             self.rate_limiter.wait().await;
 
-            let separator = if base_url.contains('?') { "&" } else { "?" };
-            let url = format!("{}{}per_page={}&page={}", base_url, separator, per_page, page);
-
             tracing::debug!("Fetching: {}", url);
-            let response = self.client.get(&url).send().await?;
+            let mut request = self.client.get(&url);
+            if first_page {
+                if let Some(etag) = etag {
+                    request = request.header(header::IF_NONE_MATCH, etag);
+                }
+            }
+            let response = request.send().await?;
             self.rate_limiter.update_from_response(&response);
 
-            // Check for next page in Link header
-            let has_next = response
-                .headers()
-                .get("link")
-                .and_then(|v| v.to_str().ok())
-                .map(|v| v.contains("rel=\"next\""))
-                .unwrap_or(false);
+            if first_page {
+                if response.status() == StatusCode::NOT_MODIFIED {
+                    return Ok(Conditional::NotModified);
+                }
+                first_etag = response
+                    .headers()
+                    .get(header::ETAG)
+                    .and_then(|v| v.to_str().ok())
+                    .map(|v| v.to_string());
+                first_page = false;
+            }
 
-            let items: Vec<T> = response.json().await?;
-            let items_count = items.len();
-            all_items.extend(items);
+            let response = ensure_success(response).await?;
+            let next = next_link(&response);
+            let items: Vec<serde_json::Value> = response.json().await?;
 
-            if !has_next || items_count < per_page as usize {
-                break;
+            match next {
+                Some(next) => {
+                    if let Some(checkpoints) = self.checkpoints {
+                        checkpoints.record(first_url, &next, first_etag.as_deref(), &items);
+                    }
+                    all_items.extend(items);
+                    url = next;
+                }
+                None => {
+                    all_items.extend(items);
+                    break;
+                }
             }
+        }
 
-            page += 1;
+        if let Some(checkpoints) = self.checkpoints {
+            checkpoints.clear(first_url);
         }
+        let data = all_items
+            .into_iter()
+            .map(serde_json::from_value)
+            .collect::<std::result::Result<_, _>>()?;
+        Ok(Conditional::Modified {
+            data,
+            etag: first_etag,
+        })
+    }
 
-        Ok(all_items)
+    /// Fetches a single page (1-based) of `per_page` items.
+    pub async fn fetch_page<T: DeserializeOwned>(
+        &self,
+        base_url: &str,
+        per_page: u32,
+        page: u32,
+    ) -> Result<Vec<T>> {
+        self.rate_limiter.wait().await;
+
+        let url = format!("{}&page={}", first_page_url(base_url, per_page), page);
+
+        tracing::debug!("Fetching: {}", url);
+        let response = self.client.get(&url).send().await?;
+        self.rate_limiter.update_from_response(&response);
+        if self.is_empty(&response) {
+            return Ok(Vec::new());
+        }
+
+        Ok(ensure_success(response).await?.json().await?)
     }
 
+    /// Fetches pages until `max_items` items are collected or the results
+    /// run out, following each response's `Link: rel="next"` URL as GitHub
+    /// recommends rather than counting pages, so deep histories and cursor
+    /// pagination work too.
     pub async fn fetch_limited<T: DeserializeOwned>(
         &self,
         base_url: &str,
         per_page: u32,
         max_items: u32,
+    ) -> Result<Vec<T>> {
+        self.fetch_matching(base_url, per_page, max_items, |_| true).await
+    }
+
+    /// Like `fetch_limited`, but keeps only items for which `matches` returns
+    /// true, paging on until `max_items` match or the results run out.
+    pub async fn fetch_matching<T: DeserializeOwned>(
+        &self,
+        base_url: &str,
+        per_page: u32,
+        max_items: u32,
+        matches: impl Fn(&T) -> bool,
     ) -> Result<Vec<T>> {
         let mut all_items = Vec::new();
-        let mut page = 1;
+        let mut url = first_page_url(base_url, per_page);
 
         loop {
             self.rate_limiter.wait().await;
 
-            let separator = if base_url.contains('?') { "&" } else { "?" };
-            let url = format!("{}{}per_page={}&page={}", base_url, separator, per_page, page);
-
             tracing::debug!("Fetching: {}", url);
             let response = self.client.get(&url).send().await?;
             self.rate_limiter.update_from_response(&response);
+            if self.is_empty(&response) {
+                break;
+            }
 
-            let has_next = response
-                .headers()
-                .get("link")
-                .and_then(|v| v.to_str().ok())
-                .map(|v| v.contains("rel=\"next\""))
-                .unwrap_or(false);
-
+            let response = ensure_success(response).await?;
+            let next = next_link(&response);
             let items: Vec<T> = response.json().await?;
-            let items_count = items.len();
-            all_items.extend(items);
+            all_items.extend(items.into_iter().filter(|item| matches(item)));
 
-            if all_items.len() >= max_items as usize || !has_next || items_count < per_page as usize
-            {
-                break;
+            match next {
+                Some(next) if all_items.len() < max_items as usize => url = next,
+                _ => break,
             }
-
-            page += 1;
         }
 
         all_items.truncate(max_items as usize);
         Ok(all_items)
     }
 }
+
+/// Fails with the status and body of a response that is not a success,
+/// rather than trying to read an error message as a page of items.
+async fn ensure_success(response: reqwest::Response) -> Result<reqwest::Response> {
+    if response.status().is_success() {
+        return Ok(response);
+    }
+    let status = response.status();
+    let url = response.url().to_string();
+    let body = response.text().await.unwrap_or_default();
+    Err(Error::GitHubApi(format!("Failed to fetch {}: {} - {}", url, status, body)))
+}
+
+/// `base_url` with `per_page` added; later pages come from `next_link`.
+fn first_page_url(base_url: &str, per_page: u32) -> String {
+    let separator = if base_url.contains('?') { "&" } else { "?" };
+    format!("{}{}per_page={}", base_url, separator, per_page)
+}
+
+/// The `rel="next"` URL from the response's `Link` header, if any.
+fn next_link(response: &reqwest::Response) -> Option<String> {
+    let link = response.headers().get(header::LINK)?.to_str().ok()?;
+    next_url_from_link(link)
+}
+
+fn next_url_from_link(link: &str) -> Option<String> {
+    link.split(',')
+        .find(|part| part.contains("rel=\"next\""))
+        .and_then(|part| part.split(';').next())
+        .map(|url| url.trim().trim_start_matches('<').trim_end_matches('>').to_string())
+        .filter(|url| !url.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockResponse, MockServer};
+
+    #[test]
+    fn test_next_url_from_link() {
+        let link = "<https://api.github.com/repositories/1/commits?per_page=2&page=2>; rel=\"next\", \
+                    <https://api.github.com/repositories/1/commits?per_page=2&page=9>; rel=\"last\"";
+        assert_eq!(
+            next_url_from_link(link).as_deref(),
+            Some("https://api.github.com/repositories/1/commits?per_page=2&page=2")
+        );
+        assert_eq!(next_url_from_link("<https://api.github.com/x?page=1>; rel=\"prev\""), None);
+    }
+
+    /// Serves `pages` of JSON in order, linking each to the next with an
+    /// opaque cursor. An empty page closes the connection without answering.
+    async fn serve_link_chain(pages: Vec<&'static str>) -> MockServer {
+        MockServer::start_with(|base_url| {
+            pages
+                .iter()
+                .enumerate()
+                .map(|(i, body)| {
+                    if body.is_empty() {
+                        return MockResponse::hang_up();
+                    }
+                    let response = MockResponse::json(*body);
+                    if i + 1 < pages.len() {
+                        response.header(
+                            "Link",
+                            format!("<{}/commits?per_page=2&after=cursor{}>; rel=\"next\"", base_url, i + 1),
+                        )
+                    } else {
+                        response
+                    }
+                })
+                .collect()
+        })
+        .await
+    }
+
+    fn targets(server: &MockServer) -> Vec<String> {
+        server.requests().into_iter().map(|r| r.target).collect()
+    }
+
+    #[tokio::test]
+    async fn test_follows_link_header_and_truncates() {
+        let server = serve_link_chain(vec!["[1, 2]", "[3, 4]", "[5, 6]"]).await;
+        let client = Client::new();
+        let rate_limiter = RateLimiter::new();
+        let paginator = Paginator::new(&client, &rate_limiter);
+
+        let items: Vec<u32> = paginator
+            .fetch_limited(&format!("{}/commits", server.url()), 2, 3)
+            .await
+            .unwrap();
+        assert_eq!(items, vec![1, 2, 3]);
+        assert_eq!(
+            targets(&server),
+            vec!["/commits?per_page=2", "/commits?per_page=2&after=cursor1"]
+        );
+
+        let server = serve_link_chain(vec!["[1, 2]", "[3]"]).await;
+        let items: Vec<u32> = paginator
+            .fetch_limited(&format!("{}/commits", server.url()), 2, 100)
+            .await
+            .unwrap();
+        assert_eq!(items, vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn test_resumes_from_checkpoint_after_failure() {
+        let server = serve_link_chain(vec!["[1, 2]", "", "[3, 4]"]).await;
+        let client = Client::new();
+        let rate_limiter = RateLimiter::new();
+        let checkpoints = PageCheckpoints::new();
+        let paginator = Paginator::new(&client, &rate_limiter).with_checkpoints(&checkpoints);
+        let url = format!("{}/commits", server.url());
+
+        assert!(paginator.fetch_all::<u32>(&url, 2).await.is_err());
+        let items: Vec<u32> = paginator.fetch_all(&url, 2).await.unwrap();
+        assert_eq!(items, vec![1, 2, 3, 4]);
+        // The first page is not fetched again
+        assert_eq!(
+            targets(&server),
+            vec![
+                "/commits?per_page=2",
+                "/commits?per_page=2&after=cursor1",
+                "/commits?per_page=2&after=cursor1",
+            ]
+        );
+        assert!(checkpoints.get(&format!("{}?per_page=2", url)).is_none());
+
+        let server = serve_link_chain(vec!["[5, 6]"]).await;
+        let items: Vec<u32> = paginator
+            .fetch_all_from(&format!("{}/repos", server.url()), 2, 3)
+            .await
+            .unwrap();
+        assert_eq!(items, vec![5, 6]);
+        assert_eq!(targets(&server), vec!["/repos?per_page=2&page=3"]);
+    }
+
+    #[tokio::test]
+    async fn test_error_status_fails_instead_of_parsing() {
+        let server = MockServer::start(vec![
+            MockResponse::status(StatusCode::FORBIDDEN)
+                .header("Content-Type", "application/json")
+                .body(r#"{"message": "API rate limit exceeded"}"#),
+            MockResponse::status(StatusCode::CONFLICT),
+            MockResponse::status(StatusCode::CONFLICT),
+        ])
+        .await;
+        let client = Client::new();
+        let rate_limiter = RateLimiter::new();
+        let paginator = Paginator::new(&client, &rate_limiter);
+        let url = format!("{}/commits", server.url());
+
+        let error = paginator.fetch_page::<u32>(&url, 2, 1).await.unwrap_err();
+        assert!(matches!(error, Error::GitHubApi(ref message) if message.contains("rate limit exceeded")));
+        // 409 only means "no items" where the caller says so
+        assert!(paginator.fetch_limited::<u32>(&url, 2, 10).await.is_err());
+        let empty = Paginator::new(&client, &rate_limiter)
+            .with_empty_status(StatusCode::CONFLICT)
+            .fetch_limited::<u32>(&url, 2, 10)
+            .await
+            .unwrap();
+        assert!(empty.is_empty());
+    }
+}
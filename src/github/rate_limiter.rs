@@ -3,8 +3,12 @@ use tokio::sync::Mutex;
 use tokio::time::{Duration, sleep};
 use reqwest::Response;
 
+/// Requests per minute `RateLimiter::new` allows, to be polite.
+pub const DEFAULT_REQUESTS_PER_MINUTE: u32 = 30;
+
 pub struct RateLimiter {
     state: Arc<Mutex<RateLimitState>>,
+    requests_per_minute: u32,
 }
 
 struct RateLimitState {
@@ -23,9 +27,16 @@ impl RateLimiter {
                 requests_this_minute: 0,
                 minute_start: std::time::Instant::now(),
             })),
+            requests_per_minute: DEFAULT_REQUESTS_PER_MINUTE,
         }
     }
 
+    /// Caps requests at `requests_per_minute` instead of the default.
+    pub fn with_requests_per_minute(mut self, requests_per_minute: u32) -> Self {
+        self.requests_per_minute = requests_per_minute.max(1);
+        self
+    }
+
     pub async fn wait(&self) {
         let mut state = self.state.lock().await;
 
@@ -43,10 +54,10 @@ impl RateLimiter {
             }
         }
 
-        // Soft rate limiting: max 30 requests per minute to be polite
+        // Soft rate limiting: at most `requests_per_minute` per minute
         let minute_elapsed = state.minute_start.elapsed();
         if minute_elapsed < Duration::from_secs(60) {
-            if state.requests_this_minute >= 30 {
+            if state.requests_this_minute >= self.requests_per_minute {
                 let wait_time = Duration::from_secs(60) - minute_elapsed;
                 drop(state);
                 tracing::debug!("Soft rate limiting, waiting {:?}", wait_time);
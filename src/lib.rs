@@ -1,5 +1,6 @@
 pub mod config;
 pub mod error;
+pub mod format;
 pub mod models;
 pub mod github;
 pub mod llm;
@@ -7,9 +8,12 @@ pub mod taxonomy;
 pub mod analysis;
 pub mod storage;
 
-pub use config::{Config, PipelineConfig};
+#[cfg(test)]
+mod test_support;
+
+pub use config::{Concurrency, Config, FileConfig, PipelineConfig, SecretFiles};
 pub use error::{Error, Result};
 pub use github::GitHubClient;
-pub use llm::{ClaudeProvider, LLMProvider};
+pub use llm::{ClaudeProvider, LLMProvider, ReplayLLMProvider};
 pub use analysis::AnalysisPipeline;
 pub use storage::Storage;
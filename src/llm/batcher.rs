@@ -1,19 +1,62 @@
-use crate::models::commit::{CommitForAnalysis, FileForAnalysis};
+use crate::llm::prompts::{truncate_diff, DEFAULT_MAX_DIFF_CHARS, SYSTEM_PROMPT};
+use crate::models::commit::CommitForAnalysis;
+use crate::taxonomy::is_ci_config;
+
+/// Default cap on commits per batch, so the model can still reason about each one.
+pub const DEFAULT_MAX_COMMITS_PER_BATCH: usize = 25;
+
+/// Tokens taken by the request's framing around the commits: the header
+/// naming the repository and its description, and the closing instruction.
+pub const PROMPT_FRAMING_TOKENS: usize = 500;
+
+/// Tokens each request spends outside the commits: `system_prompt`, at ~4
+/// characters per token, plus the framing.
+pub fn prompt_overhead_tokens(system_prompt: &str) -> usize {
+    system_prompt.len() / 4 + PROMPT_FRAMING_TOKENS
+}
+
+/// Tokens to hold back from each batch for the prompt overhead and the
+/// model's reply. At most half the context, so batches always have room.
+pub fn default_reserved_tokens(
+    max_context_tokens: usize,
+    max_output_tokens: usize,
+    system_prompt: &str,
+) -> usize {
+    (max_output_tokens + prompt_overhead_tokens(system_prompt)).min(max_context_tokens / 2)
+}
 
 pub struct CommitBatcher {
     max_tokens: usize,
     reserved_tokens: usize,
+    max_diff_chars: usize,
+    max_commits_per_batch: usize,
 }
 
 impl CommitBatcher {
-    pub fn new(max_tokens: usize) -> Self {
+    /// Batches fill `max_tokens` minus `reserved_tokens`; see
+    /// `default_reserved_tokens` for a value derived from the provider.
+    pub fn new(max_tokens: usize, reserved_tokens: usize) -> Self {
         Self {
             max_tokens,
-            // Reserve tokens for system prompt (~1000) and response (~3000)
-            reserved_tokens: 4_000,
+            reserved_tokens,
+            max_diff_chars: DEFAULT_MAX_DIFF_CHARS,
+            max_commits_per_batch: DEFAULT_MAX_COMMITS_PER_BATCH,
         }
     }
 
+    pub fn with_max_diff_chars(mut self, max_diff_chars: usize) -> Self {
+        self.max_diff_chars = max_diff_chars;
+        self
+    }
+
+    /// Caps how many commits go into one batch, regardless of token budget.
+    pub fn with_max_commits_per_batch(mut self, max_commits: usize) -> Self {
+        self.max_commits_per_batch = max_commits.max(1);
+        self
+    }
+
+    /// Groups commits into batches bounded by both the token budget and
+    /// `max_commits_per_batch`; a new batch starts when either limit is hit.
     pub fn create_batches(
         &self,
         commits: Vec<CommitForAnalysis>,
@@ -23,7 +66,12 @@ impl CommitBatcher {
         let mut current_batch = Vec::new();
         let mut current_tokens = 0;
 
-        for commit in commits {
+        for mut commit in commits {
+            // Apply the per-file limit up front so estimates match the prompt
+            for file in &mut commit.files_changed {
+                file.diff = truncate_diff(&file.diff, self.max_diff_chars);
+            }
+
             let commit_tokens = self.estimate_commit_tokens(&commit);
 
             // If single commit is too large, truncate its diffs
@@ -37,7 +85,9 @@ impl CommitBatcher {
                 continue;
             }
 
-            if current_tokens + commit_tokens > available_tokens {
+            if current_tokens + commit_tokens > available_tokens
+                || current_batch.len() >= self.max_commits_per_batch
+            {
                 if !current_batch.is_empty() {
                     batches.push(std::mem::take(&mut current_batch));
                 }
@@ -95,10 +145,7 @@ impl CommitBatcher {
                 break;
             }
 
-            if file.diff.len() > available {
-                file.diff = file.diff.chars().take(available).collect();
-                file.diff.push_str("\n... [truncated]");
-            }
+            file.diff = truncate_diff(&file.diff, available);
 
             used_chars += file_overhead + file.diff.len();
             truncated_files.push(file);
@@ -109,6 +156,10 @@ impl CommitBatcher {
     }
 
     fn file_priority(&self, filename: &str) -> u32 {
+        // CI/CD config is short and says more than other YAML
+        if is_ci_config(filename) {
+            return 90;
+        }
         let ext = filename.rsplit('.').next().unwrap_or("");
         match ext.to_lowercase().as_str() {
             // High priority: main code files
@@ -130,7 +181,92 @@ impl CommitBatcher {
 
 impl Default for CommitBatcher {
     fn default() -> Self {
-        // Default to Claude's context window
-        Self::new(200_000)
+        // Default to Claude's context window and reply limit
+        Self::new(200_000, default_reserved_tokens(200_000, 4_096, SYSTEM_PROMPT))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::commit::{CommitStats, FileForAnalysis};
+    use chrono::Utc;
+
+    fn commit_with_diff(sha: &str, diff_chars: usize) -> CommitForAnalysis {
+        CommitForAnalysis {
+            sha: sha.to_string(),
+            repository: "octocat/hello".to_string(),
+            message: "Update".to_string(),
+            stats: CommitStats::default(),
+            files_changed: vec![FileForAnalysis {
+                filename: "src/main.rs".to_string(),
+                language: Some("Rust".to_string()),
+                diff: "x".repeat(diff_chars),
+                additions: 1,
+                deletions: 0,
+            }],
+            binary_files_changed: 0,
+            committed_at: Utc::now(),
+            collaborative: false,
+        }
+    }
+
+    #[test]
+    fn test_batches_capped_by_commit_count() {
+        let commits = (0..12).map(|i| commit_with_diff(&i.to_string(), 10)).collect();
+        let batches = CommitBatcher::default()
+            .with_max_commits_per_batch(5)
+            .create_batches(commits);
+
+        let sizes: Vec<_> = batches.iter().map(|b| b.len()).collect();
+        assert_eq!(sizes, vec![5, 5, 2]);
+    }
+
+    #[test]
+    fn test_batches_capped_by_token_budget() {
+        // ~600 tokens per commit against 1,500 available (5,500 minus 4,000 reserved)
+        let commits = (0..3).map(|i| commit_with_diff(&i.to_string(), 2_000)).collect();
+        let batches = CommitBatcher::new(5_500, 4_000)
+            .with_max_diff_chars(10_000)
+            .with_max_commits_per_batch(10)
+            .create_batches(commits);
+
+        let sizes: Vec<_> = batches.iter().map(|b| b.len()).collect();
+        assert_eq!(sizes, vec![2, 1]);
+    }
+
+    #[test]
+    fn test_reserved_tokens_boundaries() {
+        assert_eq!(default_reserved_tokens(200_000, 4_096, ""), 4_596);
+        assert_eq!(
+            default_reserved_tokens(200_000, 4_096, SYSTEM_PROMPT),
+            4_596 + SYSTEM_PROMPT.len() / 4
+        );
+        // A longer custom prompt takes its extra length out of the batches
+        let long_prompt = SYSTEM_PROMPT.repeat(4);
+        assert_eq!(
+            default_reserved_tokens(200_000, 4_096, &long_prompt),
+            4_596 + long_prompt.len() / 4
+        );
+        // Small context windows keep half for commits
+        assert_eq!(default_reserved_tokens(8_000, 4_096, SYSTEM_PROMPT), 4_000);
+
+        // ~604 tokens per commit
+        let commits: Vec<_> = (0..2).map(|i| commit_with_diff(&i.to_string(), 2_000)).collect();
+        let sizes = |max_tokens| {
+            CommitBatcher::new(max_tokens, 5_000)
+                .with_max_diff_chars(10_000)
+                .create_batches(commits.clone())
+                .iter()
+                .map(|b| b.len())
+                .collect::<Vec<_>>()
+        };
+        // Exactly enough room for both shares a batch; one token less splits
+        assert_eq!(sizes(6_208), vec![2]);
+        assert_eq!(sizes(6_207), vec![1, 1]);
+
+        // Reserving the whole context leaves nothing for diffs
+        let batches = CommitBatcher::new(5_000, 5_000).create_batches(commits);
+        assert!(batches.iter().flatten().all(|c| c.files_changed.is_empty()));
     }
 }
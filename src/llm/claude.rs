@@ -4,14 +4,26 @@ use serde::{Deserialize, Serialize};
 
 use crate::error::{Error, Result};
 use crate::llm::parser::parse_llm_response;
-use crate::llm::prompts::{AnalysisRequest, SYSTEM_PROMPT};
-use crate::llm::provider::LLMProvider;
+use crate::llm::prompts::{missing_schema_fields, AnalysisRequest, JSON_REPROMPT, SYSTEM_PROMPT};
+use crate::llm::provider::{LLMProvider, TokenPricing, TokenUsage};
+use crate::llm::replay::recording_path;
 use crate::models::analysis::LLMAnalysisResult;
 
+/// Default Anthropic API root, used unless `with_base_url` points elsewhere.
+pub const DEFAULT_ANTHROPIC_BASE_URL: &str = "https://api.anthropic.com";
+
+/// Most tokens requested for a single reply.
+const MAX_OUTPUT_TOKENS: u32 = 4096;
+
 pub struct ClaudeProvider {
     client: Client,
+    base_url: String,
     api_key: String,
     model: String,
+    system_prompt: String,
+    usage: std::sync::Mutex<TokenUsage>,
+    /// Directory each final reply is saved to, named by prompt hash
+    recording_dir: Option<std::path::PathBuf>,
 }
 
 #[derive(Serialize)]
@@ -23,7 +35,7 @@ struct ClaudeRequest {
     system: Option<String>,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 struct ClaudeMessage {
     role: String,
     content: String,
@@ -34,6 +46,14 @@ struct ClaudeResponse {
     content: Vec<ContentBlock>,
     #[serde(default)]
     error: Option<ClaudeError>,
+    #[serde(default)]
+    usage: Option<ClaudeUsage>,
+}
+
+#[derive(Deserialize)]
+struct ClaudeUsage {
+    input_tokens: u64,
+    output_tokens: u64,
 }
 
 #[derive(Deserialize)]
@@ -57,31 +77,65 @@ impl ClaudeProvider {
 
         Self {
             client,
+            base_url: DEFAULT_ANTHROPIC_BASE_URL.to_string(),
             api_key,
             model: model.unwrap_or_else(|| "claude-sonnet-4-20250514".to_string()),
+            system_prompt: SYSTEM_PROMPT.to_string(),
+            usage: std::sync::Mutex::new(TokenUsage::default()),
+            recording_dir: None,
         }
     }
-}
 
-#[async_trait]
-impl LLMProvider for ClaudeProvider {
-    async fn analyze_commits(&self, request: AnalysisRequest) -> Result<LLMAnalysisResult> {
-        let prompt = request.to_prompt();
-        tracing::debug!("Sending {} tokens to Claude", request.estimate_tokens());
+    /// Points the provider at a different API root, e.g. an internal gateway
+    /// that forwards to Anthropic (`https://llm-gateway.example.com/anthropic`).
+    /// Requests go to `<base_url>/v1/messages` with the usual headers.
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Replaces the built-in system prompt. Warns if the custom prompt does not
+    /// mention every field of the JSON schema the response parser expects.
+    pub fn with_system_prompt(mut self, system_prompt: String) -> Self {
+        let missing = missing_schema_fields(&system_prompt);
+        if !missing.is_empty() {
+            tracing::warn!(
+                "Custom system prompt does not mention required JSON fields: {}",
+                missing.join(", ")
+            );
+        }
+        self.system_prompt = system_prompt;
+        self
+    }
+
+    /// Saves every final reply to `dir` under the hash of its prompt, for
+    /// `ReplayLLMProvider` to answer the same requests later. Unparseable
+    /// replies are saved too, so a replay fails the same way.
+    pub fn with_recording(mut self, dir: impl Into<std::path::PathBuf>) -> Self {
+        self.recording_dir = Some(dir.into());
+        self
+    }
 
+    fn record(&self, request: &AnalysisRequest, text: &str) -> Result<()> {
+        if let Some(ref dir) = self.recording_dir {
+            std::fs::create_dir_all(dir)?;
+            std::fs::write(recording_path(dir, request), text)?;
+        }
+        Ok(())
+    }
+
+    /// Sends the conversation so far and returns the text of Claude's reply.
+    async fn send(&self, messages: &[ClaudeMessage]) -> Result<String> {
         let request_body = ClaudeRequest {
             model: self.model.clone(),
-            max_tokens: 4096,
-            system: Some(SYSTEM_PROMPT.to_string()),
-            messages: vec![ClaudeMessage {
-                role: "user".to_string(),
-                content: prompt,
-            }],
+            max_tokens: MAX_OUTPUT_TOKENS,
+            system: Some(self.system_prompt.clone()),
+            messages: messages.to_vec(),
         };
 
         let response = self
             .client
-            .post("https://api.anthropic.com/v1/messages")
+            .post(format!("{}/v1/messages", self.base_url))
             .header("x-api-key", &self.api_key)
             .header("anthropic-version", "2023-06-01")
             .header("content-type", "application/json")
@@ -107,6 +161,11 @@ impl LLMProvider for ClaudeProvider {
         if let Some(error) = result.error {
             return Err(Error::LLMApi(error.message));
         }
+        if let Some(usage) = result.usage {
+            let mut total = self.usage.lock().unwrap();
+            total.input_tokens += usage.input_tokens;
+            total.output_tokens += usage.output_tokens;
+        }
 
         let text = result
             .content
@@ -120,7 +179,43 @@ impl LLMProvider for ClaudeProvider {
             return Err(Error::LLMApi("Empty response from Claude".to_string()));
         }
 
-        parse_llm_response(&text)
+        Ok(text)
+    }
+}
+
+#[async_trait]
+impl LLMProvider for ClaudeProvider {
+    /// Asks once more, with the unparseable reply and `JSON_REPROMPT` as
+    /// follow-up turns, when the first response is not valid JSON. The second
+    /// response is final.
+    async fn analyze_commits(&self, request: AnalysisRequest) -> Result<LLMAnalysisResult> {
+        tracing::debug!("Sending {} tokens to Claude", request.estimate_tokens());
+        let mut messages = vec![ClaudeMessage {
+            role: "user".to_string(),
+            content: request.to_prompt(),
+        }];
+
+        let text = self.send(&messages).await?;
+        match parse_llm_response(&text) {
+            Err(Error::ParseError(e)) => {
+                tracing::warn!("Claude returned unparseable output ({}); asking again for JSON only", e);
+                messages.push(ClaudeMessage {
+                    role: "assistant".to_string(),
+                    content: text,
+                });
+                messages.push(ClaudeMessage {
+                    role: "user".to_string(),
+                    content: JSON_REPROMPT.to_string(),
+                });
+                let text = self.send(&messages).await?;
+                self.record(&request, &text)?;
+                parse_llm_response(&text)
+            }
+            result => {
+                self.record(&request, &text)?;
+                result
+            }
+        }
     }
 
     fn max_context_tokens(&self) -> usize {
@@ -130,4 +225,162 @@ impl LLMProvider for ClaudeProvider {
     fn name(&self) -> &str {
         "Claude"
     }
+
+    fn max_output_tokens(&self) -> usize {
+        MAX_OUTPUT_TOKENS as usize
+    }
+
+    fn system_prompt(&self) -> &str {
+        &self.system_prompt
+    }
+
+    fn usage(&self) -> TokenUsage {
+        *self.usage.lock().unwrap()
+    }
+
+    fn pricing(&self) -> Option<TokenPricing> {
+        model_pricing(&self.model)
+    }
+}
+
+/// Anthropic's list prices for a model family, matched by name, or `None` for
+/// models this table does not know.
+pub fn model_pricing(model: &str) -> Option<TokenPricing> {
+    let (input_per_million, output_per_million) = if model.contains("opus") {
+        (15.0, 75.0)
+    } else if model.contains("sonnet") {
+        (3.0, 15.0)
+    } else if model.contains("haiku") {
+        (0.8, 4.0)
+    } else {
+        return None;
+    };
+    Some(TokenPricing {
+        input_per_million,
+        output_per_million,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::prompts::AnalysisContext;
+    use crate::test_support::{MockResponse, MockServer};
+
+    #[tokio::test]
+    async fn test_requests_go_to_custom_base_url() {
+        let server =
+            MockServer::start(vec![MockResponse::status(reqwest::StatusCode::SERVICE_UNAVAILABLE)]).await;
+
+        let provider = ClaudeProvider::new("sk-test".to_string(), None)
+            .with_base_url(format!("{}/anthropic/", server.url()));
+        let request = AnalysisRequest::new(Vec::new(), AnalysisContext::default());
+        assert!(provider.analyze_commits(request).await.is_err());
+
+        let requests = server.finish().await;
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].target, "/anthropic/v1/messages");
+        assert_eq!(requests[0].header("x-api-key"), Some("sk-test"));
+        assert_eq!(requests[0].header("anthropic-version"), Some("2023-06-01"));
+    }
+
+    #[test]
+    fn test_custom_system_prompt_is_reserved_in_batches() {
+        use crate::llm::default_reserved_tokens;
+
+        let provider = ClaudeProvider::new("sk-test".to_string(), None);
+        let default_reserved =
+            default_reserved_tokens(200_000, provider.max_output_tokens(), provider.system_prompt());
+
+        let custom = format!("{}\n{}", SYSTEM_PROMPT, "Weigh test coverage heavily. ".repeat(400));
+        let provider = provider.with_system_prompt(custom.clone());
+        assert_eq!(provider.system_prompt(), custom);
+        let custom_reserved =
+            default_reserved_tokens(200_000, provider.max_output_tokens(), provider.system_prompt());
+        assert_eq!(custom_reserved - default_reserved, (custom.len() - SYSTEM_PROMPT.len()) / 4);
+    }
+
+    /// Answers one request per reply with a Claude message holding that
+    /// reply's text, billed as 1000 input and 200 output tokens.
+    async fn serve_replies(replies: Vec<&'static str>) -> MockServer {
+        MockServer::start(
+            replies
+                .into_iter()
+                .map(|reply| {
+                    MockResponse::json(
+                        serde_json::json!({
+                            "content": [{ "type": "text", "text": reply }],
+                            "usage": { "input_tokens": 1000, "output_tokens": 200 },
+                        })
+                        .to_string(),
+                    )
+                })
+                .collect(),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_reprompts_once_after_unparseable_reply() {
+        let server = serve_replies(vec![
+            "These commits show solid Rust skills.",
+            r#"{"skills": [], "patterns": [], "complexity_assessment": {"overall_score": 6, "algorithmic_complexity": 5, "architectural_complexity": 5, "reasoning": ""}, "quality_assessment": {"code_quality": 7, "testing_coverage": 0.5, "documentation_quality": 6, "error_handling": 6, "observations": []}, "domain_signals": ["backend"], "notable_aspects": []}"#,
+        ])
+        .await;
+
+        let provider = ClaudeProvider::new("sk-test".to_string(), None).with_base_url(server.url());
+        let request = AnalysisRequest::new(Vec::new(), AnalysisContext::default());
+        let result = provider.analyze_commits(request).await.unwrap();
+        assert_eq!(result.complexity_assessment.overall_score, 6);
+        assert_eq!(result.domain_signals, vec!["backend"]);
+
+        let requests = server.finish().await;
+        let retry: serde_json::Value = serde_json::from_str(&requests[1].body).unwrap();
+        let messages = retry["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[1]["role"], "assistant");
+        assert_eq!(messages[1]["content"], "These commits show solid Rust skills.");
+        assert_eq!(messages[2]["content"], JSON_REPROMPT);
+
+        // Both requests are billed
+        let usage = provider.usage();
+        assert_eq!((usage.input_tokens, usage.output_tokens), (2000, 400));
+        let cost = provider.pricing().unwrap().cost(usage);
+        assert!((cost - 0.012).abs() < 1e-9);
+        assert_eq!(model_pricing("gpt-4o"), None);
+    }
+
+    #[tokio::test]
+    async fn test_recorded_replies_replay_without_network() {
+        use crate::llm::ReplayLLMProvider;
+
+        let server = serve_replies(vec![
+            r#"{"skills": [], "patterns": [], "complexity_assessment": {"overall_score": 4, "algorithmic_complexity": 5, "architectural_complexity": 5, "reasoning": ""}, "quality_assessment": {"code_quality": 7, "testing_coverage": 0.5, "documentation_quality": 6, "error_handling": 6, "observations": []}, "domain_signals": ["cli"], "notable_aspects": []}"#,
+        ])
+        .await;
+        let dir = std::env::temp_dir().join(format!("gitanalyzer-recording-{}", std::process::id()));
+        let request = || AnalysisRequest::new(Vec::new(), AnalysisContext::default());
+
+        let recorder = ClaudeProvider::new("sk-test".to_string(), None)
+            .with_base_url(server.url())
+            .with_recording(&dir);
+        let live = recorder.analyze_commits(request()).await.unwrap();
+        server.finish().await;
+
+        let replayed = ReplayLLMProvider::new(&dir).analyze_commits(request()).await.unwrap();
+        assert_eq!(replayed.complexity_assessment.overall_score, live.complexity_assessment.overall_score);
+        assert_eq!(replayed.domain_signals, vec!["cli"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_one_reprompt() {
+        let server = serve_replies(vec!["No JSON here.", "Still no JSON."]).await;
+
+        let provider = ClaudeProvider::new("sk-test".to_string(), None).with_base_url(server.url());
+        let request = AnalysisRequest::new(Vec::new(), AnalysisContext::default());
+        let result = provider.analyze_commits(request).await;
+        assert!(matches!(result, Err(Error::ParseError(_))));
+        assert_eq!(server.finish().await.len(), 2);
+    }
 }
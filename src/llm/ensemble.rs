@@ -0,0 +1,297 @@
+use async_trait::async_trait;
+
+use crate::error::Result;
+use crate::llm::prompts::AnalysisRequest;
+use crate::llm::provider::{LLMProvider, TokenPricing, TokenUsage};
+use crate::models::analysis::{
+    ComplexityAssessment, DetectedPattern, ExtractedSkill, LLMAnalysisResult, QualityAssessment,
+};
+
+/// Confidence multiplier for skills both providers report.
+const AGREEMENT_BOOST: f32 = 1.2;
+
+/// Confidence multiplier for skills only one provider reports.
+const DISAGREEMENT_PENALTY: f32 = 0.7;
+
+/// Patterns carry no confidence of their own, so one counts as confidently
+/// detected when its `quality_impact` is at least this far from neutral, the
+/// same cut-off the rating engine uses for strengths and improvement areas.
+const MIN_PATTERN_IMPACT: f32 = 0.3;
+
+/// Runs every request through two providers and reconciles their answers
+/// with `merge_results`. Each request costs what both providers charge.
+pub struct EnsembleProvider {
+    first: Box<dyn LLMProvider>,
+    second: Box<dyn LLMProvider>,
+    name: String,
+}
+
+impl EnsembleProvider {
+    pub fn new(first: impl LLMProvider + 'static, second: impl LLMProvider + 'static) -> Self {
+        let name = format!("{}+{}", first.name(), second.name());
+        Self {
+            first: Box::new(first),
+            second: Box::new(second),
+            name,
+        }
+    }
+}
+
+#[async_trait]
+impl LLMProvider for EnsembleProvider {
+    /// Sends the request to both providers at once. When only one succeeds
+    /// its result is used unchanged, with a warning.
+    async fn analyze_commits(&self, request: AnalysisRequest) -> Result<LLMAnalysisResult> {
+        let (first, second) = futures::join!(
+            self.first.analyze_commits(request.clone()),
+            self.second.analyze_commits(request)
+        );
+        match (first, second) {
+            (Ok(a), Ok(b)) => Ok(merge_results(a, b)),
+            (Ok(result), Err(e)) | (Err(e), Ok(result)) => {
+                tracing::warn!("One ensemble provider failed, using the other's analysis: {}", e);
+                Ok(result)
+            }
+            (Err(e), Err(_)) => Err(e),
+        }
+    }
+
+    /// The smaller of the two, so every batch fits both
+    fn max_context_tokens(&self) -> usize {
+        self.first.max_context_tokens().min(self.second.max_context_tokens())
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn max_output_tokens(&self) -> usize {
+        self.first.max_output_tokens().max(self.second.max_output_tokens())
+    }
+
+    /// The longer of the two, so every batch fits both
+    fn system_prompt(&self) -> &str {
+        let (a, b) = (self.first.system_prompt(), self.second.system_prompt());
+        if a.len() >= b.len() {
+            a
+        } else {
+            b
+        }
+    }
+
+    /// The larger of the two providers' counts, per kind. Priced with
+    /// `pricing`, which adds both providers' prices, this overstates the
+    /// spend slightly, which errs on the safe side for a cost cap.
+    fn usage(&self) -> TokenUsage {
+        let (a, b) = (self.first.usage(), self.second.usage());
+        TokenUsage {
+            input_tokens: a.input_tokens.max(b.input_tokens),
+            output_tokens: a.output_tokens.max(b.output_tokens),
+        }
+    }
+
+    fn pricing(&self) -> Option<TokenPricing> {
+        let (a, b) = (self.first.pricing()?, self.second.pricing()?);
+        Some(TokenPricing {
+            input_per_million: a.input_per_million + b.input_per_million,
+            output_per_million: a.output_per_million + b.output_per_million,
+        })
+    }
+}
+
+/// Reconciles two analyses of the same commits. Skills are unioned by name:
+/// ones both report get their confidences averaged and boosted, ones only
+/// one reports are kept with lowered confidence. Only patterns both report
+/// with an impact of at least `MIN_PATTERN_IMPACT` are kept. Assessment scores are averaged; lists are unioned.
+pub fn merge_results(a: LLMAnalysisResult, b: LLMAnalysisResult) -> LLMAnalysisResult {
+    let mut skills = Vec::new();
+    let mut b_skills = b.skills;
+    for skill in a.skills {
+        match b_skills
+            .iter()
+            .position(|other| other.name.eq_ignore_ascii_case(&skill.name))
+        {
+            Some(i) => skills.push(merge_skill(skill, b_skills.remove(i))),
+            None => skills.push(penalize(skill)),
+        }
+    }
+    skills.extend(b_skills.into_iter().map(penalize));
+
+    let confident = |pattern: &DetectedPattern| pattern.quality_impact.abs() >= MIN_PATTERN_IMPACT;
+    let patterns = a
+        .patterns
+        .into_iter()
+        .filter(confident)
+        .filter_map(|pattern| {
+            let other = b
+                .patterns
+                .iter()
+                .filter(|other| confident(other))
+                .find(|other| other.name.eq_ignore_ascii_case(&pattern.name))?;
+            Some(DetectedPattern {
+                quality_impact: (pattern.quality_impact + other.quality_impact) / 2.0,
+                ..pattern
+            })
+        })
+        .collect();
+
+    let average = |x: u8, y: u8| ((x as u16 + y as u16) / 2) as u8;
+    let complexity_assessment = ComplexityAssessment {
+        overall_score: average(
+            a.complexity_assessment.overall_score,
+            b.complexity_assessment.overall_score,
+        ),
+        algorithmic_complexity: average(
+            a.complexity_assessment.algorithmic_complexity,
+            b.complexity_assessment.algorithmic_complexity,
+        ),
+        architectural_complexity: average(
+            a.complexity_assessment.architectural_complexity,
+            b.complexity_assessment.architectural_complexity,
+        ),
+        reasoning: a.complexity_assessment.reasoning,
+    };
+    let quality_assessment = QualityAssessment {
+        code_quality: average(a.quality_assessment.code_quality, b.quality_assessment.code_quality),
+        testing_coverage: (a.quality_assessment.testing_coverage
+            + b.quality_assessment.testing_coverage)
+            / 2.0,
+        documentation_quality: average(
+            a.quality_assessment.documentation_quality,
+            b.quality_assessment.documentation_quality,
+        ),
+        error_handling: average(
+            a.quality_assessment.error_handling,
+            b.quality_assessment.error_handling,
+        ),
+        observations: union(a.quality_assessment.observations, b.quality_assessment.observations),
+    };
+
+    LLMAnalysisResult {
+        skills,
+        patterns,
+        complexity_assessment,
+        quality_assessment,
+        domain_signals: union(a.domain_signals, b.domain_signals),
+        notable_aspects: union(a.notable_aspects, b.notable_aspects),
+    }
+}
+
+/// Combines two reports of the same skill, keeping the proficiency level of
+/// the more confident one.
+fn merge_skill(a: ExtractedSkill, b: ExtractedSkill) -> ExtractedSkill {
+    let confidence = ((a.confidence + b.confidence) / 2.0 * AGREEMENT_BOOST).min(1.0);
+    let (primary, other) = if b.confidence > a.confidence { (b, a) } else { (a, b) };
+    ExtractedSkill {
+        confidence,
+        evidence: union(primary.evidence, other.evidence),
+        ..primary
+    }
+}
+
+fn penalize(skill: ExtractedSkill) -> ExtractedSkill {
+    ExtractedSkill {
+        confidence: skill.confidence * DISAGREEMENT_PENALTY,
+        ..skill
+    }
+}
+
+/// `a` followed by the entries of `b` it lacks.
+fn union(mut a: Vec<String>, b: Vec<String>) -> Vec<String> {
+    for item in b {
+        if !a.contains(&item) {
+            a.push(item);
+        }
+    }
+    a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn skill(name: &str, level: &str, confidence: f32) -> ExtractedSkill {
+        ExtractedSkill {
+            name: name.to_string(),
+            category: "language".to_string(),
+            proficiency_level: level.to_string(),
+            confidence,
+            evidence: vec![format!("{} evidence", name)],
+        }
+    }
+
+    fn pattern(name: &str, quality_impact: f32) -> DetectedPattern {
+        DetectedPattern {
+            pattern_type: "design".to_string(),
+            name: name.to_string(),
+            description: String::new(),
+            quality_impact,
+        }
+    }
+
+    fn result(skills: Vec<ExtractedSkill>, patterns: Vec<DetectedPattern>, score: u8) -> LLMAnalysisResult {
+        LLMAnalysisResult {
+            skills,
+            patterns,
+            complexity_assessment: ComplexityAssessment {
+                overall_score: score,
+                ..Default::default()
+            },
+            quality_assessment: QualityAssessment::default(),
+            domain_signals: vec!["backend".to_string()],
+            notable_aspects: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_merge_results_reconciles_two_analyses() {
+        let a = result(
+            vec![skill("Rust", "advanced", 0.6), skill("Go", "beginner", 0.5)],
+            vec![pattern("Builder", 0.4), pattern("Singleton", -0.2)],
+            6,
+        );
+        let b = result(
+            vec![skill("rust", "expert", 0.8), skill("SQL", "intermediate", 1.0)],
+            vec![pattern("builder", 0.8)],
+            9,
+        );
+
+        let merged = merge_results(a, b);
+        let names: Vec<_> = merged.skills.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["rust", "Go", "SQL"]);
+
+        // Agreement: averaged and boosted, level from the more confident side
+        let rust = &merged.skills[0];
+        assert!((rust.confidence - 0.84).abs() < 1e-6);
+        assert_eq!(rust.proficiency_level, "expert");
+        assert_eq!(rust.evidence, vec!["rust evidence", "Rust evidence"]);
+        // Disagreement: kept, but less trusted
+        assert!((merged.skills[1].confidence - 0.35).abs() < 1e-6);
+        assert!((merged.skills[2].confidence - 0.7).abs() < 1e-6);
+
+        assert_eq!(merged.patterns.len(), 1);
+        assert!((merged.patterns[0].quality_impact - 0.6).abs() < 1e-6);
+        assert_eq!(merged.complexity_assessment.overall_score, 7);
+        assert_eq!(merged.domain_signals, vec!["backend"]);
+    }
+
+    #[test]
+    fn test_merge_results_drops_weak_patterns() {
+        let a = result(
+            Vec::new(),
+            vec![pattern("Logging", 0.1), pattern("Retries", 0.5), pattern("Globals", -0.6)],
+            5,
+        );
+        let b = result(
+            Vec::new(),
+            vec![pattern("logging", 0.2), pattern("retries", 0.1), pattern("globals", -0.4)],
+            5,
+        );
+
+        // Both report Logging, but neither with confidence; Retries is weak on one side
+        let merged = merge_results(a, b);
+        let names: Vec<_> = merged.patterns.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["Globals"]);
+        assert!((merged.patterns[0].quality_impact + 0.5).abs() < 1e-6);
+    }
+}
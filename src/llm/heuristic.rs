@@ -0,0 +1,110 @@
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+
+use crate::error::Result;
+use crate::llm::prompts::AnalysisRequest;
+use crate::llm::provider::LLMProvider;
+use crate::models::analysis::{
+    ComplexityAssessment, ExtractedSkill, LLMAnalysisResult, QualityAssessment,
+};
+
+/// Confidence given to a language seen in a batch's changed files: the
+/// file extension is certain, but says nothing about how well it is used.
+const LANGUAGE_CONFIDENCE: f32 = 0.5;
+
+/// Answers requests without a model (`--no-llm`): every language among a
+/// batch's changed files becomes a skill with no proficiency signal, and
+/// complexity and quality get their neutral defaults. Needs no API key.
+#[derive(Debug, Default)]
+pub struct HeuristicProvider;
+
+impl HeuristicProvider {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl LLMProvider for HeuristicProvider {
+    async fn analyze_commits(&self, request: AnalysisRequest) -> Result<LLMAnalysisResult> {
+        let mut files_by_language: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for file in request.commits.iter().flat_map(|c| &c.files_changed) {
+            if let Some(language) = file.language.as_deref() {
+                files_by_language.entry(language).or_default().push(&file.filename);
+            }
+        }
+
+        let skills = files_by_language
+            .into_iter()
+            .map(|(language, files)| ExtractedSkill {
+                name: language.to_string(),
+                category: "language".to_string(),
+                // Not a rated level, so it scores as an unknown signal
+                proficiency_level: "unknown".to_string(),
+                confidence: LANGUAGE_CONFIDENCE,
+                evidence: files.iter().take(3).map(|f| format!("Changed {}", f)).collect(),
+            })
+            .collect();
+
+        Ok(LLMAnalysisResult {
+            skills,
+            patterns: Vec::new(),
+            complexity_assessment: ComplexityAssessment::default(),
+            quality_assessment: QualityAssessment::default(),
+            domain_signals: Vec::new(),
+            notable_aspects: Vec::new(),
+        })
+    }
+
+    fn max_context_tokens(&self) -> usize {
+        200_000
+    }
+
+    fn name(&self) -> &str {
+        "Heuristic"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::prompts::AnalysisContext;
+    use crate::models::commit::{CommitForAnalysis, CommitStats, FileForAnalysis};
+
+    fn file(filename: &str, language: Option<&str>) -> FileForAnalysis {
+        FileForAnalysis {
+            filename: filename.to_string(),
+            language: language.map(str::to_string),
+            diff: "+x".to_string(),
+            additions: 1,
+            deletions: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_languages_of_changed_files_become_skills() {
+        let commit = CommitForAnalysis {
+            sha: "abc1234".to_string(),
+            repository: "octocat/hello".to_string(),
+            message: "Add parser".to_string(),
+            stats: CommitStats { additions: 3, deletions: 0, total: 3 },
+            files_changed: vec![
+                file("src/main.rs", Some("Rust")),
+                file("src/parser.rs", Some("Rust")),
+                file("build.py", Some("Python")),
+                file("LICENSE", None),
+            ],
+            binary_files_changed: 0,
+            committed_at: chrono::Utc::now(),
+            collaborative: false,
+        };
+        let request = AnalysisRequest::new(vec![commit], AnalysisContext::default());
+
+        let result = HeuristicProvider::new().analyze_commits(request).await.unwrap();
+        let names: Vec<_> = result.skills.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["Python", "Rust"]);
+        assert_eq!(result.skills[1].evidence, vec!["Changed src/main.rs", "Changed src/parser.rs"]);
+        assert!(result.skills.iter().all(|s| s.proficiency_level == "unknown"));
+        assert!(result.patterns.is_empty());
+    }
+}
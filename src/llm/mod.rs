@@ -3,8 +3,16 @@ pub mod claude;
 pub mod prompts;
 pub mod parser;
 pub mod batcher;
+pub mod ensemble;
+pub mod heuristic;
+pub mod replay;
 
-pub use provider::LLMProvider;
-pub use claude::ClaudeProvider;
-pub use prompts::{AnalysisRequest, AnalysisContext};
-pub use batcher::CommitBatcher;
+pub use provider::{LLMProvider, TokenPricing, TokenUsage};
+pub use claude::{model_pricing, ClaudeProvider, DEFAULT_ANTHROPIC_BASE_URL};
+pub use prompts::{AnalysisRequest, AnalysisContext, DEFAULT_MAX_DIFF_CHARS};
+pub use batcher::{
+    default_reserved_tokens, prompt_overhead_tokens, CommitBatcher, DEFAULT_MAX_COMMITS_PER_BATCH,
+};
+pub use ensemble::{merge_results, EnsembleProvider};
+pub use heuristic::HeuristicProvider;
+pub use replay::ReplayLLMProvider;
@@ -46,10 +46,48 @@ Guidelines:
 - Consider code complexity, patterns, and best practices when assessing proficiency
 - Domain signals help categorize what type of development this is"#;
 
+/// Follow-up sent once, after the model's own reply, when that reply could
+/// not be parsed.
+pub const JSON_REPROMPT: &str =
+    "Return ONLY valid JSON matching the schema, no prose.";
+
+/// Top-level keys the response parser requires in the LLM's JSON output.
+pub const REQUIRED_SCHEMA_FIELDS: &[&str] = &[
+    "skills",
+    "patterns",
+    "complexity_assessment",
+    "quality_assessment",
+    "domain_signals",
+    "notable_aspects",
+];
+
+/// Returns the required schema keys a custom system prompt never mentions.
+/// A non-empty result means the LLM is unlikely to produce parseable output.
+pub fn missing_schema_fields(system_prompt: &str) -> Vec<&'static str> {
+    REQUIRED_SCHEMA_FIELDS
+        .iter()
+        .copied()
+        .filter(|field| !system_prompt.contains(field))
+        .collect()
+}
+
+/// Default per-file diff size, in characters, sent to the LLM.
+pub const DEFAULT_MAX_DIFF_CHARS: usize = 3000;
+
+/// Truncates a diff to at most `max_chars` characters, appending a marker
+/// when anything was cut. Always splits on a char boundary.
+pub fn truncate_diff(diff: &str, max_chars: usize) -> String {
+    match diff.char_indices().nth(max_chars) {
+        Some((idx, _)) => format!("{}\n... [truncated]", &diff[..idx]),
+        None => diff.to_string(),
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct AnalysisRequest {
     pub commits: Vec<CommitForAnalysis>,
     pub context: AnalysisContext,
+    pub max_diff_chars: usize,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -61,7 +99,16 @@ pub struct AnalysisContext {
 
 impl AnalysisRequest {
     pub fn new(commits: Vec<CommitForAnalysis>, context: AnalysisContext) -> Self {
-        Self { commits, context }
+        Self {
+            commits,
+            context,
+            max_diff_chars: DEFAULT_MAX_DIFF_CHARS,
+        }
+    }
+
+    pub fn with_max_diff_chars(mut self, max_diff_chars: usize) -> Self {
+        self.max_diff_chars = max_diff_chars;
+        self
     }
 
     pub fn to_prompt(&self) -> String {
@@ -82,23 +129,24 @@ impl AnalysisRequest {
             prompt.push_str(&format!("## Commit: {}\n", &commit.sha[..8.min(commit.sha.len())]));
             prompt.push_str(&format!("Message: {}\n", commit.message.lines().next().unwrap_or("")));
             prompt.push_str(&format!(
-                "Stats: +{} -{}\n\n",
+                "Stats: +{} -{}\n",
                 commit.stats.additions, commit.stats.deletions
             ));
+            if commit.binary_files_changed > 0 {
+                prompt.push_str(&format!(
+                    "Binary files changed: {}\n",
+                    commit.binary_files_changed
+                ));
+            }
+            prompt.push('\n');
 
             for file in &commit.files_changed {
                 prompt.push_str(&format!("### File: {}", file.filename));
-                if let Some(lang) = &file.language {
-                    prompt.push_str(&format!(" ({})", lang));
-                }
+                let lang = file.language.as_deref().unwrap_or("Unknown language");
+                prompt.push_str(&format!(" ({})", lang));
                 prompt.push_str("\n```\n");
                 // Limit diff size per file to avoid huge prompts
-                let diff = if file.diff.len() > 3000 {
-                    format!("{}...\n[truncated]", &file.diff[..3000])
-                } else {
-                    file.diff.clone()
-                };
-                prompt.push_str(&diff);
+                prompt.push_str(&truncate_diff(&file.diff, self.max_diff_chars));
                 prompt.push_str("\n```\n\n");
             }
         }
@@ -123,3 +171,58 @@ impl AnalysisRequest {
         char_count / 4
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::commit::{CommitStats, FileForAnalysis};
+
+    #[test]
+    fn test_builtin_prompt_has_schema_fields() {
+        assert!(missing_schema_fields(SYSTEM_PROMPT).is_empty());
+        assert_eq!(
+            missing_schema_fields("Find security issues. Reply with \"skills\" and \"patterns\"."),
+            vec![
+                "complexity_assessment",
+                "quality_assessment",
+                "domain_signals",
+                "notable_aspects"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_truncate_diff_multibyte_boundary() {
+        assert_eq!(truncate_diff("a😀b", 2), "a😀\n... [truncated]");
+        assert_eq!(truncate_diff("日本語", 3), "日本語");
+        assert_eq!(truncate_diff("日本語", 1), "日\n... [truncated]");
+    }
+
+    #[test]
+    fn test_to_prompt_does_not_panic_on_multibyte_diff() {
+        // Byte 3000 falls inside a 3-byte CJK character
+        let diff = format!("a{}", "日".repeat(2000));
+        let commit = CommitForAnalysis {
+            sha: "abcdef1234567890".to_string(),
+            repository: "octocat/hello".to_string(),
+            message: "Add translations".to_string(),
+            stats: CommitStats::default(),
+            files_changed: vec![FileForAnalysis {
+                filename: "i18n.txt".to_string(),
+                language: None,
+                diff,
+                additions: 1,
+                deletions: 0,
+            }],
+            binary_files_changed: 0,
+            committed_at: chrono::Utc::now(),
+            collaborative: false,
+        };
+
+        let request = AnalysisRequest::new(vec![commit], AnalysisContext::default())
+            .with_max_diff_chars(1000);
+        let prompt = request.to_prompt();
+        assert!(prompt.contains("[truncated]"));
+        assert!(prompt.contains(&"日".repeat(999)));
+    }
+}
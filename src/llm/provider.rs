@@ -1,11 +1,57 @@
 use async_trait::async_trait;
 use crate::error::Result;
-use crate::llm::prompts::AnalysisRequest;
+use crate::llm::prompts::{AnalysisRequest, SYSTEM_PROMPT};
 use crate::models::analysis::LLMAnalysisResult;
 
+/// Tokens billed for LLM requests.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+/// Prices in US dollars per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TokenPricing {
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+}
+
+impl TokenPricing {
+    /// Cost in US dollars of `usage` at these prices.
+    pub fn cost(&self, usage: TokenUsage) -> f64 {
+        (usage.input_tokens as f64 * self.input_per_million
+            + usage.output_tokens as f64 * self.output_per_million)
+            / 1_000_000.0
+    }
+}
+
 #[async_trait]
 pub trait LLMProvider: Send + Sync {
     async fn analyze_commits(&self, request: AnalysisRequest) -> Result<LLMAnalysisResult>;
     fn max_context_tokens(&self) -> usize;
     fn name(&self) -> &str;
+
+    /// Most tokens a single reply can use.
+    fn max_output_tokens(&self) -> usize {
+        4096
+    }
+
+    /// System prompt sent with every request, which the batcher leaves room
+    /// for in each batch.
+    fn system_prompt(&self) -> &str {
+        SYSTEM_PROMPT
+    }
+
+    /// Tokens used by every request made so far. Providers that do not track
+    /// usage report none.
+    fn usage(&self) -> TokenUsage {
+        TokenUsage::default()
+    }
+
+    /// Prices of the model in use, or `None` when unknown, in which case no
+    /// spending cap can be enforced.
+    fn pricing(&self) -> Option<TokenPricing> {
+        None
+    }
 }
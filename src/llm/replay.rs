@@ -0,0 +1,85 @@
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+use crate::llm::parser::parse_llm_response;
+use crate::llm::prompts::AnalysisRequest;
+use crate::llm::provider::LLMProvider;
+use crate::models::analysis::LLMAnalysisResult;
+
+/// Stable hash of a request's prompt, used to name recorded responses.
+/// FNV-1a rather than `DefaultHasher`, whose output may change between Rust
+/// releases and would orphan existing recordings.
+pub fn prompt_hash(request: &AnalysisRequest) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in request.to_prompt().bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// Where the response to `request` is recorded inside `dir`.
+pub fn recording_path(dir: &Path, request: &AnalysisRequest) -> PathBuf {
+    dir.join(format!("{}.json", prompt_hash(request)))
+}
+
+/// Answers requests from responses recorded by `ClaudeProvider::with_recording`
+/// (`--dump-llm`), so a full analysis can run without network access and
+/// always produce the same result. A request with no recording is an error.
+pub struct ReplayLLMProvider {
+    dir: PathBuf,
+}
+
+impl ReplayLLMProvider {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+#[async_trait]
+impl LLMProvider for ReplayLLMProvider {
+    async fn analyze_commits(&self, request: AnalysisRequest) -> Result<LLMAnalysisResult> {
+        let path = recording_path(&self.dir, &request);
+        let text = std::fs::read_to_string(&path).map_err(|e| {
+            Error::LLMApi(format!(
+                "No recorded response for this prompt ({}): {}",
+                path.display(),
+                e
+            ))
+        })?;
+        parse_llm_response(&text)
+    }
+
+    fn max_context_tokens(&self) -> usize {
+        // Batching must match the recorded run for the prompts to match
+        200_000
+    }
+
+    fn name(&self) -> &str {
+        "Replay"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::prompts::AnalysisContext;
+
+    #[tokio::test]
+    async fn test_cache_miss_is_an_error() {
+        let dir = std::env::temp_dir().join(format!("gitanalyzer-replay-miss-{}", std::process::id()));
+        let provider = ReplayLLMProvider::new(&dir);
+        let request = AnalysisRequest::new(Vec::new(), AnalysisContext::default());
+        assert!(matches!(provider.analyze_commits(request).await, Err(Error::LLMApi(_))));
+
+        let other = AnalysisContext {
+            repository_name: "octocat/hello".to_string(),
+            ..Default::default()
+        };
+        assert_ne!(
+            prompt_hash(&AnalysisRequest::new(Vec::new(), AnalysisContext::default())),
+            prompt_hash(&AnalysisRequest::new(Vec::new(), other))
+        );
+    }
+}
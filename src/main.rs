@@ -1,25 +1,63 @@
 use clap::Parser;
+use colored::Colorize;
 use tracing_subscriber::EnvFilter;
 
 use gitanalyzer::{
-    AnalysisPipeline, ClaudeProvider, Config, GitHubClient, PipelineConfig, Storage,
+    AnalysisPipeline, ClaudeProvider, Concurrency, Config, Error, FileConfig, GitHubClient,
+    PipelineConfig, ReplayLLMProvider, SecretFiles, Storage,
 };
-use gitanalyzer::models::UserProfile;
+use gitanalyzer::analysis::{
+    calibrate, top_by_category, CalibratedProfile, NormalizationEntry, SkillExtractor,
+    CONTRIBUTION_TYPES,
+};
+use gitanalyzer::analysis::scoring::{check_signal_score, parse_signal_score, FrequencyBasis};
+use gitanalyzer::format::{parse_date_format, NumberLocale, DEFAULT_DATE_FORMAT};
+use gitanalyzer::github::CommitSampling;
+use gitanalyzer::llm::{EnsembleProvider, HeuristicProvider};
+use gitanalyzer::models::skill::{GradeThresholds, SkillCategory};
+use gitanalyzer::models::{
+    AnalysisWarning, LLMAnalysisResult, ProcessMetrics, ProfileSummaryView, RunDiagnostics, UserProfile,
+};
+use gitanalyzer::taxonomy::{parse_alias, SkillTaxonomy};
 
 #[derive(Parser, Debug)]
 #[command(name = "gitanalyzer")]
 #[command(version = "0.1.0")]
 #[command(about = "Analyze GitHub profiles and extract developer skills")]
 #[command(author = "Git Profile Analyzer")]
+#[command(subcommand_negates_reqs = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// GitHub username to analyze
-    #[arg(short, long)]
-    username: String,
+    #[arg(short, long, required = true)]
+    username: Option<String>,
+
+    /// Project config file (defaults to .gitanalyzer.toml in the current directory, if present)
+    #[arg(long, value_name = "PATH")]
+    config: Option<std::path::PathBuf>,
+
+    /// Read the GitHub token from this file (must not be readable by other users)
+    #[arg(long)]
+    github_token_file: Option<std::path::PathBuf>,
 
-    /// Output format (json, text, markdown)
+    /// Read the Anthropic API key from this file (must not be readable by other users)
+    #[arg(long)]
+    anthropic_key_file: Option<std::path::PathBuf>,
+
+    /// Output format (json, jsonl, text, term, markdown)
     #[arg(short, long, default_value = "text")]
     format: String,
 
+    /// Disable colors in the term format
+    #[arg(long)]
+    no_color: bool,
+
+    /// Log progress as plain lines instead of drawing progress bars
+    #[arg(long)]
+    no_progress: bool,
+
     /// Output file (defaults to stdout)
     #[arg(short, long)]
     output: Option<String>,
@@ -28,17 +66,311 @@ struct Args {
     #[arg(long, default_value = "50")]
     max_commits_per_repo: u32,
 
+    /// Maximum commits to analyze in repos with over 10x --max-commits-per-repo commits
+    #[arg(long, value_name = "N")]
+    max_commits_big_repo: Option<u32>,
+
+    /// Which commits to analyze when a repo has more than --max-commits-per-repo (recent, spread, oldest)
+    #[arg(long)]
+    commit_sampling: Option<CommitSampling>,
+
+    /// Also analyze commits authored with this email, even if it isn't linked to the account
+    #[arg(long, value_name = "EMAIL")]
+    author_email: Option<String>,
+
+    /// Also analyze other people's commits that credit the user in a Co-authored-by trailer
+    #[arg(long)]
+    include_coauthored: bool,
+
+    /// Keep commits authored by bots (dependabot[bot], renovate, ...), which are skipped by default
+    #[arg(long)]
+    include_bots: bool,
+
+    /// Skip repositories with fewer stars than this
+    #[arg(long)]
+    min_stars: Option<u32>,
+
+    /// Skip repositories where the user has fewer commits than this
+    #[arg(long)]
+    min_commits: Option<u32>,
+
+    /// Drop commits whose diff is identical to an earlier one (e.g. after history rewrites)
+    #[arg(long)]
+    dedup_by_diff: bool,
+
+    /// Only analyze repositories and files in these languages (comma-separated, e.g. rust,go)
+    #[arg(long, value_delimiter = ',')]
+    only_languages: Vec<String>,
+
+    /// Maximum commits sent to the LLM in a single batch
+    #[arg(long)]
+    max_commits_per_batch: Option<usize>,
+
+    /// Tokens of each LLM batch held back for the system prompt and the reply
+    #[arg(long)]
+    reserved_tokens: Option<usize>,
+
+    /// Stop starting LLM batches once the estimated spend (US dollars) could exceed this
+    #[arg(long, value_name = "USD", value_parser = parse_max_cost)]
+    max_cost: Option<f64>,
+
+    /// Maximum characters of each file's diff sent to the LLM [default: 3000, or MAX_DIFF_CHARS]
+    #[arg(long, value_name = "N")]
+    max_diff_chars: Option<usize>,
+
+    /// Save every LLM reply to this directory, named by prompt hash, for --replay-llm
+    #[arg(long, value_name = "DIR")]
+    dump_llm: Option<std::path::PathBuf>,
+
+    /// Answer LLM requests from replies saved with --dump-llm instead of calling Claude
+    #[arg(long, value_name = "DIR", conflicts_with = "dump_llm")]
+    replay_llm: Option<std::path::PathBuf>,
+
+    /// Write counters explaining what the run fetched, skipped and sent to the LLM to this JSON file
+    #[arg(long, value_name = "FILE")]
+    diagnostics: Option<std::path::PathBuf>,
+
+    /// Analyze every batch with two Claude models and merge the results (doubles LLM cost)
+    #[arg(long, value_name = "MODEL,MODEL", value_delimiter = ',', conflicts_with_all = ["dump_llm", "replay_llm"])]
+    ensemble: Vec<String>,
+
+    /// Skip the LLM and rate only the languages of changed files; needs no ANTHROPIC_API_KEY
+    #[arg(long, conflicts_with_all = ["dump_llm", "replay_llm", "ensemble", "system_prompt_file"])]
+    no_llm: bool,
+
+    /// File containing a custom system prompt for the LLM
+    #[arg(long)]
+    system_prompt_file: Option<String>,
+
+    /// Repositories fetched concurrently from GitHub
+    #[arg(long)]
+    concurrency_github: Option<usize>,
+
+    /// Commit diffs fetched concurrently within each repository
+    #[arg(long)]
+    concurrency_commit_detail: Option<usize>,
+
+    /// LLM batches analyzed concurrently
+    #[arg(long)]
+    concurrency_llm: Option<usize>,
+
     /// Include forked repositories
     #[arg(long)]
     include_forks: bool,
 
+    /// Also analyze repositories owned by others that the user contributed to
+    #[arg(long)]
+    include_contributions: bool,
+
+    /// Also analyze the user's public gists, treating each as a single commit
+    #[arg(long)]
+    include_gists: bool,
+
+    /// Also fetch the user's recent pull requests for merge rate and PR size metrics
+    #[arg(long)]
+    include_prs: bool,
+
+    /// Only analyze commits made since each repository's latest release
+    #[arg(long)]
+    since_last_release: bool,
+
+    /// Let skills named in the user's bio and repo descriptions slightly raise their ratings
+    #[arg(long)]
+    use_bio_signals: bool,
+
+    /// Fetch each commit's patch for the author's timezone, to report working hours in local time (one extra request per commit)
+    #[arg(long)]
+    author_timezones: bool,
+
     /// Database path for storing results
     #[arg(long, default_value = "gitanalyzer.db")]
     database: String,
 
-    /// Use cached profile if available
+    /// Serve only stored profiles and never touch the network (implies --cached)
     #[arg(long)]
+    offline: bool,
+
+    /// Use cached profile if available
+    #[arg(long, conflicts_with = "as_of")]
     cached: bool,
+
+    /// With --cached, re-analyze if the cached profile is older than this many days
+    #[arg(long)]
+    max_cache_age: Option<i64>,
+
+    /// Only output skills in these categories (comma-separated, e.g. language,framework)
+    #[arg(long, value_delimiter = ',')]
+    categories: Vec<SkillCategory>,
+
+    /// Print each skill rating to stdout as a JSON line as soon as it is computed
+    #[arg(long)]
+    stream_skills: bool,
+
+    /// Show the evidence behind each skill rating
+    #[arg(long)]
+    show_evidence: bool,
+
+    /// Profile JSON (as written by --format json) to report each skill's score against
+    #[arg(long, value_name = "FILE")]
+    baseline: Option<std::path::PathBuf>,
+
+    /// Strip the user's email, location, company and emails in evidence from the output
+    #[arg(long)]
+    redact: bool,
+
+    /// Also redact the profile before saving it to the database (implies --redact)
+    #[arg(long)]
+    redact_store: bool,
+
+    /// Show the profile as it was on this date (YYYY-MM-DD): later commits are left out and recency is measured from then
+    #[arg(long, value_name = "DATE", value_parser = parse_as_of)]
+    as_of: Option<chrono::DateTime<chrono::Utc>>,
+
+    /// Show the component scores behind each skill rating
+    #[arg(long)]
+    explain: bool,
+
+    /// strftime format for dates in text, term and markdown output, e.g. "%d.%m.%Y %H:%M"
+    #[arg(long, value_name = "FORMAT", value_parser = parse_date_format)]
+    date_format: Option<String>,
+
+    /// Number formatting (decimal and thousands separators) for text, term and markdown output, e.g. de or fr-FR
+    #[arg(long, default_value = "en")]
+    locale: NumberLocale,
+
+    /// Only output experience level, primary languages and domains, top 3 skills and confidence
+    #[arg(long)]
+    summary_only: bool,
+
+    /// Add every LLM batch's raw analysis to JSON output; makes it much larger
+    #[arg(long, conflicts_with = "cached")]
+    include_raw_analyses: bool,
+
+    /// Lowest percentile (or score, when unranked) for grades A, B and C; below C is a D
+    #[arg(long, value_name = "A,B,C", default_value = "80,65,50")]
+    grade_thresholds: GradeThresholds,
+
+    /// Extra skill alias as ALIAS=SKILL, e.g. reactjs=react (repeatable)
+    #[arg(long = "alias", value_name = "ALIAS=SKILL", value_parser = parse_alias)]
+    aliases: Vec<(String, String)>,
+
+    /// Drop skills seen in fewer commits than this [default: 1, or MIN_COMMITS_PER_SKILL]
+    #[arg(long, value_name = "N")]
+    min_commits_per_skill: Option<u32>,
+
+    /// Disable the repository breadth adjustment in skill ratings
+    #[arg(long)]
+    no_breadth_factor: bool,
+
+    /// Discount old commits in skill frequency/proficiency, halving their weight every this many days
+    #[arg(long, value_name = "DAYS")]
+    age_decay: Option<f32>,
+
+    /// What skill frequency counts (commits, lines, blended)
+    #[arg(long)]
+    frequency_basis: Option<FrequencyBasis>,
+
+    /// Score (0-100) for an LLM proficiency level as LEVEL=SCORE, e.g. advanced=75 (repeatable)
+    #[arg(long = "signal-score", value_name = "LEVEL=SCORE", value_parser = parse_signal_score)]
+    signal_scores: Vec<(String, f32)>,
+
+    /// Score (0-100) for proficiency levels without a configured score
+    #[arg(long, value_name = "SCORE", value_parser = parse_unknown_signal_score)]
+    unknown_signal_score: Option<f32>,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum Command {
+    /// Quick language breakdown from GitHub's per-repo language stats (no commits or LLM)
+    Languages {
+        /// GitHub username to summarize
+        username: String,
+    },
+    /// Write every stored profile to a JSON file (no GitHub or LLM access needed)
+    Export {
+        /// File to write
+        file: std::path::PathBuf,
+    },
+    /// Load profiles from a JSON file written by `export`, replacing existing ones
+    Import {
+        /// File to read
+        file: std::path::PathBuf,
+    },
+    /// List stored profiles with the most similar skills to a stored user's (no GitHub or LLM access needed)
+    Similar {
+        /// Stored username to compare against
+        username: String,
+        /// Maximum number of profiles to list
+        #[arg(long, default_value = "10")]
+        limit: usize,
+    },
+    /// Analyze a user and show how each raw LLM skill name was normalized
+    TaxonomyReport {
+        /// GitHub username to analyze
+        username: String,
+    },
+}
+
+/// Parses `--unknown-signal-score`, which must be within 0-100.
+fn parse_unknown_signal_score(value: &str) -> Result<f32, String> {
+    let score = value
+        .trim()
+        .parse::<f32>()
+        .map_err(|_| format!("invalid number '{}'", value))?;
+    check_signal_score(score)
+}
+
+/// Clock the working hours histogram is in: the authors' own with
+/// `--author-timezones`, UTC otherwise.
+fn working_hours_clock(profile: &UserProfile) -> &'static str {
+    if profile.summary.modal_timezone.is_some() {
+        "local"
+    } else {
+        "UTC"
+    }
+}
+
+/// Header line for a profile rated as of a past date (`--as-of`).
+fn as_of_notice(as_of: chrono::DateTime<chrono::Utc>) -> String {
+    format!(
+        "As of: {} (later commits and pull requests were ignored)",
+        as_of.format("%Y-%m-%d")
+    )
+}
+
+/// The analysis date in `--date-format`.
+fn analysis_date(profile: &UserProfile, options: &OutputOptions) -> String {
+    let format = options.date_format.as_deref().unwrap_or(DEFAULT_DATE_FORMAT);
+    profile.analysis_date.format(format).to_string()
+}
+
+/// Parses `--max-cost`, which must not be negative.
+fn parse_max_cost(value: &str) -> Result<f64, String> {
+    match value.trim().trim_start_matches('$').parse::<f64>() {
+        Ok(cost) if cost >= 0.0 => Ok(cost),
+        _ => Err(format!("invalid cost '{}' (expected US dollars, e.g. 5.00)", value)),
+    }
+}
+
+/// Parses `--as-of` as the end of that day in UTC, so commits made on the
+/// day itself are included.
+fn parse_as_of(value: &str) -> Result<chrono::DateTime<chrono::Utc>, String> {
+    let date = chrono::NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+        .map_err(|_| format!("invalid date '{}' (expected YYYY-MM-DD)", value))?;
+    let end_of_day = date.and_hms_opt(23, 59, 59).expect("valid time of day");
+    Ok(end_of_day.and_utc())
+}
+
+/// Layout version of `export` files; `import` refuses files from newer layouts.
+const EXPORT_FORMAT_VERSION: u32 = 1;
+
+/// Document written by `export` and read by `import`.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct DatabaseExport<P> {
+    #[serde(default)]
+    format_version: u32,
+    exported_at: chrono::DateTime<chrono::Utc>,
+    profiles: Vec<P>,
 }
 
 #[tokio::main]
@@ -58,53 +390,600 @@ async fn main() -> anyhow::Result<()> {
     // Parse CLI arguments
     let args = Args::parse();
 
-    // Load configuration
-    let config = Config::from_env()?;
+    // Export, import and similar only touch the database, so they need no credentials
+    match &args.command {
+        Some(Command::Export { file }) => return export_database(&args.database, file),
+        Some(Command::Import { file }) => return import_database(&args.database, file),
+        Some(Command::Similar { username, limit }) => {
+            return print_similar_profiles(&args, username, *limit)
+        }
+        _ => {}
+    }
+
+    // Offline mode serves stored profiles and never builds a network client
+    if args.offline {
+        return serve_offline(&args);
+    }
+
+    // Load configuration; project file values override the environment
+    let mut config = Config::from_env_with_secrets(&SecretFiles {
+        github_token: args.github_token_file.clone(),
+        anthropic_api_key: args.anthropic_key_file.clone(),
+    })?;
+    if let Some(file) = FileConfig::load(args.config.as_deref())? {
+        config.apply_file(&file);
+    }
+
+    if let Some(Command::Languages { username }) = &args.command {
+        return print_language_breakdown(&config, username, &args).await;
+    }
+    let taxonomy_report = matches!(args.command, Some(Command::TaxonomyReport { .. }));
+    // clap enforces --username whenever no subcommand is given
+    let username = match &args.command {
+        Some(Command::TaxonomyReport { username }) => username.clone(),
+        _ => args.username.clone().unwrap_or_default(),
+    };
 
     // Initialize storage
     let storage = Storage::new(&args.database)?;
 
-    // Check for cached profile if requested
-    if args.cached {
-        if let Some(profile) = storage.get_profile(&args.username)? {
-            tracing::info!("Using cached profile from {}", profile.analysis_date);
-            output_profile(&profile, &args)?;
-            return Ok(());
+    // Check for cached profile if requested; cached profiles keep no raw
+    // LLM output, so the taxonomy report always analyzes afresh
+    if args.cached && !taxonomy_report {
+        match storage.get_profile(&username)? {
+            Some(profile) => {
+                let age_days = (chrono::Utc::now() - profile.analysis_date).num_days();
+                match args.max_cache_age {
+                    Some(max_age) if age_days > max_age => {
+                        tracing::info!(
+                            "Cached profile is {} days old (max {}), performing fresh analysis",
+                            age_days,
+                            max_age
+                        );
+                    }
+                    _ => return output_cached_profile(&profile, &args),
+                }
+            }
+            None => tracing::info!("No cached profile found, performing fresh analysis"),
         }
-        tracing::info!("No cached profile found, performing fresh analysis");
     }
 
-    // Initialize clients
+    // Read before the analysis so a bad file fails fast
+    let baseline = load_baseline(&args)?;
+
+    // Initialize clients; a replay or --no-llm needs no Anthropic key
     let github = GitHubClient::new(&config.github_token)?;
-    let llm = ClaudeProvider::new(
-        config.anthropic_api_key.clone(),
-        Some("claude-sonnet-4-20250514".to_string()),
-    );
+    let builder = AnalysisPipeline::builder().github(github);
+    let claude = |model: Option<String>| -> anyhow::Result<ClaudeProvider> {
+        let mut llm = ClaudeProvider::new(config.require_anthropic_api_key()?.to_string(), model);
+        if let Some(ref base_url) = config.anthropic_base_url {
+            llm = llm.with_base_url(base_url);
+        }
+        if let Some(ref path) = args.system_prompt_file {
+            llm = llm.with_system_prompt(std::fs::read_to_string(path)?);
+        }
+        Ok(llm)
+    };
+    let builder = if args.no_llm {
+        builder.llm(HeuristicProvider::new())
+    } else if let Some(ref dir) = args.replay_llm {
+        builder.llm(ReplayLLMProvider::new(dir))
+    } else if let [first, second] = args.ensemble.as_slice() {
+        builder.llm(EnsembleProvider::new(
+            claude(Some(first.clone()))?,
+            claude(Some(second.clone()))?,
+        ))
+    } else if !args.ensemble.is_empty() {
+        anyhow::bail!("--ensemble takes exactly two models, e.g. claude-sonnet-4-20250514,claude-opus-4-20250514");
+    } else {
+        let mut llm = claude(config.anthropic_model.clone())?;
+        if let Some(ref dir) = args.dump_llm {
+            llm = llm.with_recording(dir);
+        }
+        builder.llm(llm)
+    };
 
     // Create pipeline
     let pipeline_config = PipelineConfig {
         max_commits_per_repo: args.max_commits_per_repo,
+        max_commits_big_repo: args.max_commits_big_repo.or(config.max_commits_big_repo),
+        commit_sampling: args.commit_sampling.unwrap_or(config.commit_sampling),
+        author_email: args.author_email.clone().or_else(|| config.author_email.clone()),
+        include_coauthored: args.include_coauthored || config.include_coauthored,
+        include_bots: args.include_bots || config.include_bots,
         include_forks: args.include_forks,
-        concurrency_limit: config.concurrency_limit,
+        include_contributions: args.include_contributions || config.include_contributions,
+        include_gists: args.include_gists || config.include_gists,
+        include_prs: args.include_prs || config.include_prs,
+        since_last_release: args.since_last_release || config.since_last_release,
+        use_bio_signals: args.use_bio_signals || config.use_bio_signals,
+        author_timezones: args.author_timezones || config.author_timezones,
+        concurrency: Concurrency {
+            github: args.concurrency_github.unwrap_or(config.concurrency.github),
+            commit_detail: args
+                .concurrency_commit_detail
+                .unwrap_or(config.concurrency.commit_detail),
+            llm: args.concurrency_llm.unwrap_or(config.concurrency.llm),
+        },
+        breadth_factor: config.breadth_factor && !args.no_breadth_factor,
+        min_commits_per_skill: args.min_commits_per_skill.unwrap_or(config.min_commits_per_skill),
+        per_file_diff_limit: args.max_diff_chars.unwrap_or(config.per_file_diff_limit),
+        min_stars: args.min_stars.unwrap_or(config.min_stars),
+        min_repo_commits: args.min_commits.unwrap_or(config.min_repo_commits),
+        neutral_baseline: config.neutral_baseline,
+        age_decay_half_life_days: args.age_decay.or(config.age_decay_half_life_days),
+        frequency_basis: args.frequency_basis.unwrap_or(config.frequency_basis),
+        signal_scores: config
+            .signal_scores
+            .iter()
+            .chain(&args.signal_scores)
+            .cloned()
+            .collect(),
+        unknown_signal_score: args.unknown_signal_score.or(config.unknown_signal_score),
+        dedup_by_diff: args.dedup_by_diff || config.dedup_by_diff,
+        max_commits_per_batch: args
+            .max_commits_per_batch
+            .unwrap_or(config.max_commits_per_batch),
+        reserved_tokens: args.reserved_tokens.or(config.reserved_tokens),
+        max_cost: args.max_cost.or(config.max_cost),
+        only_languages: if args.only_languages.is_empty() {
+            config.only_languages.clone()
+        } else {
+            args.only_languages.clone()
+        },
+        redact_store: args.redact_store,
+        as_of: args.as_of,
+        exclude_repositories: config.exclude_repositories.clone(),
+        exclude_paths: config.exclude_paths.clone(),
+        skill_aliases: config
+            .skill_aliases
+            .iter()
+            .chain(&args.aliases)
+            .cloned()
+            .collect(),
+        taxonomy: config.taxonomy.clone(),
     };
+    let skill_aliases = pipeline_config.skill_aliases.clone();
+    let taxonomy = pipeline_config.taxonomy.clone();
 
-    let pipeline = AnalysisPipeline::new(github, llm, storage, pipeline_config);
+    let mut pipeline = builder
+        .store(storage)
+        .config(pipeline_config)
+        .progress(show_progress(&args))
+        .build()?;
+    if args.stream_skills {
+        let username = username.clone();
+        let categories = args.categories.clone();
+        let redact = args.redact || args.redact_store;
+        let grades = args.grade_thresholds;
+        pipeline = pipeline.with_skill_callback(move |rating| {
+            if !categories.is_empty() && !categories.contains(&rating.skill.category) {
+                return;
+            }
+            let mut rating = rating.clone();
+            if redact {
+                rating.redact();
+            }
+            let line = serde_json::json!({
+                "type": "skill",
+                "username": username,
+                "rating": rating,
+                "grade": rating.grade_with(&grades).to_string(),
+            });
+            println!("{}", line);
+        });
+    }
+
+    let analyses = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    if taxonomy_report || args.include_raw_analyses {
+        let analyses = analyses.clone();
+        pipeline = pipeline.with_analyses_callback(move |results| {
+            analyses.lock().unwrap().extend_from_slice(results);
+        });
+    }
+
+    // First Ctrl-C stops new work and saves partial results; second aborts
+    let shutdown = pipeline.shutdown_token();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            tracing::warn!(
+                "Interrupted: finishing in-flight work and saving partial results (Ctrl-C again to abort)"
+            );
+            shutdown.cancel();
+            if tokio::signal::ctrl_c().await.is_ok() {
+                std::process::exit(130);
+            }
+        }
+    });
 
     // Run analysis
-    tracing::info!("Starting analysis for GitHub user: {}", args.username);
-    let profile = pipeline.analyze_user(&args.username).await?;
+    tracing::info!("Starting analysis for GitHub user: {}", username);
+    let result = pipeline.analyze_user(&username).await;
+    if let Some(path) = &args.diagnostics {
+        let diagnostics = match &result {
+            Ok(result) => result.diagnostics.clone(),
+            Err(e) => RunDiagnostics {
+                error: Some(e.to_string()),
+                ..pipeline.diagnostics(&username)
+            },
+        };
+        std::fs::write(path, serde_json::to_string_pretty(&diagnostics)?)?;
+        tracing::info!("Diagnostics written to {}", path.display());
+    }
+    let result = result?;
+
+    if taxonomy_report {
+        let report = SkillExtractor::with_taxonomy(SkillTaxonomy::with_extensions(&taxonomy, &skill_aliases))
+            .normalization_report(&analyses.lock().unwrap());
+        return output_taxonomy_report(&username, &report, &args);
+    }
 
     // Output results
-    output_profile(&profile, &args)?;
+    let options = OutputOptions {
+        show_evidence: args.show_evidence,
+        explain: args.explain,
+        grades: args.grade_thresholds,
+        summary_only: args.summary_only,
+        date_format: args.date_format.clone(),
+        locale: args.locale,
+        categories: args.categories.clone(),
+        warnings: result.warnings,
+        redact: args.redact || args.redact_store,
+        calibration: baseline.map(|b| calibrate(&result.profile, &b)),
+        raw_analyses: args
+            .include_raw_analyses
+            .then(|| std::mem::take(&mut *analyses.lock().unwrap())),
+        ..Default::default()
+    };
+    output_profile(&result.profile, &args, &options)?;
+
+    Ok(())
+}
+
+fn output_cached_profile(profile: &UserProfile, args: &Args) -> anyhow::Result<()> {
+    tracing::info!("Using cached profile from {}", profile.analysis_date);
+    let baseline = load_baseline(args)?;
+    let options = OutputOptions {
+        calibration: baseline.map(|b| calibrate(profile, &b)),
+        cached_age_days: Some((chrono::Utc::now() - profile.analysis_date).num_days()),
+        show_evidence: args.show_evidence,
+        explain: args.explain,
+        grades: args.grade_thresholds,
+        summary_only: args.summary_only,
+        date_format: args.date_format.clone(),
+        locale: args.locale,
+        categories: args.categories.clone(),
+        redact: args.redact || args.redact_store,
+        ..Default::default()
+    };
+    output_profile(profile, args, &options)
+}
+
+/// Reads the `--baseline` profile, if one was given.
+fn load_baseline(args: &Args) -> anyhow::Result<Option<UserProfile>> {
+    let Some(path) = &args.baseline else {
+        return Ok(None);
+    };
+    let mut baseline: UserProfile = serde_json::from_str(&std::fs::read_to_string(path)?)
+        .map_err(|e| anyhow::anyhow!("{} is not a profile JSON file: {}", path.display(), e))?;
+    baseline.migrate();
+    Ok(Some(baseline))
+}
+
+/// e.g. "+12 vs torvalds", or `None` without a baseline.
+fn baseline_delta(options: &OutputOptions, skill: &gitanalyzer::models::skill::SkillRating) -> Option<String> {
+    let calibration = options.calibration.as_ref()?;
+    Some(match calibration.delta_for(&skill.skill.name).and_then(|d| d.delta) {
+        Some(delta) => format!("{:+} vs {}", delta, calibration.baseline),
+        None => format!("not in {}", calibration.baseline),
+    })
+}
+
+/// Prints the stored profile for `--username`, failing with
+/// `Error::Offline` when there is none or the command needs the network.
+/// A profile older than `--max-cache-age` is still served, with a warning.
+fn serve_offline(args: &Args) -> anyhow::Result<()> {
+    let needs_network = match &args.command {
+        Some(Command::Languages { .. }) => Some("languages"),
+        Some(Command::TaxonomyReport { .. }) => Some("taxonomy-report"),
+        _ => None,
+    };
+    if let Some(command) = needs_network {
+        return Err(Error::Offline(format!(
+            "the {} command needs network access and offline mode is set",
+            command
+        ))
+        .into());
+    }
+
+    let username = args.username.clone().unwrap_or_default();
+    let Some(profile) = Storage::new(&args.database)?.get_profile(&username)? else {
+        return Err(Error::Offline(format!(
+            "profile for {} is not cached and offline mode is set",
+            username
+        ))
+        .into());
+    };
+
+    let age_days = (chrono::Utc::now() - profile.analysis_date).num_days();
+    if let Some(max_age) = args.max_cache_age.filter(|&max_age| age_days > max_age) {
+        tracing::warn!(
+            "Cached profile is {} days old (max {}), serving it anyway in offline mode",
+            age_days,
+            max_age
+        );
+    }
+    output_cached_profile(&profile, args)
+}
+
+/// Sums GitHub's per-repository language byte counts and prints a ranked
+/// percentage breakdown, without fetching commits or calling the LLM.
+async fn print_language_breakdown(
+    config: &Config,
+    username: &str,
+    args: &Args,
+) -> anyhow::Result<()> {
+    let github = GitHubClient::new(&config.github_token)?;
+    let repos: Vec<_> = github
+        .get_user_repos(username)
+        .await?
+        .into_iter()
+        .filter(|r| args.include_forks || config.include_forks || !r.fork)
+        .collect();
+
+    let totals = github
+        .get_language_totals(
+            &repos,
+            args.concurrency_github.unwrap_or(config.concurrency.github),
+        )
+        .await;
+    let total_bytes: u64 = totals.values().sum();
+
+    let mut languages: Vec<_> = totals.into_iter().collect();
+    languages.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
 
+    let output = if args.format == "json" {
+        let entries: Vec<_> = languages
+            .iter()
+            .map(|(language, bytes)| {
+                serde_json::json!({
+                    "language": language,
+                    "bytes": bytes,
+                    "percentage": *bytes as f64 / total_bytes as f64 * 100.0,
+                })
+            })
+            .collect();
+        serde_json::to_string_pretty(&serde_json::json!({
+            "username": username,
+            "repositories": repos.len(),
+            "languages": entries,
+        }))?
+    } else {
+        let mut output = format!(
+            "\n=== Languages: {} ({} repositories) ===\n\n",
+            username,
+            repos.len()
+        );
+        if languages.is_empty() {
+            output.push_str("No language data found\n");
+        }
+        for (language, bytes) in &languages {
+            output.push_str(&format!(
+                "  {:<20} {:>5.1}%\n",
+                language,
+                *bytes as f64 / total_bytes as f64 * 100.0
+            ));
+        }
+        output
+    };
+
+    if let Some(ref path) = args.output {
+        std::fs::write(path, &output)?;
+        tracing::info!("Output written to: {}", path);
+    } else {
+        println!("{}", output);
+    }
+
+    Ok(())
+}
+
+/// Prints a raw name -> normalized name -> category table, flagging names
+/// that are not in the taxonomy, or the same entries as JSON.
+fn output_taxonomy_report(
+    username: &str,
+    report: &[NormalizationEntry],
+    args: &Args,
+) -> anyhow::Result<()> {
+    let unmapped = report.iter().filter(|e| !e.mapped).count();
+
+    let output = if args.format == "json" {
+        serde_json::to_string_pretty(&serde_json::json!({
+            "username": username,
+            "unmapped": unmapped,
+            "entries": report,
+        }))?
+    } else {
+        let color = args.format == "term" && use_color(args);
+        let mut output = format!(
+            "\n=== Skill Normalization: {} ({} names, {} unmapped) ===\n\n",
+            username,
+            report.len(),
+            unmapped
+        );
+        if report.is_empty() {
+            output.push_str("No skills were extracted\n");
+        }
+        for entry in report {
+            let line = format!(
+                "{} {:<28} -> {:<28} {:<10} ({}x)",
+                if entry.mapped { " " } else { "?" },
+                entry.raw_name,
+                entry.normalized_name,
+                entry.category.to_string(),
+                entry.occurrences
+            );
+            if color && !entry.mapped {
+                output.push_str(&line.yellow().to_string());
+            } else {
+                output.push_str(&line);
+            }
+            output.push('\n');
+        }
+        if unmapped > 0 {
+            output.push_str("\n? = not in the taxonomy; kept under its own name and reported category\n");
+        }
+        output
+    };
+
+    if let Some(ref path) = args.output {
+        std::fs::write(path, &output)?;
+        tracing::info!("Output written to: {}", path);
+    } else {
+        println!("{}", output);
+    }
+
+    Ok(())
+}
+
+/// Prints the stored profiles most similar to `username`'s, as JSON with
+/// `--format json` and as a ranked list otherwise.
+fn print_similar_profiles(args: &Args, username: &str, limit: usize) -> anyhow::Result<()> {
+    let similar = Storage::new(&args.database)?.find_similar(username, limit)?;
+
+    if args.format == "json" {
+        let entries: Vec<_> = similar
+            .iter()
+            .map(|(user, similarity)| serde_json::json!({ "username": user, "similarity": similarity }))
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+
+    if similar.is_empty() {
+        println!(
+            "No stored profiles share skills with {}. Analyze more users to compare against.",
+            username
+        );
+        return Ok(());
+    }
+    println!("Profiles most similar to {}:", username);
+    let width = similar.iter().map(|(user, _)| user.len()).max().unwrap_or(0);
+    for (user, similarity) in &similar {
+        println!("  {:<width$}  {:>3.0}%", user, similarity * 100.0, width = width);
+    }
     Ok(())
 }
 
-fn output_profile(profile: &UserProfile, args: &Args) -> anyhow::Result<()> {
+fn export_database(database: &str, file: &std::path::Path) -> anyhow::Result<()> {
+    let profiles = Storage::new(database)?.export_all()?;
+    let export = DatabaseExport {
+        format_version: EXPORT_FORMAT_VERSION,
+        exported_at: chrono::Utc::now(),
+        profiles,
+    };
+    std::fs::write(file, serde_json::to_string_pretty(&export)?)?;
+    tracing::info!("Exported {} profile(s) to {}", export.profiles.len(), file.display());
+    Ok(())
+}
+
+fn import_database(database: &str, file: &std::path::Path) -> anyhow::Result<()> {
+    // Profiles are parsed one by one so a single malformed entry is skipped
+    // instead of failing the whole import
+    let export: DatabaseExport<serde_json::Value> =
+        serde_json::from_str(&std::fs::read_to_string(file)?)?;
+    if export.format_version > EXPORT_FORMAT_VERSION {
+        anyhow::bail!(
+            "{} uses export format {}, but this version only understands up to {}; upgrade gitanalyzer to import it",
+            file.display(),
+            export.format_version,
+            EXPORT_FORMAT_VERSION
+        );
+    }
+
+    let total = export.profiles.len();
+    let profiles: Vec<UserProfile> = export
+        .profiles
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, value)| match serde_json::from_value(value) {
+            Ok(profile) => Some(profile),
+            Err(e) => {
+                tracing::warn!("Skipping profile #{} in {}: {}", i + 1, file.display(), e);
+                None
+            }
+        })
+        .collect();
+
+    let imported = Storage::new(database)?.import_all(&profiles)?;
+    tracing::info!(
+        "Imported {} of {} profile(s) from {} into {}",
+        imported,
+        total,
+        file.display(),
+        database
+    );
+    Ok(())
+}
+
+/// Render-time settings that are not part of the profile itself.
+#[derive(Debug, Default)]
+struct OutputOptions {
+    /// Age of the analysis in days when served from the cache
+    cached_age_days: Option<i64>,
+    /// Render evidence snippets under each skill
+    show_evidence: bool,
+    /// Render the component score breakdown under each skill
+    explain: bool,
+    /// Cut-offs for the letter grade shown next to each score
+    grades: GradeThresholds,
+    /// Render only `UserProfile::summary_view`
+    summary_only: bool,
+    /// strftime format for the analysis date; `DEFAULT_DATE_FORMAT` if unset
+    date_format: Option<String>,
+    /// Separators for numbers in human-readable formats
+    locale: NumberLocale,
+    /// Only render skills in these categories (empty = all)
+    categories: Vec<SkillCategory>,
+    /// Problems from a fresh analysis; cached profiles have none
+    warnings: Vec<AnalysisWarning>,
+    /// Strip personal details before rendering
+    redact: bool,
+    /// Scores relative to `--baseline`, when one was given
+    calibration: Option<CalibratedProfile>,
+    /// Per-batch LLM analyses for JSON output (`--include-raw-analyses`)
+    raw_analyses: Option<Vec<LLMAnalysisResult>>,
+}
+
+fn output_profile(
+    profile: &UserProfile,
+    args: &Args,
+    options: &OutputOptions,
+) -> anyhow::Result<()> {
+    // Category filtering and redaction apply to output only; storage keeps
+    // every skill and, without --redact-store, the personal details
+    let adjusted;
+    let profile = if options.categories.is_empty() && !options.redact {
+        profile
+    } else {
+        let mut copy = profile.clone();
+        if !options.categories.is_empty() {
+            copy.skills
+                .retain(|s| options.categories.contains(&s.skill.category));
+        }
+        if options.redact {
+            copy.redact();
+        }
+        adjusted = copy;
+        &adjusted
+    };
+
     let output = match args.format.as_str() {
-        "json" => serde_json::to_string_pretty(profile)?,
-        "markdown" => format_markdown(profile),
-        _ => format_text(profile),
+        _ if options.summary_only => format_summary(&profile.summary_view(), &args.format)?,
+        "json" => format_json(profile, options)?,
+        "jsonl" => format_jsonl(profile, options)?,
+        "markdown" => format_markdown(profile, options),
+        "term" if use_color(args) => format_term(profile, options),
+        _ => format_text(profile, options),
     };
 
     if let Some(ref path) = args.output {
@@ -117,105 +996,680 @@ fn output_profile(profile: &UserProfile, args: &Args) -> anyhow::Result<()> {
     Ok(())
 }
 
-fn format_text(profile: &UserProfile) -> String {
+/// Colors are used only when printing to a terminal, and never with
+/// `--no-color` or a non-empty `NO_COLOR` (https://no-color.org).
+fn use_color(args: &Args) -> bool {
+    use std::io::IsTerminal;
+
+    let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    let enabled =
+        !args.no_color && !no_color_env && args.output.is_none() && std::io::stdout().is_terminal();
+    if enabled {
+        // We've already checked the terminal, so don't let `colored` second-guess it
+        colored::control::set_override(true);
+    }
+    enabled
+}
+
+/// Progress bars are drawn only when stderr is a terminal, and never with
+/// `--no-progress` or in CI (a non-empty `CI`, as set by most CI services).
+fn show_progress(args: &Args) -> bool {
+    use std::io::IsTerminal;
+
+    let ci_env = std::env::var_os("CI").is_some_and(|v| !v.is_empty());
+    !args.no_progress && !ci_env && std::io::stderr().is_terminal()
+}
+
+/// Renders `--summary-only` output in the given format. `term` is the same
+/// as `text`, since there is too little to color.
+fn format_summary(view: &ProfileSummaryView, format: &str) -> anyhow::Result<String> {
+    let top_skills = view
+        .top_skills
+        .iter()
+        .map(|s| format!("{} {}", s.name, s.proficiency_score))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let domains = view
+        .primary_domains
+        .iter()
+        .map(|d| d.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let partial = if view.incomplete { " (partial analysis)" } else { "" };
+
+    Ok(match format {
+        "json" => serde_json::to_string_pretty(view)?,
+        "jsonl" => serde_json::to_string(&serde_json::json!({
+            "type": "summary",
+            "username": view.login,
+            "summary": view,
+        }))?,
+        "markdown" => {
+            let mut output = format!("# {}{}\n\n", view.login, partial);
+            output.push_str("| Metric | Value |\n|--------|-------|\n");
+            output.push_str(&format!("| Experience Level | {} |\n", view.experience_level));
+            output.push_str(&format!(
+                "| Analysis Confidence | {:.0}% |\n",
+                view.overall_confidence * 100.0
+            ));
+            output.push_str(&format!("| Top Skills | {} |\n", top_skills));
+            output.push_str(&format!("| Primary Languages | {} |\n", view.primary_languages.join(", ")));
+            output.push_str(&format!("| Primary Domains | {} |\n", domains));
+            output
+        }
+        _ => {
+            let mut output = format!(
+                "{}: {} (confidence {:.0}%){}\n",
+                view.login,
+                view.experience_level,
+                view.overall_confidence * 100.0,
+                partial
+            );
+            output.push_str(&format!("Top skills: {}\n", top_skills));
+            output.push_str(&format!("Primary languages: {}\n", view.primary_languages.join(", ")));
+            output.push_str(&format!("Primary domains: {}\n", domains));
+            output
+        }
+    })
+}
+
+fn format_json(profile: &UserProfile, options: &OutputOptions) -> anyhow::Result<String> {
+    let mut value = serde_json::to_value(profile)?;
+    if let Some(skills) = value["skills"].as_array_mut() {
+        for (skill, rating) in skills.iter_mut().zip(&profile.skills) {
+            skill["grade"] = serde_json::json!(rating.grade_with(&options.grades).to_string());
+        }
+    }
+    if let Some(age) = options.cached_age_days {
+        value["cached_age_days"] = serde_json::json!(age);
+    }
+    value["warnings"] = serde_json::to_value(&options.warnings)?;
+    if let Some(ref calibration) = options.calibration {
+        value["calibration"] = serde_json::to_value(calibration)?;
+    }
+    if let Some(ref analyses) = options.raw_analyses {
+        let mut analyses = analyses.clone();
+        if options.redact {
+            analyses.iter_mut().for_each(LLMAnalysisResult::redact);
+        }
+        value["raw_analyses"] = serde_json::to_value(analyses)?;
+    }
+    Ok(serde_json::to_string_pretty(&value)?)
+}
+
+/// Emits one JSON object per line: a header with user info followed by
+/// one object per skill rating. Each line is independently parseable.
+fn format_jsonl(profile: &UserProfile, options: &OutputOptions) -> anyhow::Result<String> {
+    let mut lines = Vec::with_capacity(profile.skills.len() + 1);
+
+    let mut header = serde_json::json!({
+        "type": "header",
+        "schema_version": profile.schema_version,
+        "user": profile.user,
+        "total_commits_analyzed": profile.total_commits_analyzed,
+        "repositories": profile.repositories.len(),
+        "analysis_date": profile.analysis_date,
+        "summary": profile.summary,
+    });
+    if let Some(as_of) = profile.as_of {
+        header["as_of"] = serde_json::json!(as_of);
+    }
+    if let Some(age) = options.cached_age_days {
+        header["cached_age_days"] = serde_json::json!(age);
+    }
+    lines.push(serde_json::to_string(&header)?);
+
+    for skill in &profile.skills {
+        lines.push(serde_json::to_string(&serde_json::json!({
+            "type": "skill",
+            "username": profile.user.login,
+            "rating": skill,
+            "grade": skill.grade_with(&options.grades).to_string(),
+        }))?);
+    }
+
+    if let Some(ref calibration) = options.calibration {
+        lines.push(serde_json::to_string(&serde_json::json!({
+            "type": "calibration",
+            "username": profile.user.login,
+            "calibration": calibration,
+        }))?);
+    }
+
+    for warning in &options.warnings {
+        lines.push(serde_json::to_string(&serde_json::json!({
+            "type": "warning",
+            "username": profile.user.login,
+            "warning": warning,
+        }))?);
+    }
+
+    Ok(lines.join("\n"))
+}
+
+/// Width of the proficiency bars in the term format, in characters.
+const TERM_BAR_WIDTH: usize = 20;
+
+/// Red below 40, yellow below 70, green from there up.
+fn score_color(text: &str, score: u8) -> colored::ColoredString {
+    match score {
+        0..=39 => text.red(),
+        40..=69 => text.yellow(),
+        _ => text.green(),
+    }
+}
+
+/// A bar of `TERM_BAR_WIDTH` cells, filled in proportion to `score` (0-100).
+fn proficiency_bar(score: u8) -> String {
+    let filled = (score as usize * TERM_BAR_WIDTH + 50) / 100;
+    format!(
+        "{}{}",
+        score_color(&"█".repeat(filled), score),
+        "░".repeat(TERM_BAR_WIDTH - filled).dimmed()
+    )
+}
+
+fn trend_arrow(trend: &gitanalyzer::models::skill::SkillTrend) -> colored::ColoredString {
+    use gitanalyzer::models::skill::SkillTrend;
+    match trend {
+        SkillTrend::Improving => "↑".green().bold(),
+        SkillTrend::Declining => "↓".red().bold(),
+        SkillTrend::Dormant => "⏸".dimmed(),
+        SkillTrend::New => "★".cyan(),
+        SkillTrend::Stable => "→".normal(),
+    }
+}
+
+/// Number of repositories named after "primarily used in".
+const PRIMARY_REPO_COUNT: usize = 2;
+
+/// The skill's most used repositories, e.g. "octocat/a, octocat/b", or
+/// `None` when no repositories were recorded.
+fn primary_repositories(skill: &gitanalyzer::models::skill::SkillRating) -> Option<String> {
+    let repos = &skill.evidence.repositories;
+    (!repos.is_empty()).then(|| {
+        repos
+            .iter()
+            .take(PRIMARY_REPO_COUNT)
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(", ")
+    })
+}
+
+/// e.g. "3 of 12 commits co-authored", or `None` when the skill was never
+/// seen in a commit with `Co-authored-by:` trailers.
+fn collaborative_note(skill: &gitanalyzer::models::skill::SkillRating) -> Option<String> {
+    let evidence = &skill.evidence;
+    (evidence.collaborative_commits > 0).then(|| {
+        format!(
+            "{} of {} commits co-authored",
+            evidence.collaborative_commits, evidence.commit_count
+        )
+    })
+}
+
+/// e.g. "2 of 9 commits from gists", for skills seen with `--include-gists`.
+fn gist_note(skill: &gitanalyzer::models::skill::SkillRating) -> Option<String> {
+    let evidence = &skill.evidence;
+    (evidence.gist_commits > 0)
+        .then(|| format!("{} of {} commits from gists", evidence.gist_commits, evidence.commit_count))
+}
+
+/// e.g. ", self-reported +4" when the bio or repo descriptions raised the
+/// score, otherwise empty.
+fn self_reported_note(breakdown: &gitanalyzer::models::skill::ScoreBreakdown) -> String {
+    if breakdown.self_reported_bonus > 0.0 {
+        format!(", self-reported +{:.0}", breakdown.self_reported_bonus)
+    } else {
+        String::new()
+    }
+}
+
+/// e.g. "feature 45%, fix 30%, refactor 25%", largest share first, or `None`
+/// when no commits were classified.
+fn format_contributions(profile: &UserProfile) -> Option<String> {
+    let breakdown = &profile.summary.contribution_breakdown;
+    if breakdown.is_empty() {
+        return None;
+    }
+    let mut shares: Vec<_> = CONTRIBUTION_TYPES
+        .iter()
+        .filter_map(|kind| breakdown.get(*kind).map(|share| (*kind, *share)))
+        .collect();
+    // Stable, so equal shares keep the CONTRIBUTION_TYPES order
+    shares.sort_by(|a, b| b.1.total_cmp(&a.1));
+    Some(
+        shares
+            .iter()
+            .map(|(kind, share)| format!("{} {:.0}%", kind, share * 100.0))
+            .collect::<Vec<_>>()
+            .join(", "),
+    )
+}
+
+/// Languages listed by `format_language_shares`; the rest are left out.
+const MAX_LANGUAGE_SHARES: usize = 5;
+
+/// e.g. "Python 70%, JavaScript 30%", largest share first, or `None` when
+/// there are no languages.
+fn format_language_shares(shares: &std::collections::HashMap<String, f32>) -> Option<String> {
+    if shares.is_empty() {
+        return None;
+    }
+    let mut shares: Vec<_> = shares.iter().collect();
+    shares.sort_by(|a, b| b.1.total_cmp(a.1).then_with(|| a.0.cmp(b.0)));
+    Some(
+        shares
+            .iter()
+            .take(MAX_LANGUAGE_SHARES)
+            .map(|(language, share)| format!("{} {:.0}%", language, *share * 100.0))
+            .collect::<Vec<_>>()
+            .join(", "),
+    )
+}
+
+/// e.g. "24 (83% merged, 140 lines on average, 75% from prefixed branches)",
+/// leaving out figures there was no data for.
+fn format_process_metrics(metrics: &ProcessMetrics) -> String {
+    let mut details = Vec::new();
+    if let Some(rate) = metrics.merge_rate {
+        details.push(format!("{:.0}% merged", rate * 100.0));
+    }
+    if let Some(size) = metrics.average_size {
+        details.push(format!("{:.0} lines on average", size));
+    }
+    if let Some(rate) = metrics.prefixed_branch_rate {
+        details.push(format!("{:.0}% from prefixed branches", rate * 100.0));
+    }
+    if details.is_empty() {
+        metrics.pull_requests.to_string()
+    } else {
+        format!("{} ({})", metrics.pull_requests, details.join(", "))
+    }
+}
+
+/// e.g. "2019-03 to 2024-01 (peak: 2022)"
+fn format_activity_period(profile: &UserProfile) -> Option<String> {
+    let summary = &profile.summary;
+    let (start, end) = (summary.activity_start?, summary.activity_end?);
+    let mut period = format!("{} to {}", start.format("%Y-%m"), end.format("%Y-%m"));
+    if let Some(peak) = summary.activity_peak_year {
+        period.push_str(&format!(" (peak: {})", peak));
+    }
+    Some(period)
+}
+
+/// Renders values as Unicode block characters scaled to the largest value.
+fn sparkline(data: &[u32]) -> String {
+    const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let max = data.iter().copied().max().unwrap_or(0).max(1) as u64;
+    data.iter()
+        .map(|&v| BLOCKS[(v as u64 * (BLOCKS.len() as u64 - 1) / max) as usize])
+        .collect()
+}
+
+/// How `format_report` renders: plain text, or with colors for interactive
+/// terminals. Both render the same sections.
+#[derive(Clone, Copy)]
+enum ReportStyle {
+    Plain,
+    Term,
+}
+
+impl ReportStyle {
+    fn title(self, login: &str) -> String {
+        match self {
+            ReportStyle::Plain => format!("\n=== Profile Analysis: {} ===\n\n", login),
+            ReportStyle::Term => format!(
+                "\n{}\n\n",
+                format!("Profile Analysis: {}", login).bold().cyan()
+            ),
+        }
+    }
+
+    fn heading(self, title: &str) -> String {
+        match self {
+            ReportStyle::Plain => format!("\n{}:\n", title),
+            ReportStyle::Term => format!("\n{}\n", title.bold().underline()),
+        }
+    }
+
+    /// A "Label: value" line.
+    fn field(self, label: &str, value: impl std::fmt::Display) -> String {
+        format!("{} {}\n", self.paint(&format!("{}:", label), |s| s.bold()), value)
+    }
+
+    /// `text` with `color` applied in the term style, unchanged otherwise.
+    fn paint(self, text: &str, color: impl FnOnce(&str) -> colored::ColoredString) -> String {
+        match self {
+            ReportStyle::Plain => text.to_string(),
+            ReportStyle::Term => color(text).to_string(),
+        }
+    }
+}
+
+fn format_text(profile: &UserProfile, options: &OutputOptions) -> String {
+    format_report(profile, options, ReportStyle::Plain)
+}
+
+/// Colored variant of the text format for interactive terminals.
+fn format_term(profile: &UserProfile, options: &OutputOptions) -> String {
+    format_report(profile, options, ReportStyle::Term)
+}
+
+fn format_report(profile: &UserProfile, options: &OutputOptions, style: ReportStyle) -> String {
     let mut output = String::new();
 
-    output.push_str(&format!(
-        "\n=== Profile Analysis: {} ===\n\n",
-        profile.user.login
-    ));
+    output.push_str(&style.title(&profile.user.login));
+
+    if let Some(age) = options.cached_age_days {
+        output.push_str(&format!(
+            "{}\n\n",
+            style.paint(&format!("[Cached] Analysis is {} day(s) old", age), |s| s.dimmed())
+        ));
+    }
+    if profile.incomplete {
+        output.push_str(&format!(
+            "{}\n\n",
+            style.paint(
+                "WARNING: Partial analysis (run was interrupted or hit --max-cost)",
+                |s| s.yellow().bold()
+            )
+        ));
+    }
+    if let Some(as_of) = profile.as_of {
+        output.push_str(&format!("{}\n\n", style.paint(&as_of_notice(as_of), |s| s.yellow())));
+    }
 
     if let Some(ref name) = profile.user.name {
-        output.push_str(&format!("Name: {}\n", name));
+        output.push_str(&style.field("Name", name));
     }
     if let Some(ref bio) = profile.user.bio {
-        output.push_str(&format!("Bio: {}\n", bio));
+        output.push_str(&style.field("Bio", style.paint(bio, |s| s.italic())));
     }
 
-    output.push_str(&format!(
-        "Commits analyzed: {}\n",
-        profile.total_commits_analyzed
-    ));
-    output.push_str(&format!(
-        "Repositories: {}\n",
-        profile.repositories.len()
-    ));
-    output.push_str(&format!(
-        "Experience Level: {}\n\n",
-        profile.summary.experience_level
+    // Profiles saved before this was recorded deserialize as zero
+    if profile.summary.overall_confidence > 0.0 {
+        let confidence = profile.summary.overall_confidence * 100.0;
+        output.push_str(&style.field(
+            "Analysis confidence",
+            style.paint(&format!("{:.0}%", confidence), |s| {
+                score_color(s, confidence.round() as u8)
+            }),
+        ));
+    }
+    output.push_str(&style.field(
+        "Commits analyzed",
+        options.locale.integer(profile.total_commits_analyzed as u64),
     ));
+    output.push_str(&style.field("Repositories", profile.repositories.len()));
+    output.push_str(&style.field("Experience Level", &profile.summary.experience_level));
+    if let Some(period) = format_activity_period(profile) {
+        output.push_str(&style.field("Active", period));
+    }
+    if let Some(contributions) = format_contributions(profile) {
+        output.push_str(&style.field("Contributions", contributions));
+    }
+    if !profile.summary.commit_histogram.is_empty() {
+        output.push_str(&style.field(
+            "Monthly commits",
+            style.paint(&sparkline(&profile.summary.commit_histogram), |s| s.cyan()),
+        ));
+    }
+    if let Some(ref timezone) = profile.summary.modal_timezone {
+        output.push_str(&style.field("Timezone", format!("UTC{}", timezone)));
+    }
+    if !profile.summary.working_hours.is_empty() {
+        output.push_str(&style.field(
+            "Working hours",
+            format!(
+                "{} (00-23h {})",
+                style.paint(&sparkline(&profile.summary.working_hours), |s| s.cyan()),
+                working_hours_clock(profile)
+            ),
+        ));
+    }
+    if let Some(coverage) = profile.summary.language_coverage {
+        output.push_str(&style.field(
+            "Language detection coverage",
+            format!("{:.0}%", coverage * 100.0),
+        ));
+    }
 
     // Top Skills
-    output.push_str("Top Skills:\n");
+    output.push_str(&style.heading("Top Skills"));
+    let name_width = profile
+        .skills
+        .iter()
+        .take(10)
+        .map(|s| s.skill.display_name.chars().count())
+        .max()
+        .unwrap_or(0);
     for skill in profile.skills.iter().take(10) {
-        let trend_indicator = match skill.trend {
-            gitanalyzer::models::skill::SkillTrend::Improving => " ↑",
-            gitanalyzer::models::skill::SkillTrend::Declining => " ↓",
-            gitanalyzer::models::skill::SkillTrend::Dormant => " ⏸",
-            _ => "",
-        };
-        output.push_str(&format!(
-            "  - {} ({}): {}/100 (confidence: {:.0}%){}\n",
-            skill.skill.name,
-            skill.skill.category,
-            skill.proficiency_score,
-            skill.confidence * 100.0,
-            trend_indicator
-        ));
+        match style {
+            ReportStyle::Plain => {
+                let trend_indicator = match skill.trend {
+                    gitanalyzer::models::skill::SkillTrend::Improving => " ↑",
+                    gitanalyzer::models::skill::SkillTrend::Declining => " ↓",
+                    gitanalyzer::models::skill::SkillTrend::Dormant => " ⏸",
+                    _ => "",
+                };
+                output.push_str(&format!(
+                    "  - {} ({}): {}/100 [{}]{} across {} repo(s) (confidence: {:.0}%){}\n",
+                    skill.skill.display_name,
+                    skill.skill.category,
+                    skill.proficiency_score,
+                    skill.grade_with(&options.grades),
+                    baseline_delta(options, skill)
+                        .map(|d| format!(" ({})", d))
+                        .unwrap_or_default(),
+                    skill.evidence.repositories.len(),
+                    skill.confidence * 100.0,
+                    trend_indicator
+                ));
+            }
+            ReportStyle::Term => {
+                output.push_str(&format!(
+                    "  {:<width$}  {} {} {} {}  {}\n",
+                    skill.skill.display_name.bold(),
+                    proficiency_bar(skill.proficiency_score),
+                    score_color(&format!("{:>3}", skill.proficiency_score), skill.proficiency_score),
+                    score_color(&skill.grade_with(&options.grades).to_string(), skill.proficiency_score).bold(),
+                    trend_arrow(&skill.trend),
+                    format!(
+                        "{}, {} repo(s), confidence {:.0}%{}",
+                        skill.skill.category,
+                        skill.evidence.repositories.len(),
+                        skill.confidence * 100.0,
+                        baseline_delta(options, skill)
+                            .map(|d| format!(", {}", d))
+                            .unwrap_or_default()
+                    )
+                    .dimmed(),
+                    width = name_width
+                ));
+            }
+        }
+
+        let mut notes = Vec::new();
+        if let Some(repos) = primary_repositories(skill) {
+            notes.push(format!("primarily used in {}", repos));
+        }
+        notes.extend(collaborative_note(skill));
+        notes.extend(gist_note(skill));
+        if options.explain {
+            if let Some(ref b) = skill.breakdown {
+                notes.push(format!(
+                    "frequency {:.0}, recency {:.0}, complexity {:.0}, quality {:.0}, consistency {:.0}, proficiency {:.0}, breadth x{}{}",
+                    b.frequency, b.recency, b.complexity, b.quality, b.consistency, b.proficiency, options.locale.decimal(b.breadth_multiplier as f64, 2), self_reported_note(b)
+                ));
+            }
+        }
+        if options.show_evidence {
+            for snippet in &skill.evidence.snippets {
+                notes.push(format!("> {}", snippet));
+            }
+            if let Some(url) = skill.evidence.last_seen_url() {
+                notes.push(format!("last seen: {}", url));
+            }
+        }
+        for note in notes {
+            output.push_str(&format!("      {}\n", style.paint(&note, |s| s.dimmed())));
+        }
     }
 
-    // Primary Languages
+    // Category Leaders
+    let leaders = top_by_category(&profile.skills);
+    if !leaders.is_empty() {
+        output.push_str(&style.heading("Category Leaders"));
+        for category in SkillCategory::ALL {
+            if let Some(leader) = leaders.get(&category) {
+                let score = leader.proficiency_score;
+                output.push_str(&format!(
+                    "  {}: {} ({})\n",
+                    category,
+                    style.paint(&leader.skill.display_name, |s| s.bold()),
+                    style.paint(&format!("{}/100", score), |s| score_color(s, score))
+                ));
+            }
+        }
+    }
+
+    // Languages and pull requests
+    let mut languages = Vec::new();
     if !profile.summary.primary_languages.is_empty() {
-        output.push_str(&format!(
-            "\nPrimary Languages: {}\n",
-            profile.summary.primary_languages.join(", ")
-        ));
+        languages.push(style.field("Primary Languages", profile.summary.primary_languages.join(", ")));
+    }
+    if let Some(shares) = format_language_shares(&profile.summary.commit_languages) {
+        languages.push(style.field("Languages in commits", shares));
+    }
+    if let Some(shares) = format_language_shares(&profile.summary.repository_languages) {
+        languages.push(style.field("Languages of repositories", shares));
+    }
+    if let Some(metrics) = &profile.summary.process_metrics {
+        languages.push(style.field("Pull requests", format_process_metrics(metrics)));
+    }
+    if !languages.is_empty() {
+        output.push('\n');
+        output.push_str(&languages.concat());
     }
 
     // Strengths
     if !profile.summary.strengths.is_empty() {
-        output.push_str("\nStrengths:\n");
+        output.push_str(&style.heading("Strengths"));
         for strength in &profile.summary.strengths {
-            output.push_str(&format!("  + {}: {}\n", strength.area, strength.description));
+            output.push_str(&format!(
+                "  {} {}: {}\n",
+                style.paint("+", |s| s.green().bold()),
+                style.paint(&strength.area, |s| s.bold()),
+                strength.description
+            ));
         }
     }
 
     // Weaknesses
     if !profile.summary.weaknesses.is_empty() {
-        output.push_str("\nAreas for Improvement:\n");
+        output.push_str(&style.heading("Areas for Improvement"));
         for weakness in &profile.summary.weaknesses {
-            output.push_str(&format!("  - {}: {}\n", weakness.area, weakness.description));
+            output.push_str(&format!(
+                "  {} {}: {}\n",
+                style.paint("-", |s| s.red().bold()),
+                style.paint(&weakness.area, |s| s.bold()),
+                weakness.description
+            ));
+        }
+    }
+
+    // Notable Highlights
+    if !profile.summary.notable_highlights.is_empty() {
+        output.push_str(&style.heading("Notable Highlights"));
+        for highlight in &profile.summary.notable_highlights {
+            output.push_str(&format!("  {} {}\n", style.paint("*", |s| s.cyan()), highlight));
+        }
+    }
+
+    // Recommendations
+    if !profile.summary.recommendations.is_empty() {
+        output.push_str(&style.heading("Recommendations"));
+        for rec in &profile.summary.recommendations {
+            output.push_str(&format!(
+                "  {} {}: {}\n",
+                style.paint("*", |s| s.cyan()),
+                style.paint(&rec.area, |s| s.bold()),
+                rec.suggestion
+            ));
         }
     }
 
     // Coding Style
-    output.push_str("\nCoding Style:\n");
-    output.push_str(&format!(
-        "  Tests: {:.0}%\n",
-        profile.summary.coding_style.writes_tests * 100.0
-    ));
-    output.push_str(&format!(
-        "  Documentation: {:.0}%\n",
-        profile.summary.coding_style.documents_code * 100.0
-    ));
-    output.push_str(&format!(
-        "  Follows Conventions: {:.0}%\n",
-        profile.summary.coding_style.follows_conventions * 100.0
-    ));
+    let coding_style = &profile.summary.coding_style;
+    output.push_str(&style.heading("Coding Style"));
+    for (label, value) in [
+        ("Tests", coding_style.writes_tests),
+        ("Documentation", coding_style.documents_code),
+        ("Follows Conventions", coding_style.follows_conventions),
+    ] {
+        match style {
+            ReportStyle::Plain => {
+                output.push_str(&format!("  {}: {:.0}%\n", label, value * 100.0));
+            }
+            ReportStyle::Term => {
+                let percent = (value * 100.0).round().clamp(0.0, 100.0) as u8;
+                output.push_str(&format!(
+                    "  {:<20} {} {}\n",
+                    label,
+                    proficiency_bar(percent),
+                    score_color(&format!("{:>3}%", percent), percent)
+                ));
+            }
+        }
+    }
 
     output.push_str(&format!(
-        "\nAnalyzed on: {}\n",
-        profile.analysis_date.format("%Y-%m-%d %H:%M:%S UTC")
+        "\n{}\n",
+        style.paint(
+            &format!(
+                "Analyzed on: {} (schema v{})",
+                analysis_date(profile, options),
+                profile.schema_version
+            ),
+            |s| s.dimmed()
+        )
     ));
 
+    if !options.warnings.is_empty() {
+        output.push_str(&format!(
+            "\n{}\n",
+            style.paint(
+                &format!("Warnings ({} item(s) skipped):", options.warnings.len()),
+                |s| s.yellow().bold()
+            )
+        ));
+        for warning in &options.warnings {
+            output.push_str(&format!("  {} {}\n", style.paint("!", |s| s.yellow()), warning));
+        }
+    }
+
     output
 }
 
-fn format_markdown(profile: &UserProfile) -> String {
+fn format_markdown(profile: &UserProfile, options: &OutputOptions) -> String {
     let mut output = String::new();
 
     output.push_str(&format!("# Profile Analysis: {}\n\n", profile.user.login));
 
+    if let Some(age) = options.cached_age_days {
+        output.push_str(&format!("> **Cached:** Analysis is {} day(s) old\n\n", age));
+    }
+
+    if profile.incomplete {
+        output.push_str("> **Warning:** Partial analysis (run was interrupted or hit --max-cost)\n\n");
+    }
+    if let Some(as_of) = profile.as_of {
+        output.push_str(&format!("> **{}\n\n", as_of_notice(as_of).replacen(": ", ":** ", 1)));
+    }
+
     if let Some(ref name) = profile.user.name {
         output.push_str(&format!("**Name:** {}\n\n", name));
     }
@@ -225,9 +1679,15 @@ fn format_markdown(profile: &UserProfile) -> String {
 
     output.push_str("## Summary\n\n");
     output.push_str("| Metric | Value |\n|--------|-------|\n");
+    if profile.summary.overall_confidence > 0.0 {
+        output.push_str(&format!(
+            "| **Analysis Confidence** | **{:.0}%** |\n",
+            profile.summary.overall_confidence * 100.0
+        ));
+    }
     output.push_str(&format!(
         "| Commits Analyzed | {} |\n",
-        profile.total_commits_analyzed
+        options.locale.integer(profile.total_commits_analyzed as u64)
     ));
     output.push_str(&format!(
         "| Repositories | {} |\n",
@@ -238,28 +1698,140 @@ fn format_markdown(profile: &UserProfile) -> String {
         profile.summary.experience_level
     ));
 
+    if let Some(period) = format_activity_period(profile) {
+        output.push_str(&format!("| Active | {} |\n", period));
+    }
+    if let Some(contributions) = format_contributions(profile) {
+        output.push_str(&format!("| Contributions | {} |\n", contributions));
+    }
+    if !profile.summary.commit_histogram.is_empty() {
+        output.push_str(&format!(
+            "| Monthly Commits | {} |\n",
+            sparkline(&profile.summary.commit_histogram)
+        ));
+    }
+    if let Some(ref timezone) = profile.summary.modal_timezone {
+        output.push_str(&format!("| Timezone | UTC{} |\n", timezone));
+    }
+    if !profile.summary.working_hours.is_empty() {
+        output.push_str(&format!(
+            "| Working Hours | {} (00-23h {}) |\n",
+            sparkline(&profile.summary.working_hours),
+            working_hours_clock(profile)
+        ));
+    }
+    if let Some(coverage) = profile.summary.language_coverage {
+        output.push_str(&format!(
+            "| Language Detection Coverage | {:.0}% |\n",
+            coverage * 100.0
+        ));
+    }
+
     if !profile.summary.primary_languages.is_empty() {
         output.push_str(&format!(
             "| Primary Languages | {} |\n",
             profile.summary.primary_languages.join(", ")
         ));
     }
+    if let Some(languages) = format_language_shares(&profile.summary.commit_languages) {
+        output.push_str(&format!("| Languages in Commits | {} |\n", languages));
+    }
+    if let Some(languages) = format_language_shares(&profile.summary.repository_languages) {
+        output.push_str(&format!("| Languages of Repositories | {} |\n", languages));
+    }
+    if let Some(metrics) = &profile.summary.process_metrics {
+        output.push_str(&format!("| Pull Requests | {} |\n", format_process_metrics(metrics)));
+    }
 
     output.push_str("\n## Top Skills\n\n");
-    output.push_str("| Skill | Category | Score | Confidence | Trend |\n");
-    output.push_str("|-------|----------|-------|------------|-------|\n");
+    let with_baseline = options.calibration.is_some();
+    output.push_str("| Skill | Category | Score | Grade |");
+    output.push_str(if with_baseline { " vs Baseline |" } else { "" });
+    output.push_str(" Repos | Primarily Used In | Confidence | Trend |\n");
+    output.push_str("|-------|----------|-------|-------|");
+    output.push_str(if with_baseline { "-------------|" } else { "" });
+    output.push_str("-------|-------------------|------------|-------|\n");
 
     for skill in profile.skills.iter().take(15) {
         output.push_str(&format!(
-            "| {} | {} | {}/100 | {:.0}% | {} |\n",
-            skill.skill.name,
+            "| {} | {} | {}/100 | {} |{} {} | {} | {:.0}% | {} |\n",
+            skill.skill.display_name,
             skill.skill.category,
             skill.proficiency_score,
+            skill.grade_with(&options.grades),
+            baseline_delta(options, skill)
+                .map(|d| format!(" {} |", d))
+                .unwrap_or_default(),
+            skill.evidence.repositories.len(),
+            primary_repositories(skill).unwrap_or_default(),
             skill.confidence * 100.0,
             skill.trend
         ));
     }
 
+    if options.explain {
+        let with_breakdown: Vec<_> = profile
+            .skills
+            .iter()
+            .take(15)
+            .filter_map(|s| s.breakdown.as_ref().map(|b| (s, b)))
+            .collect();
+        if !with_breakdown.is_empty() {
+            output.push_str("\n## Score Breakdown\n\n");
+            output.push_str("| Skill | Score | Frequency | Recency | Complexity | Quality | Consistency | Proficiency | Breadth |\n");
+            output.push_str("|-------|-------|-----------|---------|------------|---------|-------------|-------------|---------|\n");
+            for (skill, b) in with_breakdown {
+                output.push_str(&format!(
+                    "| {} | {} | {:.0} | {:.0} | {:.0} | {:.0} | {:.0} | {:.0} | x{} |\n",
+                    skill.skill.display_name,
+                    skill.proficiency_score,
+                    b.frequency,
+                    b.recency,
+                    b.complexity,
+                    b.quality,
+                    b.consistency,
+                    b.proficiency,
+                    options.locale.decimal(b.breadth_multiplier as f64, 2)
+                ));
+            }
+        }
+    }
+
+    if options.show_evidence {
+        let with_evidence: Vec<_> = profile
+            .skills
+            .iter()
+            .take(15)
+            .filter(|s| !s.evidence.snippets.is_empty() || s.evidence.last_seen_url().is_some())
+            .collect();
+        if !with_evidence.is_empty() {
+            output.push_str("\n## Skill Evidence\n");
+            for skill in with_evidence {
+                output.push_str(&format!("\n### {}\n\n", skill.skill.display_name));
+                for snippet in &skill.evidence.snippets {
+                    output.push_str(&format!("- {}\n", snippet));
+                }
+                if let Some(url) = skill.evidence.last_seen_url() {
+                    output.push_str(&format!("- Last seen: <{}>\n", url));
+                }
+            }
+        }
+    }
+
+    let leaders = top_by_category(&profile.skills);
+    if !leaders.is_empty() {
+        output.push_str("\n## Category Leaders\n\n");
+        output.push_str("| Category | Skill | Score |\n|----------|-------|-------|\n");
+        for category in SkillCategory::ALL {
+            if let Some(leader) = leaders.get(&category) {
+                output.push_str(&format!(
+                    "| {} | {} | {}/100 |\n",
+                    category, leader.skill.display_name, leader.proficiency_score
+                ));
+            }
+        }
+    }
+
     if !profile.summary.strengths.is_empty() {
         output.push_str("\n## Strengths\n\n");
         for strength in &profile.summary.strengths {
@@ -280,6 +1852,20 @@ fn format_markdown(profile: &UserProfile) -> String {
         }
     }
 
+    if !profile.summary.notable_highlights.is_empty() {
+        output.push_str("\n## Notable Highlights\n\n");
+        for highlight in &profile.summary.notable_highlights {
+            output.push_str(&format!("- {}\n", highlight));
+        }
+    }
+
+    if !profile.summary.recommendations.is_empty() {
+        output.push_str("\n## Recommendations\n\n");
+        for rec in &profile.summary.recommendations {
+            output.push_str(&format!("- **{}**: {}\n", rec.area, rec.suggestion));
+        }
+    }
+
     output.push_str("\n## Coding Style\n\n");
     output.push_str("| Metric | Score |\n|--------|-------|\n");
     output.push_str(&format!(
@@ -295,9 +1881,17 @@ fn format_markdown(profile: &UserProfile) -> String {
         profile.summary.coding_style.follows_conventions * 100.0
     ));
 
+    if !options.warnings.is_empty() {
+        output.push_str("\n## Warnings\n\n");
+        for warning in &options.warnings {
+            output.push_str(&format!("- {}\n", warning));
+        }
+    }
+
     output.push_str(&format!(
-        "\n---\n*Analyzed on {}*\n",
-        profile.analysis_date.format("%Y-%m-%d %H:%M:%S UTC")
+        "\n---\n*Analyzed on {} (schema v{})*\n",
+        analysis_date(profile, options),
+        profile.schema_version
     ));
 
     output
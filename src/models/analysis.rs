@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use super::skill::SkillDomain;
 
@@ -9,6 +11,70 @@ pub struct ProfileSummary {
     pub weaknesses: Vec<StrengthWeakness>,
     pub experience_level: ExperienceLevel,
     pub coding_style: CodingStyle,
+    #[serde(default)]
+    pub recommendations: Vec<Recommendation>,
+    /// Most frequent distinct LLM observations and notable aspects
+    #[serde(default)]
+    pub notable_highlights: Vec<String>,
+    /// Date of the earliest analyzed commit
+    #[serde(default)]
+    pub activity_start: Option<DateTime<Utc>>,
+    /// Calendar year with the most analyzed commits
+    #[serde(default)]
+    pub activity_peak_year: Option<i32>,
+    /// Date of the most recent analyzed commit
+    #[serde(default)]
+    pub activity_end: Option<DateTime<Utc>>,
+    /// Analyzed commits per calendar month, from `activity_start` to `activity_end`
+    #[serde(default)]
+    pub commit_histogram: Vec<u32>,
+    /// Fraction of changed files whose language was recognized (0.0-1.0)
+    #[serde(default)]
+    pub language_coverage: Option<f32>,
+    /// How far the profile as a whole can be trusted (0.0-1.0), from commits
+    /// analyzed, repository diversity and the LLM batch success rate
+    #[serde(default)]
+    pub overall_confidence: f32,
+    /// Share (0.0-1.0) of analyzed commits of each contribution type
+    /// ("feature", "fix", "refactor", "docs", "test", "chore", "other"),
+    /// classified from commit messages
+    #[serde(default)]
+    pub contribution_breakdown: HashMap<String, f32>,
+    /// UTC offset most analyzed commits were authored in, e.g. "+02:00";
+    /// only known with `PipelineConfig::author_timezones`
+    #[serde(default)]
+    pub modal_timezone: Option<String>,
+    /// Analyzed commits per hour of the day (24 bins, 0 = midnight), in the
+    /// author's local time when `modal_timezone` is known and in UTC otherwise
+    #[serde(default)]
+    pub working_hours: Vec<u32>,
+    /// Share (0.0-1.0) of the user's changed lines in each language, from
+    /// their own commits
+    #[serde(default)]
+    pub commit_languages: HashMap<String, f32>,
+    /// Share (0.0-1.0) of analyzed repositories with each primary language,
+    /// from GitHub's repository-wide statistics
+    #[serde(default)]
+    pub repository_languages: HashMap<String, f32>,
+    /// Pull request activity; only collected with `--include-prs`
+    #[serde(default)]
+    pub process_metrics: Option<ProcessMetrics>,
+}
+
+/// Collaboration signals from the user's recent pull requests.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ProcessMetrics {
+    /// Pull requests looked at, most recent first
+    pub pull_requests: u32,
+    /// Share (0.0-1.0) of closed pull requests that were merged; `None`
+    /// while none are closed
+    pub merge_rate: Option<f32>,
+    /// Mean lines changed (additions plus deletions) per pull request whose
+    /// size is known
+    pub average_size: Option<f32>,
+    /// Share (0.0-1.0) of pull requests from branches named by a branching
+    /// model, such as `feature/...` or `fix/...`
+    pub prefixed_branch_rate: Option<f32>,
 }
 
 impl Default for ProfileSummary {
@@ -20,10 +86,32 @@ impl Default for ProfileSummary {
             weaknesses: Vec::new(),
             experience_level: ExperienceLevel::Mid,
             coding_style: CodingStyle::default(),
+            recommendations: Vec::new(),
+            notable_highlights: Vec::new(),
+            activity_start: None,
+            activity_peak_year: None,
+            activity_end: None,
+            commit_histogram: Vec::new(),
+            language_coverage: None,
+            overall_confidence: 0.0,
+            contribution_breakdown: HashMap::new(),
+            modal_timezone: None,
+            working_hours: Vec::new(),
+            commit_languages: HashMap::new(),
+            repository_languages: HashMap::new(),
+            process_metrics: None,
         }
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recommendation {
+    pub area: String,
+    pub suggestion: String,
+    /// 1 = highest priority
+    pub priority: u8,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StrengthWeakness {
     pub area: String,
@@ -41,6 +129,36 @@ pub enum ExperienceLevel {
     Principal,
 }
 
+impl ExperienceLevel {
+    pub const ALL: [ExperienceLevel; 5] = [
+        ExperienceLevel::Junior,
+        ExperienceLevel::Mid,
+        ExperienceLevel::Senior,
+        ExperienceLevel::Staff,
+        ExperienceLevel::Principal,
+    ];
+}
+
+impl std::str::FromStr for ExperienceLevel {
+    type Err = String;
+
+    /// Accepts the display name ("Mid-Level") as well as the variant name ("Mid").
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        ExperienceLevel::ALL
+            .into_iter()
+            .find(|l| {
+                l.to_string().eq_ignore_ascii_case(s) || format!("{:?}", l).eq_ignore_ascii_case(s)
+            })
+            .ok_or_else(|| {
+                format!(
+                    "unknown experience level '{}' (expected one of: junior, mid, senior, staff, principal)",
+                    s
+                )
+            })
+    }
+}
+
 impl std::fmt::Display for ExperienceLevel {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -84,6 +202,18 @@ pub struct LLMAnalysisResult {
     pub notable_aspects: Vec<String>,
 }
 
+impl LLMAnalysisResult {
+    /// Replaces anything that looks like an email address in the model's
+    /// free-text fields with `[redacted]`.
+    pub fn redact(&mut self) {
+        let redact = |text: &mut String| *text = super::user::redact_emails(text);
+        self.skills.iter_mut().flat_map(|s| &mut s.evidence).for_each(redact);
+        redact(&mut self.complexity_assessment.reasoning);
+        self.quality_assessment.observations.iter_mut().for_each(redact);
+        self.notable_aspects.iter_mut().for_each(redact);
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExtractedSkill {
     pub name: String,
@@ -141,3 +271,38 @@ impl Default for QualityAssessment {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_experience_level_display_round_trips() {
+        for level in ExperienceLevel::ALL {
+            assert_eq!(level.to_string().parse::<ExperienceLevel>(), Ok(level));
+        }
+        assert_eq!("mid".parse::<ExperienceLevel>(), Ok(ExperienceLevel::Mid));
+        assert!("Intern".parse::<ExperienceLevel>().is_err());
+    }
+
+    #[test]
+    fn test_redact_analysis() {
+        let mut analysis = LLMAnalysisResult {
+            skills: vec![ExtractedSkill {
+                name: "Rust".to_string(),
+                category: "language".to_string(),
+                proficiency_level: "advanced".to_string(),
+                confidence: 0.9,
+                evidence: vec!["Reviewed by jane@example.com".to_string()],
+            }],
+            patterns: Vec::new(),
+            complexity_assessment: ComplexityAssessment::default(),
+            quality_assessment: QualityAssessment::default(),
+            domain_signals: Vec::new(),
+            notable_aspects: vec!["Pairs with <bob@example.org>".to_string()],
+        };
+        analysis.redact();
+        assert_eq!(analysis.skills[0].evidence, vec!["Reviewed by [redacted]"]);
+        assert_eq!(analysis.notable_aspects, vec!["Pairs with <[redacted]>"]);
+    }
+}
@@ -1,6 +1,8 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, FixedOffset, Utc};
 use serde::{Deserialize, Serialize};
 
+use super::user::is_bot_login;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommitSummary {
     pub sha: String,
@@ -14,11 +16,55 @@ pub struct CommitDetails {
     pub author: CommitAuthor,
 }
 
+impl CommitSummary {
+    /// Whether the commit was authored by a bot (see `is_bot_login`). Falls
+    /// back to the git author name when GitHub could not link the commit to
+    /// an account, since bots such as Dependabot commit as `dependabot[bot]`.
+    pub fn is_bot_authored(&self) -> bool {
+        match &self.author {
+            Some(author) => is_bot_login(&author.login),
+            None => is_bot_login(&self.commit.author.name),
+        }
+    }
+}
+
+impl CommitDetails {
+    /// People credited with `Co-authored-by:` trailers in the message, in the
+    /// order they appear. Trailers without an `<email>` are skipped.
+    pub fn co_authors(&self) -> Vec<CoAuthor> {
+        self.message
+            .lines()
+            .filter_map(|line| {
+                let (key, value) = line.trim().split_once(':')?;
+                if !key.trim().eq_ignore_ascii_case("co-authored-by") {
+                    return None;
+                }
+                let (name, rest) = value.split_once('<')?;
+                let (email, _) = rest.split_once('>')?;
+                Some(CoAuthor {
+                    name: name.trim().to_string(),
+                    email: email.trim().to_string(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// A co-author named in a `Co-authored-by: Name <email>` trailer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoAuthor {
+    pub name: String,
+    pub email: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CommitAuthor {
     pub name: String,
     pub email: String,
-    pub date: DateTime<Utc>,
+    /// Author date. GitHub's JSON reports it in UTC; the author's own offset
+    /// is filled in by `GitHubClient::localize_author_dates`. Convert with
+    /// `with_timezone(&Utc)` before doing date math.
+    pub date: DateTime<FixedOffset>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -50,6 +96,14 @@ pub struct FileChange {
     pub patch: Option<String>,
 }
 
+impl FileChange {
+    /// GitHub omits `patch` for binary files (and for diffs too large to render),
+    /// so a missing patch is treated as a non-text change.
+    pub fn is_binary(&self) -> bool {
+        self.patch.is_none()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum FileStatus {
     Added,
@@ -79,7 +133,13 @@ pub struct CommitForAnalysis {
     pub message: String,
     pub stats: CommitStats,
     pub files_changed: Vec<FileForAnalysis>,
+    /// Files changed without a textual patch (images, compiled artifacts, etc.)
+    #[serde(default)]
+    pub binary_files_changed: u32,
     pub committed_at: DateTime<Utc>,
+    /// Commit carries `Co-authored-by:` trailers, e.g. from pair programming
+    #[serde(default)]
+    pub collaborative: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -90,3 +150,40 @@ pub struct FileForAnalysis {
     pub additions: u32,
     pub deletions: u32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_co_authors_from_trailers() {
+        let details = CommitDetails {
+            message: "Add retry to the uploader\n\n\
+                      Pairing session.\n\n\
+                      Co-authored-by: Mona Lisa <mona@example.com>\n\
+                      co-authored-by:  Octo Cat  <12345+octocat@users.noreply.github.com> \n\
+                      Co-Authored-By: No Email\n\
+                      Signed-off-by: Hubot <hubot@example.com>"
+                .to_string(),
+            author: CommitAuthor {
+                name: "Hubot".to_string(),
+                email: "hubot@example.com".to_string(),
+                date: Utc::now().into(),
+            },
+        };
+
+        assert_eq!(
+            details.co_authors(),
+            vec![
+                CoAuthor {
+                    name: "Mona Lisa".to_string(),
+                    email: "mona@example.com".to_string(),
+                },
+                CoAuthor {
+                    name: "Octo Cat".to_string(),
+                    email: "12345+octocat@users.noreply.github.com".to_string(),
+                },
+            ]
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SkillRating {
@@ -10,17 +10,129 @@ pub struct SkillRating {
     pub confidence: f32,
     pub evidence: SkillEvidence,
     pub trend: SkillTrend,
+    /// Component scores behind `proficiency_score`, when the scoring strategy provides them
+    #[serde(default)]
+    pub breakdown: Option<ScoreBreakdown>,
+    /// Score per calendar quarter the skill was used in, oldest first, keyed
+    /// by the quarter's start
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trend_series: Vec<(DateTime<Utc>, u8)>,
+}
+
+impl SkillRating {
+    /// Masks email addresses in the evidence snippets, keeping the score.
+    pub fn redact(&mut self) {
+        for snippet in &mut self.evidence.snippets {
+            *snippet = super::user::redact_emails(snippet);
+        }
+    }
+
+    /// Letter grade with the default thresholds; see `grade_with`.
+    pub fn grade(&self) -> char {
+        self.grade_with(&GradeThresholds::default())
+    }
+
+    /// Letter grade (A-D) for the percentile rank, or for the proficiency
+    /// score when no percentile is known.
+    pub fn grade_with(&self, thresholds: &GradeThresholds) -> char {
+        let value = self.percentile_rank.unwrap_or(self.proficiency_score);
+        if value >= thresholds.a {
+            'A'
+        } else if value >= thresholds.b {
+            'B'
+        } else if value >= thresholds.c {
+            'C'
+        } else {
+            'D'
+        }
+    }
+}
+
+/// Lowest value (0-100) that earns each letter grade; anything below `c` is a D.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GradeThresholds {
+    pub a: u8,
+    pub b: u8,
+    pub c: u8,
+}
+
+impl Default for GradeThresholds {
+    fn default() -> Self {
+        Self { a: 80, b: 65, c: 50 }
+    }
+}
+
+impl std::str::FromStr for GradeThresholds {
+    type Err = String;
+
+    /// Parses `A,B,C`, e.g. `80,65,50`. Each must be within 0-100 and lower
+    /// than the one before.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid grade thresholds '{}' (expected A,B,C, e.g. 80,65,50)", s);
+        let values = s
+            .split(',')
+            .map(|v| v.trim().parse::<u8>().ok().filter(|v| *v <= 100))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(invalid)?;
+        match values[..] {
+            [a, b, c] if a > b && b > c => Ok(Self { a, b, c }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// Unweighted component scores (0-100) that make up a skill's rating.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScoreBreakdown {
+    pub frequency: f32,
+    pub recency: f32,
+    pub complexity: f32,
+    pub quality: f32,
+    pub consistency: f32,
+    pub proficiency: f32,
+    /// Repository breadth adjustment applied to the weighted sum
+    pub breadth_multiplier: f32,
+    /// Points added for skills the user states in their bio or repository
+    /// descriptions
+    #[serde(default)]
+    pub self_reported_bonus: f32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Skill {
+    /// Stable identifier from the taxonomy; unlike `name`, it survives
+    /// renames, so stored history is keyed by it
     pub id: String,
+    /// Lowercase name skills are matched and filtered by, which may change
+    /// between versions
     pub name: String,
+    /// Properly cased name shown in reports, e.g. "PostgreSQL"
+    #[serde(default)]
+    pub display_name: String,
     pub category: SkillCategory,
     pub subcategory: Option<String>,
     pub aliases: Vec<String>,
 }
 
+/// Display name for a skill the taxonomy doesn't know: `name` unchanged when
+/// it already has capitals ("gRPC"), otherwise with each word capitalized
+/// ("event sourcing" becomes "Event Sourcing").
+pub fn fallback_display_name(name: &str) -> String {
+    if name.chars().any(char::is_uppercase) {
+        return name.to_string();
+    }
+    name.split(' ')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum SkillCategory {
     Language,
@@ -32,6 +144,34 @@ pub enum SkillCategory {
     Concept,
 }
 
+impl SkillCategory {
+    pub const ALL: [SkillCategory; 7] = [
+        SkillCategory::Language,
+        SkillCategory::Framework,
+        SkillCategory::Library,
+        SkillCategory::Tool,
+        SkillCategory::Domain,
+        SkillCategory::Practice,
+        SkillCategory::Concept,
+    ];
+}
+
+impl std::str::FromStr for SkillCategory {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SkillCategory::ALL
+            .into_iter()
+            .find(|c| c.to_string().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| {
+                format!(
+                    "unknown skill category '{}' (expected one of: language, framework, library, tool, domain, practice, concept)",
+                    s
+                )
+            })
+    }
+}
+
 impl std::fmt::Display for SkillCategory {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -62,13 +202,64 @@ pub enum SkillDomain {
     SystemsProgramming,
 }
 
+impl std::fmt::Display for SkillDomain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkillDomain::Frontend => write!(f, "Frontend"),
+            SkillDomain::Backend => write!(f, "Backend"),
+            SkillDomain::FullStack => write!(f, "Full Stack"),
+            SkillDomain::Mobile => write!(f, "Mobile"),
+            SkillDomain::DevOps => write!(f, "DevOps"),
+            SkillDomain::DataScience => write!(f, "Data Science"),
+            SkillDomain::MachineLearning => write!(f, "Machine Learning"),
+            SkillDomain::Security => write!(f, "Security"),
+            SkillDomain::Database => write!(f, "Database"),
+            SkillDomain::Cloud => write!(f, "Cloud"),
+            SkillDomain::Embedded => write!(f, "Embedded"),
+            SkillDomain::SystemsProgramming => write!(f, "Systems Programming"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SkillEvidence {
     pub commit_count: u32,
     pub total_lines_changed: u32,
     pub first_seen: DateTime<Utc>,
     pub last_seen: DateTime<Utc>,
+    /// Repositories the skill was seen in, most used first
     pub repositories: Vec<String>,
+    /// Highest-confidence evidence strings reported by the LLM
+    #[serde(default)]
+    pub snippets: Vec<String>,
+    /// How many of `commit_count` were co-authored (`Co-authored-by:`)
+    #[serde(default)]
+    pub collaborative_commits: u32,
+    /// How many of `commit_count` were gists analyzed with `--include-gists`
+    /// rather than repository commits
+    #[serde(default)]
+    pub gist_commits: u32,
+    /// Repository of the most recent commit showing the skill
+    #[serde(default)]
+    pub last_seen_repository: Option<String>,
+    /// SHA of the most recent commit showing the skill
+    #[serde(default)]
+    pub last_seen_commit: Option<String>,
+}
+
+impl SkillEvidence {
+    /// Web link to the most recent commit showing the skill. Gists link to
+    /// the gist itself, since their revisions are not commits.
+    pub fn last_seen_url(&self) -> Option<String> {
+        let repository = self.last_seen_repository.as_deref()?;
+        match repository.strip_prefix(super::user::GIST_REPOSITORY_PREFIX) {
+            Some(id) => Some(format!("https://gist.github.com/{}", id)),
+            None => {
+                let sha = self.last_seen_commit.as_deref()?;
+                Some(format!("https://github.com/{}/commit/{}", repository, sha))
+            }
+        }
+    }
 }
 
 impl Default for SkillEvidence {
@@ -79,6 +270,11 @@ impl Default for SkillEvidence {
             first_seen: Utc::now(),
             last_seen: Utc::now(),
             repositories: Vec::new(),
+            snippets: Vec::new(),
+            collaborative_commits: 0,
+            gist_commits: 0,
+            last_seen_repository: None,
+            last_seen_commit: None,
         }
     }
 }
@@ -92,6 +288,32 @@ pub enum SkillTrend {
     Dormant,
 }
 
+impl SkillTrend {
+    pub const ALL: [SkillTrend; 5] = [
+        SkillTrend::Improving,
+        SkillTrend::Stable,
+        SkillTrend::Declining,
+        SkillTrend::New,
+        SkillTrend::Dormant,
+    ];
+}
+
+impl std::str::FromStr for SkillTrend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SkillTrend::ALL
+            .into_iter()
+            .find(|t| t.to_string().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| {
+                format!(
+                    "unknown skill trend '{}' (expected one of: improving, stable, declining, new, dormant)",
+                    s
+                )
+            })
+    }
+}
+
 impl std::fmt::Display for SkillTrend {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -113,15 +335,35 @@ pub struct SkillOccurrence {
     pub proficiency_signal: String,
     pub confidence: f32,
     pub lines_changed: u32,
+    /// Seen in a commit with co-authors
+    pub collaborative: bool,
+    /// How much the occurrence counts towards frequency, commit count and
+    /// rating confidence: 1.0, or less for commits that say little about
+    /// skill, such as dependency bumps
+    pub weight: f32,
+}
+
+impl SkillOccurrence {
+    /// Seen in a gist's pseudo-commit rather than a repository
+    pub fn from_gist(&self) -> bool {
+        self.repository.starts_with(super::user::GIST_REPOSITORY_PREFIX)
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct AggregatedSkill {
     pub skill: Skill,
+    /// LLM-reported occurrences come first, in the same order as
+    /// `complexity_scores` and `quality_scores`; occurrences inferred from
+    /// imports follow and have no scores.
     pub occurrences: Vec<SkillOccurrence>,
     pub total_lines: u32,
     pub complexity_scores: Vec<f32>,
     pub quality_scores: Vec<f32>,
+    /// Mentions of the skill in the user's bio or repository descriptions.
+    /// Kept apart from `occurrences` so they count towards none of the
+    /// commit-based components; scoring may only nudge the rating with them.
+    pub self_reported: Vec<SkillOccurrence>,
 }
 
 impl AggregatedSkill {
@@ -132,15 +374,129 @@ impl AggregatedSkill {
             total_lines: 0,
             complexity_scores: Vec::new(),
             quality_scores: Vec::new(),
+            self_reported: Vec::new(),
         }
     }
 
-    pub fn repositories(&self) -> Vec<String> {
-        self.occurrences
-            .iter()
-            .map(|o| o.repository.clone())
-            .collect::<HashSet<_>>()
+    /// Number of commits the skill was seen in, each counted by its
+    /// occurrence's `weight`.
+    pub fn weighted_commits(&self) -> f32 {
+        self.occurrences.iter().map(|o| o.weight).sum()
+    }
+
+    /// Distinct repositories the skill was seen in with their occurrence
+    /// counts, most used first. Ties go to the repository used most recently,
+    /// then by name.
+    pub fn repositories(&self) -> Vec<(String, usize)> {
+        let mut usage: HashMap<&str, (usize, DateTime<Utc>)> = HashMap::new();
+        for occurrence in &self.occurrences {
+            let entry = usage
+                .entry(occurrence.repository.as_str())
+                .or_insert((0, occurrence.timestamp));
+            entry.0 += 1;
+            entry.1 = entry.1.max(occurrence.timestamp);
+        }
+
+        let mut repos: Vec<_> = usage.into_iter().collect();
+        repos.sort_by(|(a_name, (a_count, a_last)), (b_name, (b_count, b_last))| {
+            b_count
+                .cmp(a_count)
+                .then_with(|| b_last.cmp(a_last))
+                .then_with(|| a_name.cmp(b_name))
+        });
+        repos
             .into_iter()
+            .map(|(name, (count, _))| (name.to_string(), count))
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{aggregated_skill, skill_rating};
+
+    #[test]
+    fn test_enum_display_round_trips() {
+        for category in SkillCategory::ALL {
+            assert_eq!(category.to_string().parse::<SkillCategory>(), Ok(category));
+        }
+        for trend in SkillTrend::ALL {
+            assert_eq!(trend.to_string().parse::<SkillTrend>(), Ok(trend));
+        }
+        assert_eq!("tool".parse::<SkillCategory>(), Ok(SkillCategory::Tool));
+        assert!("Gadget".parse::<SkillCategory>().is_err());
+        assert!("Sleeping".parse::<SkillTrend>().is_err());
+        assert!("".parse::<SkillTrend>().is_err());
+    }
+
+    #[test]
+    fn test_grade_uses_percentile_when_known() {
+        let mut rating = skill_rating("Rust", 70);
+        assert_eq!(rating.grade(), 'B');
+        rating.percentile_rank = Some(92);
+        assert_eq!(rating.grade(), 'A');
+        rating.percentile_rank = Some(10);
+        assert_eq!(rating.grade(), 'D');
+
+        let strict: GradeThresholds = "95, 90, 85".parse().unwrap();
+        rating.percentile_rank = Some(92);
+        assert_eq!(rating.grade_with(&strict), 'B');
+        assert!("80,80,50".parse::<GradeThresholds>().is_err());
+        assert!("80,65".parse::<GradeThresholds>().is_err());
+        assert!("120,65,50".parse::<GradeThresholds>().is_err());
+    }
+
+    #[test]
+    fn test_last_seen_url_links_commit_or_gist() {
+        let evidence = |repository: &str, commit: Option<&str>| SkillEvidence {
+            last_seen_repository: Some(repository.to_string()),
+            last_seen_commit: commit.map(str::to_string),
+            ..SkillEvidence::default()
+        };
+
+        assert_eq!(
+            evidence("octocat/hello", Some("sha5")).last_seen_url().as_deref(),
+            Some("https://github.com/octocat/hello/commit/sha5")
+        );
+        assert_eq!(
+            evidence("gist:abc123", Some("rev1")).last_seen_url().as_deref(),
+            Some("https://gist.github.com/abc123")
+        );
+        assert_eq!(evidence("octocat/hello", None).last_seen_url(), None);
+        assert_eq!(SkillEvidence::default().last_seen_url(), None);
+    }
+
+    #[test]
+    fn test_repositories_ranked_by_use() {
+        let now = Utc::now();
+        let occurrence = |repository: &str, days_ago: i64| SkillOccurrence {
+            commit_sha: String::new(),
+            repository: repository.to_string(),
+            timestamp: now - chrono::Duration::days(days_ago),
+            evidence: Vec::new(),
+            proficiency_signal: "intermediate".to_string(),
+            confidence: 0.5,
+            lines_changed: 10,
+            collaborative: false,
+            weight: 1.0,
+        };
+
+        let mut agg = aggregated_skill("rust", SkillCategory::Language);
+        agg.occurrences = vec![
+            occurrence("octocat/a-old", 300),
+            occurrence("octocat/busy", 90),
+            occurrence("octocat/busy", 80),
+            occurrence("octocat/z-new", 5),
+        ];
+
+        assert_eq!(
+            agg.repositories(),
+            vec![
+                ("octocat/busy".to_string(), 2),
+                ("octocat/z-new".to_string(), 1),
+                ("octocat/a-old".to_string(), 1),
+            ]
+        );
+    }
+}
@@ -1,7 +1,9 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use super::skill::SkillRating;
-use super::analysis::ProfileSummary;
+use std::collections::BTreeMap;
+use super::commit::{Commit, CommitAuthor, CommitDetails, CommitStats, FileChange};
+use super::skill::{fallback_display_name, SkillCategory, SkillDomain, SkillRating};
+use super::analysis::{ExperienceLevel, ProfileSummary};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GitHubUser {
@@ -17,6 +19,45 @@ pub struct GitHubUser {
     pub followers: u32,
     pub following: u32,
     pub created_at: DateTime<Utc>,
+    /// Whether the account is a person or an organization; the API serves
+    /// both from `/users/{login}`
+    #[serde(rename = "type", default)]
+    pub account_type: AccountType,
+}
+
+/// Kind of GitHub account, as reported in the API's `type` field.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AccountType {
+    #[default]
+    User,
+    Organization,
+    Bot,
+    /// Any type this version does not know, e.g. "Mannequin" for
+    /// placeholder accounts left by migrations; treated like a user
+    #[serde(other)]
+    Other,
+}
+
+/// Automation accounts that commit without a `[bot]` suffix on their login.
+const KNOWN_BOT_LOGINS: &[&str] = &[
+    "dependabot",
+    "dependabot-preview",
+    "renovate",
+    "renovate-bot",
+    "github-actions",
+    "greenkeeper",
+    "snyk-bot",
+    "pre-commit-ci",
+    "imgbot",
+    "allcontributors",
+];
+
+/// Whether `login` belongs to an automation account: GitHub App logins end
+/// in `[bot]` (e.g. `dependabot[bot]`), and a few well-known bots commit
+/// under plain logins. Case is ignored.
+pub fn is_bot_login(login: &str) -> bool {
+    let login = login.trim().to_lowercase();
+    login.ends_with("[bot]") || KNOWN_BOT_LOGINS.contains(&login.as_str())
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,14 +80,311 @@ pub struct RepositoryOwner {
     pub login: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserEvent {
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub repo: EventRepository,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventRepository {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    pub created_at: DateTime<Utc>,
+    /// `None` for drafts
+    pub published_at: Option<DateTime<Utc>>,
+}
+
+impl Release {
+    /// When the release went out: its publication date, or its creation date
+    /// if it was never published.
+    pub fn date(&self) -> DateTime<Utc> {
+        self.published_at.unwrap_or(self.created_at)
+    }
+}
+
+/// A pull request authored by the user, as returned by the issue search API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PullRequest {
+    pub number: u64,
+    pub title: String,
+    /// API URL of the target repository, e.g. `https://api.github.com/repos/octocat/hello`
+    pub repository_url: String,
+    pub created_at: DateTime<Utc>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub pull_request: PullRequestState,
+    /// Size and branch, which search results leave out; filled in from the
+    /// pull request itself when it could be fetched
+    #[serde(default)]
+    pub details: Option<PullRequestDetails>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PullRequestState {
+    pub merged_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PullRequestDetails {
+    pub additions: u32,
+    pub deletions: u32,
+    pub head: PullRequestHead,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PullRequestHead {
+    /// Name of the branch the changes come from
+    #[serde(rename = "ref")]
+    pub branch: String,
+}
+
+impl PullRequest {
+    /// Owner and name of the target repository.
+    pub fn repository(&self) -> Option<(&str, &str)> {
+        let (_, full_name) = self.repository_url.rsplit_once("/repos/")?;
+        full_name.split_once('/')
+    }
+
+    pub fn is_merged(&self) -> bool {
+        self.pull_request.merged_at.is_some()
+    }
+}
+
+/// Prefix of the pseudo-repository name given to gist-derived commits, e.g.
+/// `gist:aa5a315d61ae9438b18d`. Real repository names never contain `:`.
+pub const GIST_REPOSITORY_PREFIX: &str = "gist:";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Gist {
+    pub id: String,
+    pub description: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Keyed by filename
+    pub files: BTreeMap<String, GistFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GistFile {
+    pub filename: String,
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Only present when the gist is fetched on its own, not in listings
+    #[serde(default)]
+    pub content: Option<String>,
+}
+
+impl Gist {
+    /// The gist as a single commit adding all of its files, in a
+    /// pseudo-repository named with `GIST_REPOSITORY_PREFIX`. Gists have no
+    /// useful history, so only the latest snapshot is analyzed; each file's
+    /// content becomes an all-added patch.
+    pub fn to_commit(&self, owner: &str) -> (Repository, Commit) {
+        let files: Vec<_> = self
+            .files
+            .values()
+            .map(|file| {
+                let patch = file.content.as_ref().map(|content| {
+                    content
+                        .lines()
+                        .map(|line| format!("+{}", line))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                });
+                FileChange {
+                    filename: file.filename.clone(),
+                    status: "added".to_string(),
+                    additions: file.content.as_deref().map_or(0, |c| c.lines().count() as u32),
+                    deletions: 0,
+                    patch,
+                }
+            })
+            .collect();
+        let additions = files.iter().map(|f| f.additions).sum();
+
+        let repository = Repository {
+            id: 0,
+            name: self.id.clone(),
+            full_name: format!("{}{}", GIST_REPOSITORY_PREFIX, self.id),
+            description: self.description.clone(),
+            language: self.files.values().find_map(|f| f.language.clone()),
+            stargazers_count: 0,
+            forks_count: 0,
+            fork: false,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            owner: RepositoryOwner {
+                login: owner.to_string(),
+            },
+        };
+        let description = self
+            .description
+            .as_deref()
+            .filter(|d| !d.trim().is_empty())
+            .unwrap_or("(no description)");
+        let commit = Commit {
+            sha: self.id.clone(),
+            commit: CommitDetails {
+                message: format!("Gist: {}", description),
+                author: CommitAuthor {
+                    name: owner.to_string(),
+                    email: String::new(),
+                    date: self.updated_at.into(),
+                },
+            },
+            stats: Some(CommitStats {
+                additions,
+                deletions: 0,
+                total: additions,
+            }),
+            files: Some(files),
+        };
+        (repository, commit)
+    }
+}
+
+/// Version of the `UserProfile` shape written by this build. Profiles
+/// without a version predate versioning and are treated as version 0.
+pub const PROFILE_SCHEMA_VERSION: u32 = 1;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserProfile {
+    #[serde(default)]
+    pub schema_version: u32,
     pub user: GitHubUser,
     pub repositories: Vec<Repository>,
     pub total_commits_analyzed: u32,
     pub analysis_date: DateTime<Utc>,
     pub skills: Vec<SkillRating>,
     pub summary: ProfileSummary,
+    /// Set when the run was interrupted or stopped at the cost cap, so only
+    /// part of the data was analyzed
+    #[serde(default)]
+    pub incomplete: bool,
+    /// Set for profiles rated as of a past date (`--as-of`): commits and
+    /// pull requests after it were ignored
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub as_of: Option<DateTime<Utc>>,
+}
+
+/// A profile together with everything that was skipped or failed while
+/// producing it. Serializes as the profile with an extra `warnings` field.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileWithWarnings {
+    #[serde(flatten)]
+    pub profile: UserProfile,
+    #[serde(default)]
+    pub warnings: Vec<AnalysisWarning>,
+    /// Counters from the run, not part of the serialized profile
+    #[serde(skip)]
+    pub diagnostics: RunDiagnostics,
+}
+
+/// What a run fetched, dropped and sent to the LLM, for explaining a
+/// surprisingly thin profile (`--diagnostics`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct RunDiagnostics {
+    /// Repositories listed for the user, plus any contributed to
+    pub repositories_listed: usize,
+    pub repositories_skipped: Vec<SkippedRepository>,
+    /// Repositories with at least one analyzed commit
+    pub repositories_analyzed: usize,
+    /// Commits fetched with their diffs, gists included
+    pub commits_fetched: usize,
+    pub commits_failed: usize,
+    pub gists_fetched: usize,
+    /// Commits dropped for being made after `as_of`
+    pub commits_dropped_as_of: usize,
+    /// Commits dropped as rewrites of another commit's diff
+    pub commits_dropped_duplicate: usize,
+    /// Commits dropped for only changing paths in `exclude_paths`
+    pub commits_dropped_excluded: usize,
+    /// Commits dropped for having no files in the selected languages
+    pub commits_dropped_language: usize,
+    pub commits_analyzed: usize,
+    pub batches_created: usize,
+    pub batches_succeeded: usize,
+    /// Batches whose LLM request failed
+    pub batches_failed: usize,
+    /// Batches whose LLM response could not be parsed
+    pub batches_unparsed: usize,
+    /// Batches never sent, at the cost cap or on shutdown
+    pub batches_skipped: usize,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Estimated LLM spend in US dollars, when the model's pricing is known
+    pub estimated_cost: Option<f64>,
+    /// Why the run failed, when it did
+    pub error: Option<String>,
+}
+
+/// A repository left out of the analysis.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SkippedRepository {
+    pub repository: String,
+    pub reason: String,
+}
+
+/// A non-fatal problem encountered during analysis. The affected data is
+/// left out of the profile rather than failing the whole run.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AnalysisWarning {
+    /// A repository was skipped because its commits could not be listed or counted
+    RepositoryFailed { repository: String, error: String },
+    /// Only part of a large repository's history was analyzed
+    RepositoryTruncated { repository: String, analyzed: u32, total: u32 },
+    /// A single commit's diff could not be fetched
+    CommitFailed { repository: String, sha: String, error: String },
+    /// An LLM request for a batch of commits failed
+    BatchFailed { repository: String, commits: usize, error: String },
+    /// The LLM responded but its analysis could not be parsed
+    ParseFailed { repository: String, commits: usize, error: String },
+    /// LLM batches were not started because they could have pushed the
+    /// estimated spend past the `max_cost` cap
+    CostCapReached { max_cost: f64, skipped_batches: usize },
+}
+
+impl std::fmt::Display for AnalysisWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnalysisWarning::RepositoryFailed { repository, error } => {
+                write!(f, "{}: repository skipped: {}", repository, error)
+            }
+            AnalysisWarning::RepositoryTruncated { repository, analyzed, total } => write!(
+                f,
+                "{}: only {} of {} commits analyzed",
+                repository, analyzed, total
+            ),
+            AnalysisWarning::CommitFailed { repository, sha, error } => write!(
+                f,
+                "{}: commit {} skipped: {}",
+                repository,
+                sha.get(..7).unwrap_or(sha),
+                error
+            ),
+            AnalysisWarning::BatchFailed { repository, commits, error } => write!(
+                f,
+                "{}: LLM analysis of {} commit(s) failed: {}",
+                repository, commits, error
+            ),
+            AnalysisWarning::ParseFailed { repository, commits, error } => write!(
+                f,
+                "{}: LLM response for {} commit(s) could not be parsed: {}",
+                repository, commits, error
+            ),
+            AnalysisWarning::CostCapReached { max_cost, skipped_batches } => write!(
+                f,
+                "LLM cost cap of ${:.2} reached: {} batch(es) skipped",
+                max_cost, skipped_batches
+            ),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,3 +393,223 @@ pub struct LanguageBreakdown {
     pub bytes: u64,
     pub percentage: f32,
 }
+
+impl UserProfile {
+    /// Upgrades a profile loaded from an older schema to the current version.
+    /// Fields added since version 0 are filled with their serde defaults on load,
+    /// so the upgrade only needs to stamp the new version.
+    pub fn migrate(&mut self) {
+        // Profiles saved before skills had display names
+        for rating in &mut self.skills {
+            if rating.skill.display_name.is_empty() {
+                rating.skill.display_name = fallback_display_name(&rating.skill.name);
+            }
+        }
+
+        if self.schema_version > PROFILE_SCHEMA_VERSION {
+            tracing::warn!(
+                "Profile for {} has schema version {} (newer than supported {}); some fields may be ignored",
+                self.user.login,
+                self.schema_version,
+                PROFILE_SCHEMA_VERSION
+            );
+            return;
+        }
+
+        if self.schema_version < PROFILE_SCHEMA_VERSION {
+            tracing::info!(
+                "Migrating profile for {} from schema version {} to {}",
+                self.user.login,
+                self.schema_version,
+                PROFILE_SCHEMA_VERSION
+            );
+            self.schema_version = PROFILE_SCHEMA_VERSION;
+        }
+    }
+
+    /// Strips personal details before a profile is shared: the user's email,
+    /// location and company, and email addresses in evidence snippets.
+    /// Skills and scores are left intact.
+    pub fn redact(&mut self) {
+        self.user.email = None;
+        self.user.location = None;
+        self.user.company = None;
+        for skill in &mut self.skills {
+            skill.redact();
+        }
+    }
+
+    /// The headline figures of the profile, for consumers showing many users.
+    /// Skills are taken in profile order, which is highest score first.
+    pub fn summary_view(&self) -> ProfileSummaryView {
+        ProfileSummaryView {
+            login: self.user.login.clone(),
+            analysis_date: self.analysis_date,
+            experience_level: self.summary.experience_level.clone(),
+            primary_languages: self.summary.primary_languages.clone(),
+            primary_domains: self.summary.primary_domains.clone(),
+            top_skills: self
+                .skills
+                .iter()
+                .take(SUMMARY_TOP_SKILLS)
+                .map(|s| TopSkill {
+                    name: s.skill.display_name.clone(),
+                    category: s.skill.category.clone(),
+                    proficiency_score: s.proficiency_score,
+                })
+                .collect(),
+            overall_confidence: self.summary.overall_confidence,
+            incomplete: self.incomplete,
+        }
+    }
+}
+
+/// Number of skills in a `ProfileSummaryView`.
+pub const SUMMARY_TOP_SKILLS: usize = 3;
+
+/// Compact form of a `UserProfile` without per-skill detail (`--summary-only`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileSummaryView {
+    pub login: String,
+    pub analysis_date: DateTime<Utc>,
+    pub experience_level: ExperienceLevel,
+    pub primary_languages: Vec<String>,
+    pub primary_domains: Vec<SkillDomain>,
+    /// Highest-scoring skills, at most `SUMMARY_TOP_SKILLS`
+    pub top_skills: Vec<TopSkill>,
+    pub overall_confidence: f32,
+    pub incomplete: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopSkill {
+    pub name: String,
+    pub category: SkillCategory,
+    pub proficiency_score: u8,
+}
+
+/// Replacement for anything `redact_emails` masks.
+pub const REDACTED: &str = "[redacted]";
+
+/// Replaces every whitespace-separated word that looks like an email address
+/// with `REDACTED`, keeping surrounding punctuation such as `<...>`.
+pub fn redact_emails(text: &str) -> String {
+    text.split_inclusive(char::is_whitespace)
+        .map(|word| {
+            let core = word.trim_matches(|c: char| !c.is_alphanumeric());
+            if looks_like_email(core) {
+                word.replacen(core, REDACTED, 1)
+            } else {
+                word.to_string()
+            }
+        })
+        .collect()
+}
+
+fn looks_like_email(word: &str) -> bool {
+    let Some((local, domain)) = word.split_once('@') else {
+        return false;
+    };
+    !local.is_empty()
+        && local.chars().all(|c| c.is_alphanumeric() || "._%+-".contains(c))
+        && domain.contains('.')
+        && domain.split('.').all(|part| !part.is_empty())
+        && domain.chars().all(|c| c.is_alphanumeric() || c == '.' || c == '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gist_as_commit() {
+        let gist: Gist = serde_json::from_str(
+            r#"{
+                "id": "aa5a315d61ae9438b18d",
+                "description": "Retry helper",
+                "created_at": "2024-01-01T00:00:00Z",
+                "updated_at": "2024-02-01T00:00:00Z",
+                "files": {
+                    "retry.py": {"filename": "retry.py", "language": "Python", "content": "import time\ndef retry(): pass"},
+                    "logo.png": {"filename": "logo.png", "language": null}
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let (repo, commit) = gist.to_commit("octocat");
+        assert_eq!(repo.full_name, "gist:aa5a315d61ae9438b18d");
+        assert_eq!(repo.language.as_deref(), Some("Python"));
+        assert_eq!(commit.commit.message, "Gist: Retry helper");
+        assert_eq!(commit.stats.unwrap().additions, 2);
+
+        let files = commit.files.unwrap();
+        assert_eq!(files[1].filename, "retry.py");
+        assert_eq!(files[1].patch.as_deref(), Some("+import time\n+def retry(): pass"));
+        assert!(files[0].is_binary());
+    }
+
+    #[test]
+    fn test_account_type() {
+        let account = |extra: &str| -> GitHubUser {
+            serde_json::from_str(&format!(
+                r#"{{"login": "octo-org", "id": 1, "name": null, "email": null, "avatar_url": "",
+                    "bio": null, "company": null, "location": null, "public_repos": 3,
+                    "followers": 0, "following": 0, "created_at": "2020-01-01T00:00:00Z"{}}}"#,
+                extra
+            ))
+            .unwrap()
+        };
+        assert_eq!(account(r#", "type": "Organization""#).account_type, AccountType::Organization);
+        // Profiles saved before the field existed are users
+        assert_eq!(account("").account_type, AccountType::User);
+        // New types GitHub introduces must not break fetching the user
+        assert_eq!(account(r#", "type": "Mannequin""#).account_type, AccountType::Other);
+    }
+
+    #[test]
+    fn test_summary_view() {
+        let mut profile = crate::test_support::profile(
+            "octocat",
+            &[("rust", 90), ("go", 70), ("sql", 60), ("shell", 40)],
+        );
+        profile.summary.primary_domains = vec![SkillDomain::Backend, SkillDomain::MachineLearning];
+        profile.incomplete = true;
+
+        let view = profile.summary_view();
+        assert_eq!(view.login, "octocat");
+        let top: Vec<_> = view
+            .top_skills
+            .iter()
+            .map(|s| (s.name.as_str(), s.proficiency_score))
+            .collect();
+        assert_eq!(top, vec![("Rust", 90), ("Go", 70), ("Sql", 60)]);
+        assert_eq!(view.primary_domains, profile.summary.primary_domains);
+        assert_eq!(view.primary_domains[1].to_string(), "Machine Learning");
+        assert!(view.incomplete);
+    }
+
+    #[test]
+    fn test_bot_logins() {
+        for login in ["dependabot[bot]", "github-actions[bot]", "Renovate[BOT]", "renovate", "Dependabot"] {
+            assert!(is_bot_login(login), "{} should be a bot", login);
+        }
+        for login in ["octocat", "robot", "bot", "dependabot-fan", "[bot]-lover"] {
+            assert!(!is_bot_login(login), "{} should not be a bot", login);
+        }
+    }
+
+    #[test]
+    fn test_redact_emails() {
+        assert_eq!(
+            redact_emails("Signed-off-by: Octo Cat <octo.cat+dev@example.co.uk>"),
+            "Signed-off-by: Octo Cat <[redacted]>"
+        );
+        assert_eq!(redact_emails("mail me@home.net, thanks"), "mail [redacted], thanks");
+        assert_eq!(
+            redact_emails("uses @decorator and user@localhost"),
+            "uses @decorator and user@localhost"
+        );
+        assert_eq!(redact_emails("a  b\tc"), "a  b\tc");
+    }
+}
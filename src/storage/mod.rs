@@ -1,3 +1,3 @@
 mod sqlite;
 
-pub use sqlite::Storage;
+pub use sqlite::{CachedResponse, Storage};
@@ -1,13 +1,22 @@
 use rusqlite::{Connection, params};
+use std::collections::HashMap;
 use std::path::Path;
 
-use crate::error::Result;
-use crate::models::{UserProfile, SkillRating};
+use crate::error::{Error, Result};
+use crate::models::skill::fallback_display_name;
+use crate::models::{UserProfile, SkillRating, PROFILE_SCHEMA_VERSION};
 
 pub struct Storage {
     conn: Connection,
 }
 
+/// A cached GitHub API response keyed by resource path.
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+    pub etag: String,
+    pub body: String,
+}
+
 impl Storage {
     pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
         let conn = Connection::open(path)?;
@@ -24,6 +33,9 @@ impl Storage {
     }
 
     fn init_db(&self) -> Result<()> {
+        // Must run before the schema below, which assumes the new skills layout
+        self.migrate_skill_ids()?;
+
         self.conn.execute_batch(
             r#"
             CREATE TABLE IF NOT EXISTS users (
@@ -50,9 +62,10 @@ impl Storage {
 
             CREATE TABLE IF NOT EXISTS skills (
                 id INTEGER PRIMARY KEY,
+                canonical_id TEXT NOT NULL,
                 name TEXT NOT NULL,
                 category TEXT NOT NULL,
-                UNIQUE(name, category)
+                UNIQUE(canonical_id, category)
             );
 
             CREATE TABLE IF NOT EXISTS skill_ratings (
@@ -67,18 +80,111 @@ impl Storage {
                 UNIQUE(profile_id, skill_id)
             );
 
+            CREATE TABLE IF NOT EXISTS etags (
+                resource TEXT PRIMARY KEY,
+                etag TEXT NOT NULL,
+                body TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            );
+
             CREATE INDEX IF NOT EXISTS idx_profiles_user_id ON profiles(user_id);
             CREATE INDEX IF NOT EXISTS idx_skill_ratings_profile_id ON skill_ratings(profile_id);
             CREATE INDEX IF NOT EXISTS idx_skill_ratings_skill_id ON skill_ratings(skill_id);
             "#,
         )?;
 
+        self.add_column_if_missing("profiles", "incomplete", "INTEGER NOT NULL DEFAULT 0")?;
+        self.add_column_if_missing("profiles", "schema_version", "INTEGER NOT NULL DEFAULT 0")?;
+        self.add_column_if_missing("skill_ratings", "breakdown_json", "TEXT")?;
+        self.add_column_if_missing("skill_ratings", "trend_series_json", "TEXT")?;
+        self.add_column_if_missing("skills", "display_name", "TEXT")?;
+
+        Ok(())
+    }
+
+    /// Rebuilds a skills table from before skills had stable ids, where rows
+    /// were unique by display name. Each row gets the id the name used to be
+    /// derived as, and rows that map to the same id are merged into the
+    /// oldest, with their ratings pointed at it.
+    fn migrate_skill_ids(&self) -> Result<()> {
+        let mut stmt = self.conn.prepare("PRAGMA table_info(skills)")?;
+        let columns: Vec<String> = stmt
+            .query_map([], |row| row.get(1))?
+            .collect::<std::result::Result<_, _>>()?;
+        if columns.is_empty() || columns.iter().any(|c| c == "canonical_id") {
+            return Ok(());
+        }
+
+        tracing::info!("Migrating skills table to stable skill ids");
+        // Dropping the old table would otherwise trip the ratings' foreign
+        // key; the pragma has no effect inside a transaction
+        let foreign_keys: bool = self.conn.query_row("PRAGMA foreign_keys", [], |row| row.get(0))?;
+        self.conn.execute_batch("PRAGMA foreign_keys = OFF")?;
+        let migrated = self.with_transaction(|conn| {
+            conn.execute_batch(
+                r#"
+                CREATE TABLE skills_new (
+                    id INTEGER PRIMARY KEY,
+                    canonical_id TEXT NOT NULL,
+                    name TEXT NOT NULL,
+                    category TEXT NOT NULL,
+                    UNIQUE(canonical_id, category)
+                );
+                INSERT OR IGNORE INTO skills_new (id, canonical_id, name, category)
+                    SELECT id, lower(replace(name, ' ', '_')), name, category FROM skills ORDER BY id;
+                UPDATE OR IGNORE skill_ratings SET skill_id = (
+                    SELECT n.id FROM skills o
+                    JOIN skills_new n
+                        ON n.canonical_id = lower(replace(o.name, ' ', '_')) AND n.category = o.category
+                    WHERE o.id = skill_ratings.skill_id
+                );
+                DELETE FROM skill_ratings WHERE skill_id NOT IN (SELECT id FROM skills_new);
+                DROP TABLE skills;
+                ALTER TABLE skills_new RENAME TO skills;
+                "#,
+            )?;
+            Ok(())
+        });
+        if foreign_keys {
+            self.conn.execute_batch("PRAGMA foreign_keys = ON")?;
+        }
+        migrated
+    }
+
+    /// Adds a column to a table created by an older version of the schema.
+    fn add_column_if_missing(&self, table: &str, column: &str, definition: &str) -> Result<()> {
+        let mut stmt = self.conn.prepare(&format!("PRAGMA table_info({})", table))?;
+        let exists = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .filter_map(|name| name.ok())
+            .any(|name| name == column);
+
+        if !exists {
+            self.conn.execute_batch(&format!(
+                "ALTER TABLE {} ADD COLUMN {} {}",
+                table, column, definition
+            ))?;
+        }
+
         Ok(())
     }
 
+    /// Runs `f` inside a transaction, committing if it succeeds and rolling
+    /// back otherwise, so a failed write never leaves partial state behind.
+    pub fn with_transaction<T>(&self, f: impl FnOnce(&Connection) -> Result<T>) -> Result<T> {
+        let tx = self.conn.unchecked_transaction()?;
+        let value = f(&tx)?;
+        tx.commit()?;
+        Ok(value)
+    }
+
     pub fn save_profile(&self, profile: &UserProfile) -> Result<()> {
+        self.with_transaction(|conn| Self::write_profile(conn, profile))
+    }
+
+    fn write_profile(conn: &Connection, profile: &UserProfile) -> Result<()> {
         // Insert or update user
-        self.conn.execute(
+        conn.execute(
             r#"
             INSERT INTO users (username, name, avatar_url, bio, company, location, public_repos, followers, created_at)
             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
@@ -104,7 +210,7 @@ impl Storage {
             ],
         )?;
 
-        let user_id: i64 = self.conn.query_row(
+        let user_id: i64 = conn.query_row(
             "SELECT id FROM users WHERE username = ?1",
             params![profile.user.login],
             |row| row.get(0),
@@ -112,57 +218,75 @@ impl Storage {
 
         // Insert or update profile
         let summary_json = serde_json::to_string(&profile.summary)?;
-        self.conn.execute(
+        conn.execute(
             r#"
-            INSERT INTO profiles (user_id, total_commits_analyzed, analysis_date, summary_json)
-            VALUES (?1, ?2, ?3, ?4)
+            INSERT INTO profiles (user_id, total_commits_analyzed, analysis_date, summary_json, incomplete, schema_version)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
             ON CONFLICT(user_id) DO UPDATE SET
                 total_commits_analyzed = excluded.total_commits_analyzed,
                 analysis_date = excluded.analysis_date,
-                summary_json = excluded.summary_json
+                summary_json = excluded.summary_json,
+                incomplete = excluded.incomplete,
+                schema_version = excluded.schema_version
             "#,
             params![
                 user_id,
                 profile.total_commits_analyzed,
                 profile.analysis_date.to_rfc3339(),
                 summary_json,
+                profile.incomplete,
+                profile.schema_version,
             ],
         )?;
 
-        let profile_id: i64 = self.conn.query_row(
+        let profile_id: i64 = conn.query_row(
             "SELECT id FROM profiles WHERE user_id = ?1",
             params![user_id],
             |row| row.get(0),
         )?;
 
         // Clear existing skill ratings for this profile
-        self.conn.execute(
+        conn.execute(
             "DELETE FROM skill_ratings WHERE profile_id = ?1",
             params![profile_id],
         )?;
 
         // Insert skill ratings
         for rating in &profile.skills {
-            // Insert or get skill
-            self.conn.execute(
+            // Insert or get skill by its stable id, keeping the latest names
+            conn.execute(
                 r#"
-                INSERT OR IGNORE INTO skills (name, category)
-                VALUES (?1, ?2)
+                INSERT INTO skills (canonical_id, name, category, display_name)
+                VALUES (?1, ?2, ?3, ?4)
+                ON CONFLICT(canonical_id, category) DO UPDATE SET
+                    name = excluded.name,
+                    display_name = excluded.display_name
                 "#,
-                params![rating.skill.name, rating.skill.category.to_string()],
+                params![
+                    rating.skill.id,
+                    rating.skill.name,
+                    rating.skill.category.to_string(),
+                    rating.skill.display_name,
+                ],
             )?;
 
-            let skill_id: i64 = self.conn.query_row(
-                "SELECT id FROM skills WHERE name = ?1 AND category = ?2",
-                params![rating.skill.name, rating.skill.category.to_string()],
+            let skill_id: i64 = conn.query_row(
+                "SELECT id FROM skills WHERE canonical_id = ?1 AND category = ?2",
+                params![rating.skill.id, rating.skill.category.to_string()],
                 |row| row.get(0),
             )?;
 
             let evidence_json = serde_json::to_string(&rating.evidence)?;
-            self.conn.execute(
+            let breakdown_json = rating
+                .breakdown
+                .as_ref()
+                .map(serde_json::to_string)
+                .transpose()?;
+            let trend_series_json = serde_json::to_string(&rating.trend_series)?;
+            conn.execute(
                 r#"
-                INSERT INTO skill_ratings (profile_id, skill_id, proficiency_score, percentile_rank, confidence, trend, evidence_json)
-                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                INSERT INTO skill_ratings (profile_id, skill_id, proficiency_score, percentile_rank, confidence, trend, evidence_json, breakdown_json, trend_series_json)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
                 "#,
                 params![
                     profile_id,
@@ -172,6 +296,8 @@ impl Storage {
                     rating.confidence,
                     rating.trend.to_string(),
                     evidence_json,
+                    breakdown_json,
+                    trend_series_json,
                 ],
             )?;
         }
@@ -184,7 +310,8 @@ impl Storage {
             r#"
             SELECT p.id, p.total_commits_analyzed, p.analysis_date, p.summary_json,
                    u.username, u.name, u.avatar_url, u.bio, u.company, u.location,
-                   u.public_repos, u.followers, u.created_at, u.id as github_id
+                   u.public_repos, u.followers, u.created_at, u.id as github_id,
+                   p.incomplete, p.schema_version
             FROM profiles p
             JOIN users u ON p.user_id = u.id
             WHERE u.username = ?1
@@ -206,12 +333,14 @@ impl Storage {
                     row.get::<_, u32>(11)?,     // followers
                     row.get::<_, String>(12)?,  // created_at
                     row.get::<_, u64>(13)?,     // github_id
+                    row.get::<_, bool>(14)?,    // incomplete
+                    row.get::<_, u32>(15)?,     // schema_version
                 ))
             },
         );
 
         match result {
-            Ok((profile_id, total_commits, analysis_date_str, summary_json, username, name, avatar_url, bio, company, location, public_repos, followers, created_at_str, github_id)) => {
+            Ok((profile_id, total_commits, analysis_date_str, summary_json, username, name, avatar_url, bio, company, location, public_repos, followers, created_at_str, github_id, incomplete, schema_version)) => {
                 let user = crate::models::GitHubUser {
                     login: username,
                     id: github_id,
@@ -227,6 +356,8 @@ impl Storage {
                     created_at: chrono::DateTime::parse_from_rfc3339(&created_at_str)
                         .map(|dt| dt.with_timezone(&chrono::Utc))
                         .unwrap_or_else(|_| chrono::Utc::now()),
+                    // Only user profiles are ever saved
+                    account_type: crate::models::AccountType::User,
                 };
 
                 let summary = serde_json::from_str(&summary_json).unwrap_or_default();
@@ -237,14 +368,21 @@ impl Storage {
                 // Fetch skill ratings
                 let skills = self.get_skill_ratings(profile_id)?;
 
-                Ok(Some(UserProfile {
+                let mut profile = UserProfile {
+                    schema_version,
                     user,
                     repositories: Vec::new(), // Not stored in DB currently
                     total_commits_analyzed: total_commits,
                     analysis_date,
                     skills,
                     summary,
-                }))
+                    incomplete,
+                    // Profiles as of a past date are never saved
+                    as_of: None,
+                };
+                profile.migrate();
+
+                Ok(Some(profile))
             }
             Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
             Err(e) => Err(e.into()),
@@ -255,7 +393,8 @@ impl Storage {
         let mut stmt = self.conn.prepare(
             r#"
             SELECT s.name, s.category, sr.proficiency_score, sr.percentile_rank,
-                   sr.confidence, sr.trend, sr.evidence_json
+                   sr.confidence, sr.trend, sr.evidence_json, sr.breakdown_json,
+                   sr.trend_series_json, s.canonical_id, s.display_name
             FROM skill_ratings sr
             JOIN skills s ON sr.skill_id = s.id
             WHERE sr.profile_id = ?1
@@ -271,31 +410,33 @@ impl Storage {
             let confidence: f32 = row.get(4)?;
             let trend_str: String = row.get(5)?;
             let evidence_json: String = row.get(6)?;
-
-            let category = match category_str.as_str() {
-                "Language" => crate::models::skill::SkillCategory::Language,
-                "Framework" => crate::models::skill::SkillCategory::Framework,
-                "Library" => crate::models::skill::SkillCategory::Library,
-                "Tool" => crate::models::skill::SkillCategory::Tool,
-                "Domain" => crate::models::skill::SkillCategory::Domain,
-                "Practice" => crate::models::skill::SkillCategory::Practice,
-                _ => crate::models::skill::SkillCategory::Concept,
-            };
-
-            let trend = match trend_str.as_str() {
-                "Improving" => crate::models::skill::SkillTrend::Improving,
-                "Stable" => crate::models::skill::SkillTrend::Stable,
-                "Declining" => crate::models::skill::SkillTrend::Declining,
-                "New" => crate::models::skill::SkillTrend::New,
-                _ => crate::models::skill::SkillTrend::Dormant,
-            };
+            let breakdown_json: Option<String> = row.get(7)?;
+            let trend_series_json: Option<String> = row.get(8)?;
+            let id: String = row.get(9)?;
+            // Rows written before display names were stored have none
+            let display_name: Option<String> = row.get(10)?;
+
+            // Unknown values fail the load rather than silently becoming a default
+            let category = category_str
+                .parse()
+                .map_err(|e: String| conversion_error(1, e))?;
+            let trend = trend_str
+                .parse()
+                .map_err(|e: String| conversion_error(5, e))?;
 
             let evidence: crate::models::skill::SkillEvidence =
                 serde_json::from_str(&evidence_json).unwrap_or_default();
+            let breakdown = breakdown_json.and_then(|json| serde_json::from_str(&json).ok());
+            let trend_series = trend_series_json
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default();
 
             Ok(SkillRating {
                 skill: crate::models::skill::Skill {
-                    id: name.to_lowercase().replace(' ', "_"),
+                    id,
+                    display_name: display_name
+                        .filter(|d| !d.is_empty())
+                        .unwrap_or_else(|| fallback_display_name(&name)),
                     name,
                     category,
                     subcategory: None,
@@ -306,6 +447,8 @@ impl Storage {
                 confidence,
                 evidence,
                 trend,
+                breakdown,
+                trend_series,
             })
         })?;
 
@@ -321,16 +464,108 @@ impl Storage {
         usernames.collect::<std::result::Result<Vec<_>, _>>().map_err(Into::into)
     }
 
-    pub fn get_percentile(&self, skill_name: &str, score: u8) -> Result<Option<u8>> {
+    /// Loads every stored profile, most recently analyzed first, upgraded to
+    /// the current schema. Repositories are not stored, so they come back empty.
+    pub fn export_all(&self) -> Result<Vec<UserProfile>> {
+        let mut profiles = Vec::new();
+        for username in self.list_profiles()? {
+            if let Some(profile) = self.get_profile(&username)? {
+                profiles.push(profile);
+            }
+        }
+        Ok(profiles)
+    }
+
+    /// Saves `profiles`, replacing any stored profile for the same user, in a
+    /// single transaction. Profiles from a newer schema than this build
+    /// understands are skipped with a warning rather than saved with fields
+    /// dropped. Returns how many profiles were imported.
+    pub fn import_all(&self, profiles: &[UserProfile]) -> Result<usize> {
+        self.with_transaction(|conn| {
+            let mut imported = 0;
+            for profile in profiles {
+                if profile.schema_version > PROFILE_SCHEMA_VERSION {
+                    tracing::warn!(
+                        "Skipping profile for {}: schema version {} is newer than supported {}",
+                        profile.user.login,
+                        profile.schema_version,
+                        PROFILE_SCHEMA_VERSION
+                    );
+                    continue;
+                }
+                let mut profile = profile.clone();
+                profile.migrate();
+                Self::write_profile(conn, &profile)?;
+                imported += 1;
+            }
+            Ok(imported)
+        })
+    }
+
+    /// The `limit` stored profiles whose skills are most like `username`'s,
+    /// most similar first. Similarity is the cosine of the angle between
+    /// the two profiles' skill score vectors, with a missing skill counting
+    /// as 0, so it ranges from 0 (no skills in common) to 1 (same skills in
+    /// the same proportions). Profiles with nothing in common are left out,
+    /// so with only one profile stored the result is empty.
+    pub fn find_similar(&self, username: &str, limit: usize) -> Result<Vec<(String, f32)>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT u.username, sr.skill_id, sr.proficiency_score
+            FROM skill_ratings sr
+            JOIN profiles p ON sr.profile_id = p.id
+            JOIN users u ON p.user_id = u.id
+            "#,
+        )?;
+        let mut vectors: HashMap<String, HashMap<i64, f32>> = HashMap::new();
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, u8>(2)?))
+        })?;
+        for row in rows {
+            let (user, skill_id, score) = row?;
+            vectors.entry(user).or_default().insert(skill_id, score as f32);
+        }
+
+        let Some(target) = vectors.get(username) else {
+            if self.get_profile(username)?.is_none() {
+                return Err(Error::UserNotFound(username.to_string()));
+            }
+            // Stored, but with no skills to compare
+            return Ok(Vec::new());
+        };
+
+        let norm = |v: &HashMap<i64, f32>| v.values().map(|s| s * s).sum::<f32>().sqrt();
+        let target_norm = norm(target);
+        let mut similar: Vec<_> = vectors
+            .iter()
+            .filter(|(user, _)| user.as_str() != username)
+            .filter_map(|(user, other)| {
+                let dot: f32 = target
+                    .iter()
+                    .filter_map(|(skill, score)| other.get(skill).map(|s| s * score))
+                    .sum();
+                let denominator = target_norm * norm(other);
+                (dot > 0.0 && denominator > 0.0).then(|| (user.clone(), dot / denominator))
+            })
+            .collect();
+
+        similar.sort_by(|(a_user, a), (b_user, b)| b.total_cmp(a).then_with(|| a_user.cmp(b_user)));
+        similar.truncate(limit);
+        Ok(similar)
+    }
+
+    /// Share of stored ratings of the skill with id `skill_id` that score
+    /// below `score`, as a percentage.
+    pub fn get_percentile(&self, skill_id: &str, score: u8) -> Result<Option<u8>> {
         let result = self.conn.query_row(
             r#"
             SELECT COUNT(*) as total,
                    SUM(CASE WHEN sr.proficiency_score < ?1 THEN 1 ELSE 0 END) as below
             FROM skill_ratings sr
             JOIN skills s ON sr.skill_id = s.id
-            WHERE s.name = ?2
+            WHERE s.canonical_id = ?2
             "#,
-            params![score, skill_name],
+            params![score, skill_id],
             |row| {
                 let total: i64 = row.get(0)?;
                 let below: i64 = row.get(1)?;
@@ -348,4 +583,233 @@ impl Storage {
             Err(e) => Err(e.into()),
         }
     }
+
+    pub fn get_cached_response(&self, resource: &str) -> Result<Option<CachedResponse>> {
+        let result = self.conn.query_row(
+            "SELECT etag, body FROM etags WHERE resource = ?1",
+            params![resource],
+            |row| {
+                Ok(CachedResponse {
+                    etag: row.get(0)?,
+                    body: row.get(1)?,
+                })
+            },
+        );
+
+        match result {
+            Ok(cached) => Ok(Some(cached)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save_cached_response(&self, resource: &str, etag: &str, body: &str) -> Result<()> {
+        self.conn.execute(
+            r#"
+            INSERT INTO etags (resource, etag, body, updated_at)
+            VALUES (?1, ?2, ?3, ?4)
+            ON CONFLICT(resource) DO UPDATE SET
+                etag = excluded.etag,
+                body = excluded.body,
+                updated_at = excluded.updated_at
+            "#,
+            params![resource, etag, body, chrono::Utc::now().to_rfc3339()],
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Error for a text column holding a value its enum doesn't know.
+fn conversion_error(column: usize, message: String) -> rusqlite::Error {
+    rusqlite::Error::FromSqlConversionFailure(column, rusqlite::types::Type::Text, message.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ScoreBreakdown;
+    use crate::test_support;
+
+    fn sample_profile() -> UserProfile {
+        let mut profile = test_support::profile("octocat", &[("rust", 80)]);
+        profile.user.name = Some("The Octocat".to_string());
+        profile.total_commits_analyzed = 42;
+        let rust = &mut profile.skills[0];
+        rust.confidence = 0.9;
+        rust.breakdown = Some(ScoreBreakdown {
+            frequency: 60.0,
+            recency: 95.0,
+            complexity: 70.0,
+            quality: 80.0,
+            consistency: 40.0,
+            proficiency: 75.0,
+            breadth_multiplier: 1.0,
+            self_reported_bonus: 0.0,
+        });
+        rust.trend_series = vec![
+            (chrono::TimeZone::with_ymd_and_hms(&chrono::Utc, 2024, 1, 1, 0, 0, 0).unwrap(), 55),
+            (chrono::TimeZone::with_ymd_and_hms(&chrono::Utc, 2024, 4, 1, 0, 0, 0).unwrap(), 70),
+        ];
+        profile
+    }
+
+    #[test]
+    fn test_save_and_load_profile() {
+        let storage = Storage::in_memory().unwrap();
+        let profile = sample_profile();
+        storage.save_profile(&profile).unwrap();
+
+        let loaded = storage.get_profile("octocat").unwrap().unwrap();
+        assert_eq!(loaded.total_commits_analyzed, 42);
+        assert_eq!(loaded.skills.len(), 1);
+        assert_eq!(loaded.skills[0].proficiency_score, 80);
+        assert_eq!(loaded.skills[0].breakdown, profile.skills[0].breakdown);
+        assert_eq!(loaded.skills[0].trend_series, profile.skills[0].trend_series);
+    }
+
+    #[test]
+    fn test_find_similar_profiles() {
+        let storage = Storage::in_memory().unwrap();
+        let profile = |login: &str, id: u64, skills: &[(&str, u8)]| {
+            let mut profile = test_support::profile(login, skills);
+            profile.user.id = id;
+            profile
+        };
+
+        storage.save_profile(&profile("octocat", 1, &[("rust", 80), ("go", 40)])).unwrap();
+        assert!(storage.find_similar("octocat", 5).unwrap().is_empty());
+        assert!(matches!(storage.find_similar("ghost", 5), Err(Error::UserNotFound(_))));
+
+        storage.save_profile(&profile("twin", 2, &[("rust", 40), ("go", 20)])).unwrap();
+        storage.save_profile(&profile("partial", 3, &[("rust", 80), ("sql", 80)])).unwrap();
+        storage.save_profile(&profile("stranger", 4, &[("sql", 90)])).unwrap();
+
+        let similar = storage.find_similar("octocat", 5).unwrap();
+        let users: Vec<_> = similar.iter().map(|(user, _)| user.as_str()).collect();
+        assert_eq!(users, vec!["twin", "partial"]);
+        assert!((similar[0].1 - 1.0).abs() < 1e-6);
+        assert!(similar[1].1 < similar[0].1);
+        assert_eq!(storage.find_similar("octocat", 1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_renamed_skill_keeps_its_history() {
+        let storage = Storage::in_memory().unwrap();
+        storage.save_profile(&sample_profile()).unwrap();
+
+        let mut renamed = sample_profile();
+        renamed.user.login = "hubot".to_string();
+        renamed.skills[0].skill.name = "Rust".to_string();
+        renamed.skills[0].proficiency_score = 90;
+        storage.save_profile(&renamed).unwrap();
+
+        let count: i64 = storage
+            .conn
+            .query_row("SELECT COUNT(*) FROM skills", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 1);
+        let loaded = storage.get_profile("octocat").unwrap().unwrap();
+        assert_eq!((loaded.skills[0].skill.id.as_str(), loaded.skills[0].skill.name.as_str()), ("rust", "Rust"));
+        assert_eq!(storage.get_percentile("rust", 85).unwrap(), Some(50));
+    }
+
+    #[test]
+    fn test_legacy_skills_table_gets_stable_ids() {
+        let path = std::env::temp_dir().join(format!("gitanalyzer-legacy-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        {
+            let storage = Storage::new(&path).unwrap();
+            storage.save_profile(&sample_profile()).unwrap();
+            // Recreate the pre-migration layout, with the same skill stored
+            // under two spellings by different profiles
+            storage
+                .conn
+                .execute_batch(
+                    "PRAGMA foreign_keys = OFF;
+                     DROP TABLE skills;
+                     CREATE TABLE skills (id INTEGER PRIMARY KEY, name TEXT NOT NULL, category TEXT NOT NULL, UNIQUE(name, category));
+                     INSERT INTO skills (id, name, category) VALUES (1, 'rust', 'Language'), (2, 'Rust', 'Language');
+                     INSERT INTO skill_ratings (profile_id, skill_id, proficiency_score, confidence, trend, evidence_json)
+                         SELECT profile_id, 2, 70, 0.5, 'Stable', evidence_json FROM skill_ratings;
+                     DELETE FROM skill_ratings WHERE skill_id = 1;",
+                )
+                .unwrap();
+        }
+
+        let storage = Storage::new(&path).unwrap();
+        let loaded = storage.get_profile("octocat").unwrap().unwrap();
+        assert_eq!(loaded.skills.len(), 1);
+        assert_eq!(loaded.skills[0].skill.id, "rust");
+        assert_eq!(loaded.skills[0].proficiency_score, 70);
+        storage.save_profile(&sample_profile()).unwrap();
+        drop(storage);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_unknown_trend_fails_to_load() {
+        let storage = Storage::in_memory().unwrap();
+        storage.save_profile(&sample_profile()).unwrap();
+        storage
+            .conn
+            .execute("UPDATE skill_ratings SET trend = 'Sleeping'", [])
+            .unwrap();
+
+        assert!(storage.get_profile("octocat").is_err());
+    }
+
+    #[test]
+    fn test_export_and_import_round_trip() {
+        let source = Storage::in_memory().unwrap();
+        source.save_profile(&sample_profile()).unwrap();
+        let exported = source.export_all().unwrap();
+        assert_eq!(exported.len(), 1);
+
+        let mut from_future = sample_profile();
+        from_future.user.login = "hubot".to_string();
+        from_future.schema_version = PROFILE_SCHEMA_VERSION + 1;
+        let mut legacy = sample_profile();
+        legacy.user.login = "monalisa".to_string();
+        legacy.schema_version = 0;
+
+        let target = Storage::in_memory().unwrap();
+        let profiles = [exported, vec![from_future, legacy]].concat();
+        assert_eq!(target.import_all(&profiles).unwrap(), 2);
+        // Importing again replaces rather than duplicates
+        assert_eq!(target.import_all(&profiles).unwrap(), 2);
+
+        assert_eq!(target.list_profiles().unwrap().len(), 2);
+        let loaded = target.get_profile("octocat").unwrap().unwrap();
+        assert_eq!(loaded.skills.len(), 1);
+        assert_eq!(loaded.total_commits_analyzed, 42);
+        let legacy = target.get_profile("monalisa").unwrap().unwrap();
+        assert_eq!(legacy.schema_version, PROFILE_SCHEMA_VERSION);
+        assert!(target.get_profile("hubot").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_failed_save_leaves_no_partial_state() {
+        let storage = Storage::in_memory().unwrap();
+        storage
+            .conn
+            .execute_batch(
+                "CREATE TRIGGER fail_ratings BEFORE INSERT ON skill_ratings
+                 BEGIN SELECT RAISE(ABORT, 'injected failure'); END;",
+            )
+            .unwrap();
+
+        assert!(storage.save_profile(&sample_profile()).is_err());
+
+        let count = |table: &str| -> i64 {
+            storage
+                .conn
+                .query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0))
+                .unwrap()
+        };
+        assert_eq!(count("users"), 0);
+        assert_eq!(count("profiles"), 0);
+        assert_eq!(count("skills"), 0);
+        assert!(storage.get_profile("octocat").unwrap().is_none());
+    }
 }
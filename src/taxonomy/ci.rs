@@ -0,0 +1,84 @@
+/// Skill name registered for changes to CI/CD configuration. Matches the
+/// canonical name in `SkillTaxonomy`.
+pub const CI_SKILL: &str = "ci/cd";
+
+/// Files that configure a CI/CD service wherever they appear in the tree.
+const CI_FILENAMES: &[&str] = &[
+    ".gitlab-ci.yml",
+    ".gitlab-ci.yaml",
+    ".travis.yml",
+    ".drone.yml",
+    "azure-pipelines.yml",
+    "azure-pipelines.yaml",
+    "bitbucket-pipelines.yml",
+    "appveyor.yml",
+    ".appveyor.yml",
+    "cloudbuild.yaml",
+    "cloudbuild.yml",
+    "buildspec.yml",
+];
+
+/// Directories whose YAML files are all CI/CD configuration.
+const CI_DIRECTORIES: &[&str] = &[
+    ".github/workflows/",
+    ".circleci/",
+    ".buildkite/",
+    ".gitlab/ci/",
+];
+
+/// Whether `path` (as reported by GitHub, relative to the repository root)
+/// is a CI/CD configuration file, e.g. `.github/workflows/ci.yml`,
+/// `.gitlab-ci.yml` or `Jenkinsfile`.
+pub fn is_ci_config(path: &str) -> bool {
+    let lower = path.to_lowercase();
+    let name = lower.rsplit('/').next().unwrap_or(&lower);
+
+    if name == "jenkinsfile" || name.starts_with("jenkinsfile.") {
+        return true;
+    }
+    if CI_FILENAMES.contains(&name) {
+        return true;
+    }
+
+    let is_yaml = name.ends_with(".yml") || name.ends_with(".yaml");
+    is_yaml
+        && CI_DIRECTORIES
+            .iter()
+            .any(|dir| lower.starts_with(dir) || lower.contains(&format!("/{}", dir)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ci_config_paths() {
+        for path in [
+            ".github/workflows/ci.yml",
+            ".github/workflows/release.yaml",
+            ".gitlab-ci.yml",
+            "Jenkinsfile",
+            "ci/Jenkinsfile.release",
+            ".circleci/config.yml",
+            ".travis.yml",
+            "azure-pipelines.yml",
+            "services/api/.gitlab/ci/test.yml",
+        ] {
+            assert!(is_ci_config(path), "{} should be CI config", path);
+        }
+    }
+
+    #[test]
+    fn test_other_paths_are_not_ci_config() {
+        for path in [
+            ".github/dependabot.yml",
+            ".github/workflows/README.md",
+            "docs/.gitlab-ci.yml.example",
+            "config.yml",
+            "src/jenkinsfile_parser.rs",
+            "my.github/workflows/ci.yml",
+        ] {
+            assert!(!is_ci_config(path), "{} should not be CI config", path);
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use crate::models::commit::CommitForAnalysis;
+
+/// Files that declare a project's dependencies, by ecosystem.
+const DEPENDENCY_MANIFESTS: &[&str] = &[
+    "cargo.toml",
+    "package.json",
+    "requirements.txt",
+    "pyproject.toml",
+    "pipfile",
+    "go.mod",
+    "gemfile",
+    "pom.xml",
+    "build.gradle",
+    "build.gradle.kts",
+    "composer.json",
+];
+
+/// Lockfiles that pin resolved dependency versions, usually regenerated by a
+/// tool rather than written by hand.
+const LOCKFILES: &[&str] = &[
+    "cargo.lock",
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "poetry.lock",
+    "pipfile.lock",
+    "go.sum",
+    "gemfile.lock",
+    "composer.lock",
+    "gradle.lockfile",
+];
+
+fn file_name(path: &str) -> String {
+    let lower = path.to_lowercase();
+    lower.rsplit('/').next().unwrap_or(&lower).to_string()
+}
+
+/// Whether `path` (relative to the repository root) is a dependency
+/// manifest such as `Cargo.toml` or `package.json`.
+pub fn is_dependency_manifest(path: &str) -> bool {
+    DEPENDENCY_MANIFESTS.contains(&file_name(path).as_str())
+}
+
+/// Whether `path` is a dependency manifest or lockfile.
+pub fn is_dependency_file(path: &str) -> bool {
+    is_dependency_manifest(path) || LOCKFILES.contains(&file_name(path).as_str())
+}
+
+/// Whether the commit only touches dependency manifests and lockfiles, as a
+/// version bump does. Such commits say little about the author's skills.
+pub fn is_dependency_bump(commit: &CommitForAnalysis) -> bool {
+    !commit.files_changed.is_empty()
+        && commit
+            .files_changed
+            .iter()
+            .all(|f| is_dependency_file(&f.filename))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::commit::{CommitStats, FileForAnalysis};
+    use chrono::Utc;
+
+    fn commit(files: &[&str]) -> CommitForAnalysis {
+        CommitForAnalysis {
+            sha: "abc123".to_string(),
+            repository: "octocat/hello".to_string(),
+            message: "Bump serde from 1.0.1 to 1.0.2".to_string(),
+            stats: CommitStats::default(),
+            files_changed: files
+                .iter()
+                .map(|f| FileForAnalysis {
+                    filename: f.to_string(),
+                    language: None,
+                    diff: String::new(),
+                    additions: 1,
+                    deletions: 1,
+                })
+                .collect(),
+            binary_files_changed: 0,
+            committed_at: Utc::now(),
+            collaborative: false,
+        }
+    }
+
+    #[test]
+    fn test_dependency_bumps() {
+        assert!(is_dependency_bump(&commit(&["Cargo.toml", "Cargo.lock"])));
+        assert!(is_dependency_bump(&commit(&["web/package.json", "web/yarn.lock"])));
+        assert!(!is_dependency_bump(&commit(&["Cargo.toml", "src/main.rs"])));
+        assert!(!is_dependency_bump(&commit(&[])));
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::HashSet;
+
+/// Maps an imported module (as extracted for a language) to a framework skill.
+/// Skill names match the canonical names in `SkillTaxonomy`.
+pub const IMPORT_RULES: &[(&str, &str, &str)] = &[
+    // (language, module, skill)
+    ("JavaScript", "react", "react"),
+    ("JavaScript", "react-dom", "react"),
+    ("JavaScript", "react-native", "react native"),
+    ("JavaScript", "vue", "vue"),
+    ("JavaScript", "@angular/core", "angular"),
+    ("JavaScript", "svelte", "svelte"),
+    ("JavaScript", "next", "nextjs"),
+    ("JavaScript", "nuxt", "nuxt"),
+    ("JavaScript", "express", "express"),
+    ("Python", "django", "django"),
+    ("Python", "flask", "flask"),
+    ("Python", "fastapi", "fastapi"),
+    ("Rust", "axum", "axum"),
+    ("Rust", "actix_web", "actix"),
+    ("Rust", "rocket", "rocket"),
+    ("Go", "github.com/gin-gonic/gin", "gin"),
+    ("Go", "github.com/labstack/echo", "echo"),
+    ("Ruby", "rails", "rails"),
+    ("Java", "org.springframework", "spring"),
+    ("Dart", "flutter", "flutter"),
+    ("Swift", "SwiftUI", "swiftui"),
+];
+
+/// Scans the added lines of a diff for import statements and returns the
+/// framework skills they imply. `language` is a name from `detect_language`.
+pub fn detect_frameworks(language: &str, diff: &str) -> Vec<&'static str> {
+    let Some(family) = language_family(language) else {
+        return Vec::new();
+    };
+
+    let mut found = HashSet::new();
+    let mut frameworks = Vec::new();
+
+    for line in diff.lines() {
+        let Some(added) = line.strip_prefix('+') else {
+            continue;
+        };
+        if added.starts_with("++") {
+            continue;
+        }
+
+        let Some(module) = extract_module(family, added.trim()) else {
+            continue;
+        };
+
+        for &(rule_family, rule_module, skill) in IMPORT_RULES {
+            if rule_family == family
+                && module_matches(family, &module, rule_module)
+                && found.insert(skill)
+            {
+                frameworks.push(skill);
+            }
+        }
+    }
+
+    frameworks
+}
+
+/// Groups languages that share import syntax.
+fn language_family(language: &str) -> Option<&'static str> {
+    match language {
+        "JavaScript" | "TypeScript" | "Vue" | "Svelte" => Some("JavaScript"),
+        "Python" => Some("Python"),
+        "Rust" => Some("Rust"),
+        "Go" => Some("Go"),
+        "Ruby" => Some("Ruby"),
+        "Java" | "Kotlin" | "Scala" => Some("Java"),
+        "Dart" => Some("Dart"),
+        "Swift" => Some("Swift"),
+        _ => None,
+    }
+}
+
+fn module_matches(family: &str, module: &str, rule_module: &str) -> bool {
+    match family {
+        // Package paths: match the package itself or any subpath
+        "JavaScript" | "Go" | "Java" => {
+            module == rule_module
+                || module.starts_with(&format!("{}/", rule_module))
+                || module.starts_with(&format!("{}.", rule_module))
+        }
+        _ => module == rule_module,
+    }
+}
+
+/// Extracts the imported module from a single source line, if it is an import.
+fn extract_module(family: &str, line: &str) -> Option<String> {
+    match family {
+        "JavaScript" => {
+            // import x from 'mod' / import 'mod' / require('mod') / import('mod')
+            let is_import = line.starts_with("import")
+                || line.contains("require(")
+                || line.contains(" from ");
+            if !is_import {
+                return None;
+            }
+            quoted(line)
+        }
+        "Python" => {
+            let rest = line
+                .strip_prefix("from ")
+                .or_else(|| line.strip_prefix("import "))?;
+            let module = rest.split([' ', ',', '.']).next()?;
+            Some(module.to_string())
+        }
+        "Rust" => {
+            let rest = line
+                .strip_prefix("use ")
+                .or_else(|| line.strip_prefix("pub use "))
+                .or_else(|| line.strip_prefix("extern crate "))?;
+            let module = rest.split([':', ';', ' ', '{']).next()?;
+            Some(module.to_string())
+        }
+        "Go" => {
+            // Both `import "x"` and lines inside an `import ( ... )` block,
+            // optionally with an alias (`gin "github.com/gin-gonic/gin"`)
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words.as_slice() {
+                ["import", ..] => quoted(line),
+                [path] | [_, path] if path.starts_with('"') => quoted(line),
+                _ => None,
+            }
+        }
+        "Ruby" => {
+            let rest = line.strip_prefix("require ").or_else(|| line.strip_prefix("gem "))?;
+            quoted(rest).map(|m| m.split('/').next().unwrap_or(&m).to_string())
+        }
+        "Java" => {
+            let rest = line.strip_prefix("import ")?;
+            let rest = rest.strip_prefix("static ").unwrap_or(rest);
+            Some(rest.trim_end_matches(';').trim().to_string())
+        }
+        "Dart" => {
+            let module = quoted(line.strip_prefix("import ")?)?;
+            let package = module.strip_prefix("package:")?;
+            Some(package.split('/').next()?.to_string())
+        }
+        "Swift" => {
+            let rest = line.strip_prefix("import ")?;
+            Some(rest.trim().to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Returns the contents of the first single- or double-quoted string in `line`.
+fn quoted(line: &str) -> Option<String> {
+    let start = line.find(['\'', '"'])?;
+    let quote = line[start..].chars().next()?;
+    let rest = &line[start + 1..];
+    let end = rest.find(quote)?;
+    Some(rest[..end].to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_javascript_frameworks() {
+        let diff = "+import React, { useState } from 'react';\n\
+                    +import { createRoot } from \"react-dom/client\";\n\
+                    +const express = require('express');\n\
+                    -import Vue from 'vue';\n";
+        assert_eq!(detect_frameworks("TypeScript", diff), vec!["react", "express"]);
+        assert_eq!(
+            detect_frameworks("JavaScript", "+import { Component } from '@angular/core';"),
+            vec!["angular"]
+        );
+    }
+
+    #[test]
+    fn test_detect_python_frameworks() {
+        let diff = "+from django.db import models\n+import flask\n";
+        assert_eq!(detect_frameworks("Python", diff), vec!["django", "flask"]);
+    }
+
+    #[test]
+    fn test_detect_rust_frameworks() {
+        let diff = "+use axum::{routing::get, Router};\n+use actix_web::web;\n+use serde::Serialize;\n";
+        assert_eq!(detect_frameworks("Rust", diff), vec!["axum", "actix"]);
+    }
+
+    #[test]
+    fn test_detect_go_and_java_frameworks() {
+        let go = "+import (\n+\t\"net/http\"\n+\t\"github.com/gin-gonic/gin\"\n+)\n";
+        assert_eq!(detect_frameworks("Go", go), vec!["gin"]);
+
+        let java = "+import org.springframework.boot.SpringApplication;\n";
+        assert_eq!(detect_frameworks("Java", java), vec!["spring"]);
+    }
+
+    #[test]
+    fn test_ignores_context_and_unknown_languages() {
+        assert!(detect_frameworks("Python", " from django.db import models").is_empty());
+        assert!(detect_frameworks("Markdown", "+import React from 'react'").is_empty());
+    }
+}
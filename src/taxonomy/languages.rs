@@ -142,6 +142,52 @@ pub fn detect_language(filename: &str) -> Option<String> {
         .map(|s| s.to_string())
 }
 
+/// Tracks how many changed files `detect_language` could classify, keeping
+/// counts of the unrecognized extensions so the taxonomy can be extended.
+#[derive(Debug, Clone, Default)]
+pub struct LanguageCoverage {
+    pub recognized: u32,
+    pub unrecognized: HashMap<String, u32>,
+}
+
+impl LanguageCoverage {
+    pub fn record(&mut self, filename: &str, language: Option<&str>) {
+        if language.is_some() {
+            self.recognized += 1;
+            return;
+        }
+
+        let basename = filename.rsplit('/').next().unwrap_or(filename);
+        let key = match basename.rsplit_once('.') {
+            Some((stem, ext)) if !stem.is_empty() => format!(".{}", ext.to_lowercase()),
+            _ => basename.to_string(),
+        };
+        *self.unrecognized.entry(key).or_insert(0) += 1;
+    }
+
+    pub fn total(&self) -> u32 {
+        self.recognized + self.unrecognized.values().sum::<u32>()
+    }
+
+    /// Fraction of files with a detected language, or `None` if no files were seen.
+    pub fn ratio(&self) -> Option<f32> {
+        let total = self.total();
+        (total > 0).then(|| self.recognized as f32 / total as f32)
+    }
+
+    /// The `n` most common unrecognized extensions, most frequent first.
+    pub fn top_unrecognized(&self, n: usize) -> Vec<(String, u32)> {
+        let mut extensions: Vec<_> = self
+            .unrecognized
+            .iter()
+            .map(|(ext, count)| (ext.clone(), *count))
+            .collect();
+        extensions.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        extensions.truncate(n);
+        extensions
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -153,5 +199,23 @@ mod tests {
         assert_eq!(detect_language("index.tsx"), Some("TypeScript".to_string()));
         assert_eq!(detect_language("Dockerfile"), Some("Dockerfile".to_string()));
         assert_eq!(detect_language("types.d.ts"), Some("TypeScript".to_string()));
+        assert_eq!(detect_language("server.mjs"), Some("JavaScript".to_string()));
+        assert_eq!(detect_language("config.cjs"), Some("JavaScript".to_string()));
+    }
+
+    #[test]
+    fn test_language_coverage() {
+        let mut coverage = LanguageCoverage::default();
+        assert_eq!(coverage.ratio(), None);
+
+        for file in ["src/main.rs", "docs/a.adoc", "docs/b.ADOC", "LICENSE", "web/app.js"] {
+            coverage.record(file, detect_language(file).as_deref());
+        }
+
+        assert_eq!(coverage.ratio(), Some(0.4));
+        assert_eq!(
+            coverage.top_unrecognized(5),
+            vec![(".adoc".to_string(), 2), ("LICENSE".to_string(), 1)]
+        );
     }
 }
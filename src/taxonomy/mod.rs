@@ -1,13 +1,29 @@
 pub mod languages;
+pub mod imports;
+pub mod ci;
+pub mod security;
+pub mod dependencies;
 
 use std::collections::HashMap;
-use crate::models::skill::{Skill, SkillCategory};
+use std::path::Path;
+use serde::Deserialize;
+use crate::error::Error;
+use crate::models::skill::{fallback_display_name, Skill, SkillCategory};
 
-pub use languages::detect_language;
+pub use languages::{detect_language, LanguageCoverage};
+pub use imports::detect_frameworks;
+pub use ci::{is_ci_config, CI_SKILL};
+pub use security::{detect_security_change, SECURITY_SKILL};
+pub use dependencies::{is_dependency_bump, is_dependency_file, is_dependency_manifest};
 
 pub struct SkillTaxonomy {
     skills: HashMap<String, Skill>,
-    aliases: HashMap<String, String>,
+    /// Canonical skill names for each alias, in registration order. An alias
+    /// shared by skills in different categories has more than one entry.
+    aliases: HashMap<String, Vec<(SkillCategory, String)>>,
+    /// Canonical skill names for aliases added with `add_alias`. These win
+    /// over `aliases` whatever the category.
+    runtime_aliases: HashMap<String, String>,
 }
 
 impl SkillTaxonomy {
@@ -15,6 +31,7 @@ impl SkillTaxonomy {
         let mut taxonomy = Self {
             skills: HashMap::new(),
             aliases: HashMap::new(),
+            runtime_aliases: HashMap::new(),
         };
 
         taxonomy.init_languages();
@@ -26,135 +43,179 @@ impl SkillTaxonomy {
         taxonomy
     }
 
+    /// The built-in taxonomy plus `aliases` (alias, target) registered in
+    /// order with `add_alias`, so later entries win.
+    pub fn with_aliases(aliases: &[(String, String)]) -> Self {
+        Self::with_extensions(&TaxonomyFile::default(), aliases)
+    }
+
+    /// The built-in taxonomy plus the skills of `file`, then `aliases` as in
+    /// `with_aliases`, so aliases may point at the added skills.
+    pub fn with_extensions(file: &TaxonomyFile, aliases: &[(String, String)]) -> Self {
+        let mut taxonomy = Self::new();
+        taxonomy.extend(file);
+        for (alias, target) in aliases {
+            taxonomy.add_alias(alias, target);
+        }
+        taxonomy
+    }
+
+    /// Adds the skills of `file`. A skill with the name of a built-in one
+    /// replaces it. Categories were checked by `TaxonomyFile::parse`.
+    pub fn extend(&mut self, file: &TaxonomyFile) {
+        for entry in &file.skills {
+            let name = entry.name.trim().to_lowercase();
+            let display_name = entry
+                .display_name
+                .clone()
+                .unwrap_or_else(|| fallback_display_name(&name));
+            let category = entry.category.parse().unwrap_or(SkillCategory::Concept);
+            let aliases: Vec<&str> = entry.aliases.iter().map(String::as_str).collect();
+            self.add_skill(&name.replace([' ', '-'], "_"), &name, &display_name, category, &aliases);
+        }
+    }
+
     fn init_languages(&mut self) {
         let languages = vec![
-            ("rust", vec!["rs"]),
-            ("python", vec!["py", "python3"]),
-            ("javascript", vec!["js", "ecmascript", "es6", "es2015"]),
-            ("typescript", vec!["ts"]),
-            ("go", vec!["golang"]),
-            ("java", vec![]),
-            ("kotlin", vec!["kt"]),
-            ("swift", vec![]),
-            ("c", vec![]),
-            ("cpp", vec!["c++", "cxx"]),
-            ("csharp", vec!["c#", "cs"]),
-            ("ruby", vec!["rb"]),
-            ("php", vec![]),
-            ("scala", vec![]),
-            ("haskell", vec!["hs"]),
-            ("elixir", vec!["ex"]),
-            ("sql", vec!["plsql", "tsql"]),
-            ("shell", vec!["bash", "sh", "zsh"]),
+            ("rust", "rust", "Rust", vec!["rs"]),
+            ("python", "python", "Python", vec!["py", "python3"]),
+            ("javascript", "javascript", "JavaScript", vec!["js", "ecmascript", "es6", "es2015"]),
+            ("typescript", "typescript", "TypeScript", vec!["ts"]),
+            ("go", "go", "Go", vec!["golang"]),
+            ("java", "java", "Java", vec![]),
+            ("kotlin", "kotlin", "Kotlin", vec!["kt"]),
+            ("swift", "swift", "Swift", vec![]),
+            ("c", "c", "C", vec![]),
+            ("cpp", "cpp", "C++", vec!["c++", "cxx"]),
+            ("csharp", "csharp", "C#", vec!["c#", "cs"]),
+            ("ruby", "ruby", "Ruby", vec!["rb"]),
+            ("php", "php", "PHP", vec![]),
+            ("scala", "scala", "Scala", vec![]),
+            ("haskell", "haskell", "Haskell", vec!["hs"]),
+            ("elixir", "elixir", "Elixir", vec!["ex"]),
+            ("sql", "sql", "SQL", vec!["plsql", "tsql"]),
+            ("shell", "shell", "Shell", vec!["bash", "sh", "zsh"]),
         ];
 
-        for (name, aliases) in languages {
-            self.add_skill(name, SkillCategory::Language, &aliases);
+        for (id, name, display_name, aliases) in languages {
+            self.add_skill(id, name, display_name, SkillCategory::Language, &aliases);
         }
     }
 
     fn init_frameworks(&mut self) {
         let frameworks = vec![
             // Frontend
-            ("react", vec!["reactjs", "react.js"]),
-            ("vue", vec!["vuejs", "vue.js"]),
-            ("angular", vec!["angularjs"]),
-            ("svelte", vec!["sveltekit"]),
-            ("nextjs", vec!["next.js", "next"]),
-            ("nuxt", vec!["nuxtjs", "nuxt.js"]),
+            ("react", "react", "React", vec!["reactjs", "react.js"]),
+            ("vue", "vue", "Vue", vec!["vuejs", "vue.js"]),
+            ("angular", "angular", "Angular", vec!["angularjs"]),
+            ("svelte", "svelte", "Svelte", vec!["sveltekit"]),
+            ("nextjs", "nextjs", "Next.js", vec!["next.js", "next"]),
+            ("nuxt", "nuxt", "Nuxt", vec!["nuxtjs", "nuxt.js"]),
             // Backend
-            ("express", vec!["expressjs"]),
-            ("django", vec![]),
-            ("flask", vec![]),
-            ("fastapi", vec![]),
-            ("spring", vec!["spring boot", "springboot"]),
-            ("rails", vec!["ruby on rails", "ror"]),
-            ("actix", vec!["actix-web"]),
-            ("axum", vec![]),
-            ("rocket", vec![]),
-            ("gin", vec![]),
-            ("echo", vec![]),
+            ("express", "express", "Express", vec!["expressjs"]),
+            ("django", "django", "Django", vec![]),
+            ("flask", "flask", "Flask", vec![]),
+            ("fastapi", "fastapi", "FastAPI", vec![]),
+            ("spring", "spring", "Spring", vec!["spring boot", "springboot"]),
+            ("rails", "rails", "Rails", vec!["ruby on rails", "ror"]),
+            ("actix", "actix", "Actix", vec!["actix-web"]),
+            ("axum", "axum", "Axum", vec![]),
+            ("rocket", "rocket", "Rocket", vec![]),
+            ("gin", "gin", "Gin", vec![]),
+            ("echo", "echo", "Echo", vec![]),
             // Mobile
-            ("react native", vec!["react-native", "rn"]),
-            ("flutter", vec![]),
-            ("swiftui", vec![]),
+            ("react_native", "react native", "React Native", vec!["react-native", "rn"]),
+            ("flutter", "flutter", "Flutter", vec![]),
+            ("swiftui", "swiftui", "SwiftUI", vec![]),
         ];
 
-        for (name, aliases) in frameworks {
-            self.add_skill(name, SkillCategory::Framework, &aliases);
+        for (id, name, display_name, aliases) in frameworks {
+            self.add_skill(id, name, display_name, SkillCategory::Framework, &aliases);
         }
     }
 
     fn init_tools(&mut self) {
         let tools = vec![
-            ("docker", vec!["dockerfile", "containerization"]),
-            ("kubernetes", vec!["k8s"]),
-            ("terraform", vec!["tf", "iac"]),
-            ("aws", vec!["amazon web services"]),
-            ("gcp", vec!["google cloud", "google cloud platform"]),
-            ("azure", vec!["microsoft azure"]),
-            ("git", vec![]),
-            ("github actions", vec!["gha"]),
-            ("gitlab ci", vec!["gitlab-ci"]),
-            ("jenkins", vec![]),
-            ("postgresql", vec!["postgres", "psql"]),
-            ("mysql", vec!["mariadb"]),
-            ("mongodb", vec!["mongo"]),
-            ("redis", vec![]),
-            ("elasticsearch", vec!["elastic", "es"]),
-            ("graphql", vec!["gql"]),
-            ("rest api", vec!["restful", "rest"]),
+            ("docker", "docker", "Docker", vec!["dockerfile", "containerization"]),
+            ("kubernetes", "kubernetes", "Kubernetes", vec!["k8s"]),
+            ("terraform", "terraform", "Terraform", vec!["tf", "iac"]),
+            ("aws", "aws", "AWS", vec!["amazon web services"]),
+            ("gcp", "gcp", "GCP", vec!["google cloud", "google cloud platform"]),
+            ("azure", "azure", "Azure", vec!["microsoft azure"]),
+            ("git", "git", "Git", vec![]),
+            ("github_actions", "github actions", "GitHub Actions", vec!["gha"]),
+            ("gitlab_ci", "gitlab ci", "GitLab CI", vec!["gitlab-ci"]),
+            ("jenkins", "jenkins", "Jenkins", vec![]),
+            ("postgresql", "postgresql", "PostgreSQL", vec!["postgres", "psql"]),
+            ("mysql", "mysql", "MySQL", vec!["mariadb"]),
+            ("mongodb", "mongodb", "MongoDB", vec!["mongo"]),
+            ("redis", "redis", "Redis", vec![]),
+            ("elasticsearch", "elasticsearch", "Elasticsearch", vec!["elastic", "es"]),
+            ("graphql", "graphql", "GraphQL", vec!["gql"]),
+            ("rest_api", "rest api", "REST API", vec!["restful", "rest"]),
         ];
 
-        for (name, aliases) in tools {
-            self.add_skill(name, SkillCategory::Tool, &aliases);
+        for (id, name, display_name, aliases) in tools {
+            self.add_skill(id, name, display_name, SkillCategory::Tool, &aliases);
         }
     }
 
     fn init_domains(&mut self) {
         let domains = vec![
-            ("machine learning", vec!["ml", "deep learning", "dl", "ai"]),
-            ("data science", vec!["data analysis", "analytics"]),
-            ("devops", vec!["sre", "platform engineering"]),
-            ("security", vec!["cybersecurity", "infosec", "appsec"]),
-            ("frontend", vec!["front-end", "ui", "client-side"]),
-            ("backend", vec!["back-end", "server-side"]),
-            ("fullstack", vec!["full-stack", "full stack"]),
-            ("mobile", vec!["ios", "android", "mobile development"]),
-            ("embedded", vec!["embedded systems", "iot"]),
-            ("distributed systems", vec!["microservices", "distributed"]),
-            ("databases", vec!["database design", "data modeling"]),
+            ("machine_learning", "machine learning", "Machine Learning", vec!["ml", "deep learning", "dl", "ai"]),
+            ("data_science", "data science", "Data Science", vec!["data analysis", "analytics"]),
+            ("devops", "devops", "DevOps", vec!["sre", "platform engineering"]),
+            ("security", "security", "Security", vec!["cybersecurity", "infosec", "appsec"]),
+            ("frontend", "frontend", "Frontend", vec!["front-end", "ui", "client-side"]),
+            ("backend", "backend", "Backend", vec!["back-end", "server-side"]),
+            ("fullstack", "fullstack", "Full Stack", vec!["full-stack", "full stack"]),
+            ("mobile", "mobile", "Mobile", vec!["ios", "android", "mobile development"]),
+            ("embedded", "embedded", "Embedded", vec!["embedded systems", "iot"]),
+            ("distributed_systems", "distributed systems", "Distributed Systems", vec!["microservices", "distributed"]),
+            ("databases", "databases", "Databases", vec!["database design", "data modeling"]),
         ];
 
-        for (name, aliases) in domains {
-            self.add_skill(name, SkillCategory::Domain, &aliases);
+        for (id, name, display_name, aliases) in domains {
+            self.add_skill(id, name, display_name, SkillCategory::Domain, &aliases);
         }
     }
 
     fn init_practices(&mut self) {
         let practices = vec![
-            ("testing", vec!["unit testing", "tdd", "test-driven", "integration testing"]),
-            ("documentation", vec!["docs", "technical writing"]),
-            ("code review", vec!["pr review", "pull request review"]),
-            ("ci/cd", vec!["continuous integration", "continuous deployment", "continuous delivery"]),
-            ("agile", vec!["scrum", "kanban"]),
-            ("clean code", vec!["solid", "dry", "kiss"]),
-            ("refactoring", vec![]),
-            ("debugging", vec!["troubleshooting"]),
-            ("performance optimization", vec!["perf", "optimization"]),
-            ("error handling", vec!["exception handling"]),
+            ("testing", "testing", "Testing", vec!["unit testing", "tdd", "test-driven", "integration testing"]),
+            ("documentation", "documentation", "Documentation", vec!["docs", "technical writing"]),
+            ("code_review", "code review", "Code Review", vec!["pr review", "pull request review"]),
+            ("ci/cd", "ci/cd", "CI/CD", vec!["continuous integration", "continuous deployment", "continuous delivery"]),
+            ("agile", "agile", "Agile", vec!["scrum", "kanban"]),
+            ("clean_code", "clean code", "Clean Code", vec!["solid", "dry", "kiss"]),
+            ("refactoring", "refactoring", "Refactoring", vec![]),
+            ("debugging", "debugging", "Debugging", vec!["troubleshooting"]),
+            ("performance_optimization", "performance optimization", "Performance Optimization", vec!["perf", "optimization"]),
+            ("error_handling", "error handling", "Error Handling", vec!["exception handling"]),
         ];
 
-        for (name, aliases) in practices {
-            self.add_skill(name, SkillCategory::Practice, &aliases);
+        for (id, name, display_name, aliases) in practices {
+            self.add_skill(id, name, display_name, SkillCategory::Practice, &aliases);
         }
     }
 
-    fn add_skill(&mut self, name: &str, category: SkillCategory, aliases: &[&str]) {
+    /// Registers a built-in skill. `id` is what stored ratings are keyed by,
+    /// so it must never change once released, while `name` can be edited
+    /// freely; new skills should use the snake_case form of their first name.
+    /// `display_name` is the properly cased form shown in reports.
+    fn add_skill(
+        &mut self,
+        id: &str,
+        name: &str,
+        display_name: &str,
+        category: SkillCategory,
+        aliases: &[&str],
+    ) {
         let skill = Skill {
-            id: name.to_lowercase().replace(' ', "_"),
+            id: id.to_string(),
             name: name.to_string(),
-            category,
+            display_name: display_name.to_string(),
+            category: category.clone(),
             subcategory: None,
             aliases: aliases.iter().map(|s| s.to_string()).collect(),
         };
@@ -162,14 +223,74 @@ impl SkillTaxonomy {
         self.skills.insert(name.to_lowercase(), skill);
 
         for alias in aliases {
-            self.aliases
-                .insert(alias.to_lowercase(), name.to_lowercase());
+            let targets = self.aliases.entry(alias.to_lowercase()).or_default();
+            if targets.iter().any(|(_, target)| *target == name.to_lowercase()) {
+                continue;
+            }
+            if let Some((first_category, first)) = targets.first() {
+                tracing::warn!(
+                    "Alias \"{}\" is shared by {} ({}) and {} ({}); it resolves by category, \
+                     falling back to {}",
+                    alias,
+                    first,
+                    first_category,
+                    name,
+                    category,
+                    first
+                );
+            }
+            targets.push((category.clone(), name.to_lowercase()));
         }
     }
 
+    /// Maps `alias` to `target`, ahead of any built-in mapping of the same
+    /// alias in every category. `target` may itself be an alias. Returns false, with a warning,
+    /// when `target` is not in the taxonomy; the alias still applies and
+    /// resolves to a new skill of that name.
+    pub fn add_alias(&mut self, alias: &str, target: &str) -> bool {
+        let target = self.normalize_skill_name(target);
+        let known = self.skills.contains_key(&target);
+        if !known {
+            tracing::warn!(
+                "Alias \"{}\" points to \"{}\", which is not in the taxonomy; it will be a new skill",
+                alias,
+                target
+            );
+        }
+
+        self.runtime_aliases.insert(alias.trim().to_lowercase(), target);
+        known
+    }
+
+    /// Resolves an alias to its canonical skill name. An alias shared across
+    /// categories resolves to the skill that registered it first.
     pub fn normalize_skill_name(&self, name: &str) -> String {
         let lower = name.to_lowercase();
-        self.aliases.get(&lower).cloned().unwrap_or(lower)
+        if let Some(target) = self.runtime_aliases.get(&lower) {
+            return target.clone();
+        }
+        match self.aliases.get(&lower).and_then(|targets| targets.first()) {
+            Some((_, target)) => target.clone(),
+            None => lower,
+        }
+    }
+
+    /// Like `normalize_skill_name`, but an alias shared across categories
+    /// resolves to the skill in `category` when there is one.
+    pub fn normalize_skill_name_in(&self, name: &str, category: &SkillCategory) -> String {
+        let lower = name.to_lowercase();
+        if let Some(target) = self.runtime_aliases.get(&lower) {
+            return target.clone();
+        }
+        let Some(targets) = self.aliases.get(&lower) else {
+            return lower;
+        };
+        targets
+            .iter()
+            .find(|(c, _)| c == category)
+            .or_else(|| targets.first())
+            .map(|(_, target)| target.clone())
+            .unwrap_or(lower)
     }
 
     pub fn categorize(&self, category_str: &str) -> SkillCategory {
@@ -185,10 +306,11 @@ impl SkillTaxonomy {
     }
 
     pub fn get_or_create_skill(&self, name: &str, category: SkillCategory) -> Skill {
-        let normalized = self.normalize_skill_name(name);
+        let normalized = self.normalize_skill_name_in(name, &category);
         self.skills.get(&normalized).cloned().unwrap_or_else(|| Skill {
             id: normalized.replace(' ', "_"),
             name: name.to_string(),
+            display_name: fallback_display_name(name),
             category,
             subcategory: None,
             aliases: Vec::new(),
@@ -199,6 +321,90 @@ impl SkillTaxonomy {
         let normalized = self.normalize_skill_name(name);
         self.skills.get(&normalized)
     }
+
+    /// Canonical names of the known skills mentioned in free text, such as a
+    /// bio, by name or alias in phrases of up to three words. Each skill is
+    /// listed once, in order of first mention.
+    pub fn skills_mentioned(&self, text: &str) -> Vec<String> {
+        let words: Vec<String> = text
+            .split(|c: char| c.is_whitespace() || ",;:()[]{}!?\"'|&".contains(c))
+            .map(|w| w.trim_end_matches('.').to_lowercase())
+            .filter(|w| !w.is_empty())
+            .collect();
+
+        let mut found = Vec::new();
+        for start in 0..words.len() {
+            for len in 1..=3.min(words.len() - start) {
+                let name = self.normalize_skill_name(&words[start..start + len].join(" "));
+                if self.skills.contains_key(&name) && !found.contains(&name) {
+                    found.push(name);
+                }
+            }
+        }
+        found
+    }
+}
+
+/// Parses an `ALIAS=SKILL` pair, e.g. `reactjs=react`.
+pub fn parse_alias(spec: &str) -> Result<(String, String), String> {
+    match spec.split_once('=') {
+        Some((alias, target)) if !alias.trim().is_empty() && !target.trim().is_empty() => {
+            Ok((alias.trim().to_string(), target.trim().to_string()))
+        }
+        _ => Err(format!("expected ALIAS=SKILL, e.g. reactjs=react, got '{}'", spec)),
+    }
+}
+
+/// Skills to add to the built-in taxonomy, read from the file named by the
+/// `taxonomy` key of `.gitanalyzer.toml`:
+///
+/// ```toml
+/// [[skills]]
+/// name = "zig"
+/// display_name = "Zig"
+/// category = "language"
+/// aliases = ["ziglang"]
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TaxonomyFile {
+    #[serde(default)]
+    pub skills: Vec<TaxonomyEntry>,
+}
+
+/// One `[[skills]]` entry of a `TaxonomyFile`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TaxonomyEntry {
+    pub name: String,
+    /// Defaults to `fallback_display_name` of the name
+    pub display_name: Option<String>,
+    pub category: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+impl TaxonomyFile {
+    /// Parses a taxonomy file, rejecting unknown categories and empty names.
+    pub fn parse(text: &str) -> crate::error::Result<Self> {
+        let file: TaxonomyFile = toml::from_str(text).map_err(|e| Error::Config(e.to_string()))?;
+        for entry in &file.skills {
+            if entry.name.trim().is_empty() {
+                return Err(Error::Config("taxonomy skill with an empty name".to_string()));
+            }
+            entry
+                .category
+                .parse::<SkillCategory>()
+                .map_err(|e| Error::Config(format!("skill {}: {}", entry.name, e)))?;
+        }
+        Ok(file)
+    }
+
+    pub fn load(path: &Path) -> crate::error::Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| Error::Config(format!("Cannot read {}: {}", path.display(), e)))?;
+        Self::parse(&text).map_err(|e| Error::Config(format!("{}: {}", path.display(), e)))
+    }
 }
 
 impl Default for SkillTaxonomy {
@@ -206,3 +412,114 @@ impl Default for SkillTaxonomy {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_colliding_alias_resolves_predictably() {
+        let mut taxonomy = SkillTaxonomy::new();
+        assert_eq!(taxonomy.normalize_skill_name("ES"), "elasticsearch");
+
+        // Elixir claiming "es" too must not clobber Elasticsearch
+        taxonomy.add_skill("elixir", "elixir", "Elixir", SkillCategory::Language, &["ex", "es"]);
+        assert_eq!(taxonomy.normalize_skill_name("es"), "elasticsearch");
+        assert_eq!(
+            taxonomy.normalize_skill_name_in("es", &SkillCategory::Language),
+            "elixir"
+        );
+        assert_eq!(
+            taxonomy.normalize_skill_name_in("es", &SkillCategory::Tool),
+            "elasticsearch"
+        );
+        assert_eq!(
+            taxonomy.normalize_skill_name_in("es", &SkillCategory::Framework),
+            "elasticsearch"
+        );
+        assert_eq!(
+            taxonomy.get_or_create_skill("es", SkillCategory::Language).name,
+            "elixir"
+        );
+    }
+
+    #[test]
+    fn test_runtime_aliases() {
+        let taxonomy = SkillTaxonomy::with_aliases(&[
+            parse_alias("ReactJS.dev = react.js").unwrap(),
+            parse_alias("es=elixir").unwrap(),
+        ]);
+        assert_eq!(taxonomy.normalize_skill_name("reactjs.dev"), "react");
+        assert_eq!(
+            taxonomy.get_or_create_skill("reactjs.dev", SkillCategory::Concept).category,
+            SkillCategory::Framework
+        );
+        // Overrides the built-in Elasticsearch alias, even for tools
+        assert_eq!(taxonomy.normalize_skill_name("es"), "elixir");
+        assert_eq!(taxonomy.normalize_skill_name_in("es", &SkillCategory::Tool), "elixir");
+        assert_eq!(
+            taxonomy.get_or_create_skill("es", SkillCategory::Tool).category,
+            SkillCategory::Language
+        );
+
+        assert!(parse_alias("react").is_err());
+        assert!(parse_alias("=react").is_err());
+    }
+
+    #[test]
+    fn test_taxonomy_file_adds_skills() {
+        let file = TaxonomyFile::parse(
+            r#"
+            [[skills]]
+            name = "Zig"
+            category = "language"
+            aliases = ["ziglang"]
+
+            [[skills]]
+            name = "temporal"
+            display_name = "Temporal"
+            category = "Framework"
+            "#,
+        )
+        .unwrap();
+        let taxonomy = SkillTaxonomy::with_extensions(&file, &[parse_alias("tmprl=temporal").unwrap()]);
+
+        let zig = taxonomy.get_skill("zig").unwrap();
+        assert_eq!((zig.id.as_str(), zig.display_name.as_str()), ("zig", "Zig"));
+        assert_eq!(zig.category, SkillCategory::Language);
+        assert_eq!(taxonomy.normalize_skill_name("ZigLang"), "zig");
+        // Aliases can point at added skills
+        assert_eq!(taxonomy.get_skill("temporal").unwrap().category, SkillCategory::Framework);
+        assert_eq!(taxonomy.normalize_skill_name("tmprl"), "temporal");
+        // The built-in skills are still there
+        assert!(taxonomy.get_skill("rust").is_some());
+
+        assert!(TaxonomyFile::parse("[[skills]]\nname = \"x\"\ncategory = \"gadget\"").is_err());
+        assert!(TaxonomyFile::parse("[[skills]]\nname = \" \"\ncategory = \"tool\"").is_err());
+        assert!(TaxonomyFile::parse("[[skills]]\nname = \"x\"\ncategory = \"tool\"\nlevel = 3").is_err());
+    }
+
+    #[test]
+    fn test_display_names() {
+        let taxonomy = SkillTaxonomy::new();
+        let postgres = taxonomy.get_or_create_skill("postgres", SkillCategory::Tool);
+        assert_eq!((postgres.name.as_str(), postgres.display_name.as_str()), ("postgresql", "PostgreSQL"));
+        assert_eq!(taxonomy.get_skill("c++").unwrap().display_name, "C++");
+
+        // Unknown skills are title-cased unless they already carry capitals
+        let unknown = taxonomy.get_or_create_skill("event sourcing", SkillCategory::Concept);
+        assert_eq!(unknown.display_name, "Event Sourcing");
+        let unknown = taxonomy.get_or_create_skill("gRPC", SkillCategory::Tool);
+        assert_eq!(unknown.display_name, "gRPC");
+    }
+
+    #[test]
+    fn test_skills_mentioned_in_text() {
+        let taxonomy = SkillTaxonomy::new();
+        assert_eq!(
+            taxonomy.skills_mentioned("Rust enthusiast, ML researcher. Ruby on Rails in a past life; Next.js."),
+            vec!["rust", "machine learning", "ruby", "rails", "nextjs"]
+        );
+        assert!(taxonomy.skills_mentioned("").is_empty());
+    }
+}
@@ -0,0 +1,134 @@
+use super::dependencies::is_dependency_manifest;
+
+/// Skill name registered for security-sensitive changes. Matches the
+/// canonical name in `SkillTaxonomy`, and doubles as the domain signal.
+pub const SECURITY_SKILL: &str = "security";
+
+/// Path components (split on `/`, `.`, `_` and `-`) that mark code handling
+/// authentication, cryptography or secrets, e.g. `src/auth/session.rs`.
+const SECURITY_PATH_TOKENS: &[&str] = &[
+    "auth",
+    "authn",
+    "authz",
+    "authentication",
+    "authorization",
+    "oauth",
+    "jwt",
+    "crypto",
+    "cryptography",
+    "encryption",
+    "cipher",
+    "secret",
+    "secrets",
+    "credential",
+    "credentials",
+    "password",
+    "passwords",
+    "csrf",
+    "tls",
+    "ssl",
+];
+
+/// Security libraries across ecosystems, matched as whole words on the added
+/// lines of a dependency manifest.
+const SECURITY_LIBRARIES: &[&str] = &[
+    "argon2",
+    "bcrypt",
+    "scrypt",
+    "ring",
+    "rustls",
+    "openssl",
+    "libsodium",
+    "sodiumoxide",
+    "jsonwebtoken",
+    "pyjwt",
+    "cryptography",
+    "authlib",
+    "oauthlib",
+    "passport",
+    "helmet",
+    "devise",
+    "bouncycastle",
+    "spring-security",
+    "golang.org/x/crypto",
+];
+
+/// Why a change to `path` (relative to the repository root) with the given
+/// diff is security-sensitive, or `None` if it is not. Covers secrets files
+/// such as `.env`, paths about auth, crypto or secrets, and security
+/// libraries added to a dependency manifest.
+pub fn detect_security_change(path: &str, diff: &str) -> Option<String> {
+    let lower = path.to_lowercase();
+    let name = lower.rsplit('/').next().unwrap_or(&lower);
+
+    if name == ".env" || name.starts_with(".env.") {
+        return Some(format!("Changes secrets file {}", path));
+    }
+
+    if is_dependency_manifest(path) {
+        let library = diff
+            .lines()
+            .filter_map(|line| line.strip_prefix('+'))
+            .filter(|added| !added.starts_with("++"))
+            .find_map(|added| {
+                let added = added.to_lowercase();
+                SECURITY_LIBRARIES
+                    .iter()
+                    .find(|library| contains_word(&added, library))
+            });
+        if let Some(library) = library {
+            return Some(format!("Adds security library {} in {}", library, path));
+        }
+    }
+
+    lower
+        .split(['/', '.', '_', '-'])
+        .any(|token| SECURITY_PATH_TOKENS.contains(&token))
+        .then(|| format!("Changes security-sensitive file {}", path))
+}
+
+/// Whether `word` occurs in `line` without a letter, digit, `-` or `_` on
+/// either side, so `ring` does not match `string`.
+fn contains_word(line: &str, word: &str) -> bool {
+    let is_word_char = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+    line.match_indices(word).any(|(start, _)| {
+        let before = line[..start].chars().next_back();
+        let after = line[start + word.len()..].chars().next();
+        !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_security_sensitive_changes() {
+        for path in [
+            "src/auth/session.rs",
+            "lib/crypto.py",
+            "config/secrets.yml",
+            "api/oauth_callback.go",
+            ".env",
+            "deploy/.env.production",
+        ] {
+            assert!(detect_security_change(path, "").is_some(), "{} should be flagged", path);
+        }
+        assert_eq!(
+            detect_security_change("Cargo.toml", "+argon2 = \"0.5\"\n"),
+            Some("Adds security library argon2 in Cargo.toml".to_string())
+        );
+        assert!(detect_security_change("web/package.json", "+    \"helmet\": \"^7.0.0\",\n").is_some());
+    }
+
+    #[test]
+    fn test_other_changes_are_not_security_sensitive() {
+        for path in ["src/author.rs", "docs/authoring.md", "src/env.rs", "README.md"] {
+            assert!(detect_security_change(path, "").is_none(), "{} should not be flagged", path);
+        }
+        // Removed libraries and near-miss names do not count
+        assert!(detect_security_change("Cargo.toml", "-ring = \"0.17\"\n").is_none());
+        assert!(detect_security_change("package.json", "+    \"string-width\": \"^5.0.0\",\n").is_none());
+        assert!(detect_security_change("Cargo.toml", "+ring-buffer = \"1\"\n").is_none());
+    }
+}
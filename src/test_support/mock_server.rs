@@ -0,0 +1,193 @@
+//! A minimal HTTP server for tests of the GitHub and Claude clients. It
+//! answers one connection per canned response, in order, and records each
+//! request it received.
+
+use std::sync::{Arc, Mutex};
+
+use reqwest::StatusCode;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+/// A request as the server received it.
+#[derive(Debug, Clone)]
+pub struct MockRequest {
+    pub method: String,
+    /// Path and query, e.g. `/commits?per_page=2`
+    pub target: String,
+    /// Header names are lowercased
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl MockRequest {
+    /// The value of the header `name`, ignoring case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// A canned response.
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    /// `None` closes the connection without answering
+    status: Option<StatusCode>,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl MockResponse {
+    /// `200 OK` with a JSON body.
+    pub fn json(body: impl Into<String>) -> Self {
+        Self::status(StatusCode::OK)
+            .header("Content-Type", "application/json")
+            .body(body)
+    }
+
+    /// `status` with an empty body.
+    pub fn status(status: StatusCode) -> Self {
+        Self {
+            status: Some(status),
+            headers: Vec::new(),
+            body: String::new(),
+        }
+    }
+
+    /// Reads the request, then closes the connection without answering.
+    pub fn hang_up() -> Self {
+        Self {
+            status: None,
+            headers: Vec::new(),
+            body: String::new(),
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.headers.push((name.to_string(), value.into()));
+        self
+    }
+
+    pub fn body(mut self, body: impl Into<String>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    fn to_http(&self) -> Option<String> {
+        let status = self.status?;
+        let mut response = format!(
+            "HTTP/1.1 {} {}\r\n",
+            status.as_u16(),
+            status.canonical_reason().unwrap_or_default()
+        );
+        for (name, value) in &self.headers {
+            response.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        response.push_str(&format!(
+            "Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.body.len(),
+            self.body
+        ));
+        Some(response)
+    }
+}
+
+pub struct MockServer {
+    url: String,
+    requests: Arc<Mutex<Vec<MockRequest>>>,
+    task: JoinHandle<()>,
+}
+
+impl MockServer {
+    /// Serves `responses`, one per connection, in order. Once they run out,
+    /// further connections are refused.
+    pub async fn start(responses: Vec<MockResponse>) -> Self {
+        Self::start_with(|_| responses).await
+    }
+
+    /// Like `start`, with responses built from the server's URL, e.g. to
+    /// link pages to each other.
+    pub async fn start_with(responses: impl FnOnce(&str) -> Vec<MockResponse>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let responses = responses(&url);
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let seen = requests.clone();
+        let task = tokio::spawn(async move {
+            for response in responses {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let request = read_request(&mut socket).await;
+                seen.lock().unwrap().push(request);
+                if let Some(http) = response.to_http() {
+                    socket.write_all(http.as_bytes()).await.unwrap();
+                }
+            }
+        });
+        Self { url, requests, task }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// The requests received so far, in order.
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Waits until every response has been served and returns the requests.
+    pub async fn finish(self) -> Vec<MockRequest> {
+        self.task.await.unwrap();
+        let requests = self.requests.lock().unwrap().clone();
+        requests
+    }
+}
+
+/// Reads the request head and as much body as `Content-Length` announces.
+async fn read_request(socket: &mut tokio::net::TcpStream) -> MockRequest {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 4096];
+    let body_start = loop {
+        if let Some(i) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+            break i + 4;
+        }
+        let n = socket.read(&mut buf).await.unwrap();
+        if n == 0 {
+            break request.len();
+        }
+        request.extend_from_slice(&buf[..n]);
+    };
+
+    let head = String::from_utf8_lossy(&request[..body_start]).to_string();
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or_default().split(' ');
+    let method = request_line.next().unwrap_or_default().to_string();
+    let target = request_line.next().unwrap_or_default().to_string();
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+        .collect();
+
+    let length: usize = headers
+        .iter()
+        .find(|(name, _)| name == "content-length")
+        .and_then(|(_, value)| value.parse().ok())
+        .unwrap_or(0);
+    while request.len() < body_start + length {
+        let n = socket.read(&mut buf).await.unwrap();
+        if n == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..n]);
+    }
+
+    MockRequest {
+        method,
+        target,
+        headers,
+        body: String::from_utf8_lossy(&request[body_start..]).to_string(),
+    }
+}
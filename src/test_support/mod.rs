@@ -0,0 +1,135 @@
+//! Fixtures shared by the unit tests of several modules.
+
+pub mod mock_server;
+
+pub use mock_server::{MockResponse, MockServer};
+
+use crate::models::skill::{fallback_display_name, AggregatedSkill};
+use crate::models::{
+    Commit, CommitAuthor, CommitDetails, CommitStats, FileChange, GitHubUser, ProfileSummary,
+    Repository, RepositoryOwner, Skill, SkillCategory, SkillEvidence, SkillRating, SkillTrend,
+    UserProfile, PROFILE_SCHEMA_VERSION,
+};
+
+/// A GitHub user with nothing but a login.
+pub fn user(login: &str) -> GitHubUser {
+    GitHubUser {
+        login: login.to_string(),
+        id: 1,
+        name: None,
+        email: None,
+        avatar_url: String::new(),
+        bio: None,
+        company: None,
+        location: None,
+        public_repos: 0,
+        followers: 0,
+        following: 0,
+        created_at: chrono::Utc::now(),
+        account_type: Default::default(),
+    }
+}
+
+/// A language skill named `name`, rated `score` without a breakdown.
+pub fn skill_rating(name: &str, score: u8) -> SkillRating {
+    SkillRating {
+        skill: Skill {
+            id: name.to_lowercase(),
+            name: name.to_string(),
+            display_name: fallback_display_name(name),
+            category: SkillCategory::Language,
+            subcategory: None,
+            aliases: Vec::new(),
+        },
+        proficiency_score: score,
+        percentile_rank: None,
+        confidence: 0.5,
+        evidence: SkillEvidence::default(),
+        trend: SkillTrend::Stable,
+        breakdown: None,
+        trend_series: Vec::new(),
+    }
+}
+
+/// A `category` skill named `name`, aggregated from no occurrences yet.
+pub fn aggregated_skill(name: &str, category: SkillCategory) -> AggregatedSkill {
+    AggregatedSkill::new(Skill {
+        id: name.to_lowercase(),
+        name: name.to_string(),
+        display_name: fallback_display_name(name),
+        category,
+        subcategory: None,
+        aliases: Vec::new(),
+    })
+}
+
+/// A profile of `login` with the given `(name, score)` skills, in order.
+pub fn profile(login: &str, skills: &[(&str, u8)]) -> UserProfile {
+    UserProfile {
+        schema_version: PROFILE_SCHEMA_VERSION,
+        user: user(login),
+        repositories: Vec::new(),
+        total_commits_analyzed: 0,
+        analysis_date: chrono::Utc::now(),
+        skills: skills
+            .iter()
+            .map(|&(name, score)| skill_rating(name, score))
+            .collect(),
+        summary: ProfileSummary::default(),
+        incomplete: false,
+        as_of: None,
+    }
+}
+
+/// A public, non-fork repository named `full_name`, e.g. "octocat/hello".
+pub fn repository(full_name: &str) -> Repository {
+    let (owner, name) = full_name.split_once('/').unwrap_or(("octocat", full_name));
+    Repository {
+        id: 1,
+        name: name.to_string(),
+        full_name: full_name.to_string(),
+        description: None,
+        language: None,
+        stargazers_count: 0,
+        forks_count: 0,
+        fork: false,
+        created_at: chrono::Utc::now(),
+        updated_at: chrono::Utc::now(),
+        owner: RepositoryOwner {
+            login: owner.to_string(),
+        },
+    }
+}
+
+/// A commit by octocat, made now, changing each `(filename, patch)` in
+/// `files`.
+pub fn commit(sha: &str, files: &[(&str, &str)]) -> Commit {
+    let files: Vec<FileChange> = files
+        .iter()
+        .map(|&(filename, patch)| FileChange {
+            filename: filename.to_string(),
+            status: "modified".to_string(),
+            additions: patch.lines().count() as u32,
+            deletions: 0,
+            patch: Some(patch.to_string()),
+        })
+        .collect();
+    let additions = files.iter().map(|f| f.additions).sum();
+    Commit {
+        sha: sha.to_string(),
+        commit: CommitDetails {
+            message: format!("Commit {}", sha),
+            author: CommitAuthor {
+                name: "octocat".to_string(),
+                email: "octocat@example.com".to_string(),
+                date: chrono::Utc::now().into(),
+            },
+        },
+        stats: Some(CommitStats {
+            additions,
+            deletions: 0,
+            total: additions,
+        }),
+        files: Some(files),
+    }
+}